@@ -0,0 +1,57 @@
+//! Compare `TimeMsRangeIndex` queries with a naive linear scan.
+//!
+//! Run with `cargo bench --bench range_index`
+use std::time::Instant;
+use time_ms_conversions::{TimeMsRange, TimeMsRangeIndex};
+
+const RANGES: usize = 50_000;
+const QUERIES: usize = 10_000;
+
+fn main() {
+    // Maintenance windows of up to an hour scattered over a year
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let year_ms = 365 * 24 * 60 * 60 * 1000i64;
+    let ranges: Vec<TimeMsRange> = (0..RANGES)
+        .map(|_| {
+            let start = (next() % year_ms as u64) as i64;
+            let len = (next() % 3_600_000) as i64;
+            TimeMsRange::new(start, start + len)
+        })
+        .collect();
+    let queries: Vec<i64> = (0..QUERIES)
+        .map(|_| (next() % year_ms as u64) as i64)
+        .collect();
+
+    let start = Instant::now();
+    let idx = TimeMsRangeIndex::build(ranges.clone());
+    let build = start.elapsed();
+
+    let start = Instant::now();
+    let mut naive_hits = 0usize;
+    for &t in &queries {
+        naive_hits += ranges.iter().filter(|r| r.contains(t)).count();
+    }
+    let naive = start.elapsed();
+
+    let start = Instant::now();
+    let mut idx_hits = 0usize;
+    for &t in &queries {
+        idx_hits += idx.containing(t).count();
+    }
+    let indexed = start.elapsed();
+
+    assert_eq!(naive_hits, idx_hits);
+    println!("{RANGES} ranges, {QUERIES} containing queries, {idx_hits} hits");
+    println!("build:   {build:?}");
+    println!("naive:   {naive:?} ({:?}/query)", naive / QUERIES as u32);
+    println!(
+        "indexed: {indexed:?} ({:?}/query)",
+        indexed / QUERIES as u32
+    );
+}
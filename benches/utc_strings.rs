@@ -0,0 +1,57 @@
+//! Compare `time_ms_slice_to_utc_strings` and the `_into` variant with
+//! calling `time_ms_to_utc_string` in a loop.
+//!
+//! Run with `cargo bench --bench utc_strings`
+use std::time::Instant;
+use time_ms_conversions::{
+    time_ms_slice_to_utc_strings, time_ms_slice_to_utc_strings_into, time_ms_to_utc_string,
+};
+
+const TIMES: usize = 1_000_000;
+const BATCHES: usize = 5;
+
+fn main() {
+    // A column of timestamps a few hundred ms apart starting 2022-07-15
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let mut time_ms = 1_657_843_200_000i64;
+    let times: Vec<i64> = (0..TIMES)
+        .map(|_| {
+            time_ms += (next() % 500) as i64;
+            time_ms
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut looped = Vec::new();
+    for _ in 0..BATCHES {
+        looped = times.iter().map(|&t| time_ms_to_utc_string(t)).collect();
+    }
+    let loop_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut batched = Vec::new();
+    for _ in 0..BATCHES {
+        batched = time_ms_slice_to_utc_strings(&times);
+    }
+    let batch_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut reused = Vec::new();
+    for _ in 0..BATCHES {
+        time_ms_slice_to_utc_strings_into(&times, &mut reused);
+    }
+    let into_time = start.elapsed();
+
+    assert_eq!(looped, batched);
+    assert_eq!(looped, reused);
+    println!("{BATCHES} batches of {TIMES} time_ms");
+    println!("loop:  {loop_time:?}");
+    println!("batch: {batch_time:?}");
+    println!("into:  {into_time:?}");
+}
@@ -0,0 +1,890 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{MS_PER_DAY, MS_PER_HOUR, MS_PER_MIN, MS_PER_SEC};
+
+// Floor time_ms to a multiple of granularity_ms, saturating at i64::MIN
+fn floor_to(time_ms: i64, granularity_ms: i64) -> i64 {
+    let floor = time_ms as i128 - time_ms.rem_euclid(granularity_ms) as i128;
+    floor.max(i64::MIN as i128) as i64
+}
+
+/// Reduce the precision of time_ms to `granularity_ms`, such as for
+/// privacy preserving exports.
+///
+/// time_ms is floored, not rounded, to the start of its bucket so a
+/// coarsened value never postdates the original. If `jitter` is provided
+/// it's called with the bucket start and returns an offset into the bucket,
+/// offsets outside `[0, granularity_ms)` are clamped into it so the result
+/// is always within the original's bucket. The jitter should be
+/// deterministic, such as a keyed hash of the bucket start, so coarsening
+/// is repeatable.
+///
+/// # Panics
+///
+/// If `granularity_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::coarsen;
+///
+/// const HOUR: i64 = 60 * 60 * 1000;
+/// assert_eq!(coarsen(HOUR + 1234, HOUR, None::<&mut fn(i64) -> i64>), HOUR);
+/// assert_eq!(coarsen(-1, HOUR, None::<&mut fn(i64) -> i64>), -HOUR);
+///
+/// let mut jitter = |bucket_start: i64| bucket_start.rem_euclid(7) * 1000;
+/// assert_eq!(coarsen(HOUR + 1234, HOUR, Some(&mut jitter)), HOUR + 5000);
+/// ```
+pub fn coarsen(
+    time_ms: i64,
+    granularity_ms: i64,
+    jitter: Option<&mut impl FnMut(i64) -> i64>,
+) -> i64 {
+    assert!(granularity_ms > 0, "coarsen: granularity_ms must be > 0");
+
+    let bucket_start = floor_to(time_ms, granularity_ms);
+    match jitter {
+        Some(jitter) => {
+            let offset = jitter(bucket_start).clamp(0, granularity_ms - 1);
+            bucket_start.saturating_add(offset)
+        }
+        None => bucket_start,
+    }
+}
+
+/// [`coarsen`] each of `times` in place
+///
+/// # Panics
+///
+/// If `granularity_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::coarsen_slice;
+///
+/// let mut times = [999, 1000, 1999, -1];
+/// coarsen_slice(&mut times, 1000, None::<&mut fn(i64) -> i64>);
+/// assert_eq!(times, [0, 1000, 1000, -1000]);
+/// ```
+pub fn coarsen_slice(
+    times: &mut [i64],
+    granularity_ms: i64,
+    mut jitter: Option<&mut impl FnMut(i64) -> i64>,
+) {
+    for time_ms in times.iter_mut() {
+        *time_ms = coarsen(*time_ms, granularity_ms, jitter.as_deref_mut());
+    }
+}
+
+/// The number of whole `tick_ms` sized ticks since the epoch, floored
+/// so negative time_ms are in the tick before the epoch. This is the
+/// general bucketing key, a tick_ms of 1000 gives Unix seconds.
+///
+/// # Panics
+///
+/// If `tick_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_ticks;
+///
+/// assert_eq!(time_ms_to_ticks(1999, 1000), 1);
+/// assert_eq!(time_ms_to_ticks(-1, 1000), -1);
+/// ```
+pub fn time_ms_to_ticks(time_ms: i64, tick_ms: i64) -> i64 {
+    assert!(tick_ms > 0, "time_ms_to_ticks: tick_ms must be > 0");
+    time_ms.div_euclid(tick_ms)
+}
+
+/// The time_ms of the start of tick number `ticks` of `tick_ms` each,
+/// saturating at i64::MIN and i64::MAX.
+///
+/// # Panics
+///
+/// If `tick_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{ticks_to_time_ms, time_ms_to_ticks};
+///
+/// assert_eq!(ticks_to_time_ms(-1, 60_000), -60_000);
+/// assert_eq!(ticks_to_time_ms(time_ms_to_ticks(61_234, 60_000), 60_000), 60_000);
+/// ```
+pub fn ticks_to_time_ms(ticks: i64, tick_ms: i64) -> i64 {
+    assert!(tick_ms > 0, "ticks_to_time_ms: tick_ms must be > 0");
+    ticks.saturating_mul(tick_ms)
+}
+
+/// True if time_ms is an exact multiple of `interval_ms` from the epoch,
+/// the start of its interval.
+///
+/// # Panics
+///
+/// If `interval_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::is_aligned_to_interval;
+///
+/// assert!(is_aligned_to_interval(3_600_000, 3_600_000));
+/// assert!(!is_aligned_to_interval(3_600_001, 3_600_000));
+/// ```
+pub fn is_aligned_to_interval(time_ms: i64, interval_ms: i64) -> bool {
+    misalignment_ms(time_ms, interval_ms) == 0
+}
+
+/// The offset of time_ms into its `interval_ms` sized interval, always
+/// in `[0, interval_ms)` since intervals are floored so -1 is
+/// `interval_ms - 1` into the interval before the epoch.
+///
+/// # Panics
+///
+/// If `interval_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::misalignment_ms;
+///
+/// assert_eq!(misalignment_ms(3_600_001, 3_600_000), 1);
+/// assert_eq!(misalignment_ms(-1, 1000), 999);
+/// ```
+pub fn misalignment_ms(time_ms: i64, interval_ms: i64) -> i64 {
+    assert!(interval_ms > 0, "misalignment_ms: interval_ms must be > 0");
+    time_ms.rem_euclid(interval_ms)
+}
+
+/// The signed offset of time_ms from the nearest tick of the schedule
+/// `schedule_start_ms + n * period_ms`, how early, negative, or late,
+/// positive, it is versus its expected slot.
+///
+/// The offset is in `(-period_ms / 2, period_ms / 2]`, so a time exactly
+/// half way between two ticks is late for the earlier tick rather than
+/// early for the later one. The ticks continue before `schedule_start_ms`
+/// and the arithmetic is floored in i128 so it doesn't overflow.
+///
+/// # Panics
+///
+/// If `period_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::phase_offset_ms;
+///
+/// assert_eq!(phase_offset_ms(1_100, 0, 1_000), 100);
+/// assert_eq!(phase_offset_ms(900, 0, 1_000), -100);
+/// assert_eq!(phase_offset_ms(1_500, 0, 1_000), 500);
+/// ```
+pub fn phase_offset_ms(time_ms: i64, schedule_start_ms: i64, period_ms: i64) -> i64 {
+    assert!(period_ms > 0, "phase_offset_ms: period_ms must be > 0");
+    let period = period_ms as i128;
+    let offset = (time_ms as i128 - schedule_start_ms as i128).rem_euclid(period);
+
+    // The magnitude is at most period_ms / 2 so it fits in an i64
+    if 2 * offset <= period {
+        offset as i64
+    } else {
+        (offset - period) as i64
+    }
+}
+
+/// Floor time_ms to the start of its `interval_ms` sized interval, a
+/// multiple of `interval_ms` from the epoch, such as for candlestick
+/// buckets of 5 minutes, 300_000, or an hour, 3_600_000. Negative time_ms
+/// are floored too, so -1 is aligned to `-interval_ms`, and the result
+/// saturates at i64::MIN.
+///
+/// # Panics
+///
+/// If `interval_ms <= 0`, there's no sensible alignment
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::align_time_ms;
+///
+/// assert_eq!(align_time_ms(1_657_886_459_123, 300_000), 1_657_886_400_000);
+/// assert_eq!(align_time_ms(-1, 300_000), -300_000);
+/// ```
+pub fn align_time_ms(time_ms: i64, interval_ms: i64) -> i64 {
+    assert!(interval_ms > 0, "align_time_ms: interval_ms must be > 0");
+    floor_to(time_ms, interval_ms)
+}
+
+/// The multiples of `interval_ms` in `(from_ms, to_ms]`, in ascending
+/// order, the bucket boundaries crossed moving from `from_ms` to `to_ms`.
+///
+/// `from_ms` is excluded as it was already accounted for and `to_ms` is
+/// included, so consecutive calls with `(a, b]` then `(b, c]` report each
+/// boundary exactly once. Empty if `from_ms == to_ms`.
+///
+/// # Panics
+///
+/// If `interval_ms <= 0` or `to_ms < from_ms`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::boundaries_crossed;
+///
+/// assert_eq!(boundaries_crossed(500, 2500, 1000), [1000, 2000]);
+/// assert_eq!(boundaries_crossed(1000, 2000, 1000), [2000]);
+/// assert_eq!(boundaries_crossed(-1500, -500, 1000), [-1000]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn boundaries_crossed(from_ms: i64, to_ms: i64, interval_ms: i64) -> Vec<i64> {
+    assert!(
+        interval_ms > 0,
+        "boundaries_crossed: interval_ms must be > 0"
+    );
+    assert!(
+        to_ms >= from_ms,
+        "boundaries_crossed: to_ms must be >= from_ms"
+    );
+
+    // Stepped in i128 so the boundary after from_ms can't overflow and an
+    // interval_ms beyond usize::MAX, on a 32 bit target, isn't truncated
+    let first = from_ms as i128 - from_ms.rem_euclid(interval_ms) as i128 + interval_ms as i128;
+    core::iter::successors(Some(first), |boundary| Some(boundary + interval_ms as i128))
+        .take_while(|&boundary| boundary <= to_ms as i128)
+        .map(|boundary| boundary as i64)
+        .collect()
+}
+
+/// The start and count of the `bucket_ms` sized bucket holding the most
+/// of `times`, such as the peak hour of activity, or None if `times` is
+/// empty.
+///
+/// Buckets are aligned as [`align_time_ms`] and a tie is won by the
+/// earliest bucket. `times` need not be sorted.
+///
+/// # Panics
+///
+/// If `bucket_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::busiest_bucket;
+///
+/// let times = [100, 1100, 1200, 1900, 2500, -1];
+/// assert_eq!(busiest_bucket(&times, 1000), Some((1000, 3)));
+/// assert_eq!(busiest_bucket(&[], 1000), None);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn busiest_bucket(times: &[i64], bucket_ms: i64) -> Option<(i64, u64)> {
+    assert!(bucket_ms > 0, "busiest_bucket: bucket_ms must be > 0");
+
+    let mut ticks: Vec<i64> = times
+        .iter()
+        .map(|&time_ms| time_ms_to_ticks(time_ms, bucket_ms))
+        .collect();
+    ticks.sort_unstable();
+
+    // Runs of equal ticks are the buckets in ascending order so only a
+    // strictly larger count replaces the earliest busiest
+    let mut busiest: Option<(i64, u64)> = None;
+    let mut start = 0;
+    while start < ticks.len() {
+        let tick = ticks[start];
+        let end = start + ticks[start..].iter().take_while(|&&t| t == tick).count();
+        let count = (end - start) as u64;
+        if !matches!(busiest, Some((_, most)) if most >= count) {
+            busiest = Some((tick, count));
+        }
+        start = end;
+    }
+
+    busiest.map(|(tick, count)| (ticks_to_time_ms(tick, bucket_ms), count))
+}
+
+/// The start of the UTC day of time_ms, floored so -1 is the start of
+/// 1969-12-31
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::truncate_time_ms_to_day;
+///
+/// assert_eq!(truncate_time_ms_to_day(1_657_886_400_123), 1_657_843_200_000);
+/// assert_eq!(truncate_time_ms_to_day(-1), -86_400_000);
+/// ```
+pub fn truncate_time_ms_to_day(time_ms: i64) -> i64 {
+    floor_to(time_ms, MS_PER_DAY)
+}
+
+/// The start of the UTC hour of time_ms, floored so -1 is the start of
+/// 1969-12-31T23:00:00Z
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::truncate_time_ms_to_hour;
+///
+/// assert_eq!(truncate_time_ms_to_hour(1_657_886_400_123), 1_657_886_400_000);
+/// assert_eq!(truncate_time_ms_to_hour(-1), -3_600_000);
+/// ```
+pub fn truncate_time_ms_to_hour(time_ms: i64) -> i64 {
+    floor_to(time_ms, MS_PER_HOUR)
+}
+
+/// The start of the UTC minute of time_ms, floored so -1 is the start of
+/// 1969-12-31T23:59:00Z
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::truncate_time_ms_to_minute;
+///
+/// assert_eq!(truncate_time_ms_to_minute(1_657_886_459_123), 1_657_886_400_000);
+/// assert_eq!(truncate_time_ms_to_minute(-1), -60_000);
+/// ```
+pub fn truncate_time_ms_to_minute(time_ms: i64) -> i64 {
+    floor_to(time_ms, MS_PER_MIN)
+}
+
+/// A calendar unit of UTC time, see [`floor_time_ms_to`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl TimeUnit {
+    /// The length of the unit in milli-seconds, UTC has no leap seconds
+    /// or daylight saving time so they're all fixed
+    pub const fn ms(self) -> i64 {
+        match self {
+            TimeUnit::Second => MS_PER_SEC,
+            TimeUnit::Minute => MS_PER_MIN,
+            TimeUnit::Hour => MS_PER_HOUR,
+            TimeUnit::Day => MS_PER_DAY,
+        }
+    }
+}
+
+/// The start of the UTC `unit` containing time_ms
+///
+/// time_ms is floored, not truncated toward zero, so pre-epoch times
+/// go to the start of their own unit. For `TimeUnit::Day` that's the
+/// prior UTC midnight, `time_ms / 86_400_000` would be a day late.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{floor_time_ms_to, TimeUnit};
+///
+/// assert_eq!(floor_time_ms_to(1_657_886_459_123, TimeUnit::Minute), 1_657_886_400_000);
+///
+/// // 1969-12-31T12:00:00Z floors to 1969-12-31T00:00:00Z
+/// assert_eq!(floor_time_ms_to(-43_200_000, TimeUnit::Day), -86_400_000);
+/// ```
+pub fn floor_time_ms_to(time_ms: i64, unit: TimeUnit) -> i64 {
+    floor_to(time_ms, unit.ms())
+}
+
+/// The end of the UTC `unit` containing time_ms, the start of the next
+/// unit, or time_ms if it's already at the start of a unit. Such as for
+/// the inclusive end of a window, saturating at i64::MAX.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{ceil_time_ms_to, TimeUnit};
+///
+/// assert_eq!(ceil_time_ms_to(86_399_999, TimeUnit::Day), 86_400_000);
+/// assert_eq!(ceil_time_ms_to(86_400_000, TimeUnit::Day), 86_400_000);
+/// assert_eq!(ceil_time_ms_to(-86_399_999, TimeUnit::Day), 0);
+/// ```
+pub fn ceil_time_ms_to(time_ms: i64, unit: TimeUnit) -> i64 {
+    // i128 as -i64::MIN overflows
+    let time_ms = time_ms as i128;
+    let ceil = time_ms + (-time_ms).rem_euclid(unit.ms() as i128);
+    ceil.min(i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NO_JITTER: Option<&mut fn(i64) -> i64> = None;
+    const MIN: i64 = 60_000;
+
+    #[test]
+    fn test_coarsen() {
+        assert_eq!(coarsen(0, MIN, NO_JITTER), 0);
+        assert_eq!(coarsen(59_999, MIN, NO_JITTER), 0);
+        assert_eq!(coarsen(60_000, MIN, NO_JITTER), 60_000);
+        assert_eq!(
+            coarsen(1_657_877_459_123, MIN, NO_JITTER),
+            1_657_877_400_000
+        );
+        assert_eq!(coarsen(1234, 1, NO_JITTER), 1234);
+    }
+
+    #[test]
+    fn test_coarsen_negative() {
+        assert_eq!(coarsen(-1, MIN, NO_JITTER), -60_000);
+        assert_eq!(coarsen(-60_000, MIN, NO_JITTER), -60_000);
+        assert_eq!(coarsen(-60_001, MIN, NO_JITTER), -120_000);
+        assert_eq!(coarsen(i64::MIN, 3, NO_JITTER), i64::MIN);
+        assert_eq!(coarsen(i64::MAX, 2, NO_JITTER), i64::MAX - 1);
+    }
+
+    #[test]
+    fn test_coarsen_with_jitter() {
+        let mut calls = 0;
+        let mut jitter = |bucket_start: i64| {
+            calls += 1;
+            (bucket_start / MIN).rem_euclid(60) * 1000
+        };
+        assert_eq!(coarsen(61_000, MIN, Some(&mut jitter)), 61_000);
+        assert_eq!(coarsen(120_500, MIN, Some(&mut jitter)), 122_000);
+        assert_eq!(coarsen(-1, MIN, Some(&mut jitter)), -60_000 + 59_000);
+
+        // The same bucket always gets the same jitter
+        assert_eq!(
+            coarsen(120_000, MIN, Some(&mut jitter)),
+            coarsen(179_999, MIN, Some(&mut jitter))
+        );
+        assert_eq!(calls, 5);
+    }
+
+    #[test]
+    fn test_coarsen_misbehaving_jitter() {
+        let mut too_big = |_| MIN * 10;
+        assert_eq!(coarsen(61_000, MIN, Some(&mut too_big)), 119_999);
+
+        let mut negative = |_| -5;
+        assert_eq!(coarsen(61_000, MIN, Some(&mut negative)), 60_000);
+
+        let mut extreme = |_| i64::MAX;
+        assert_eq!(coarsen(i64::MAX, MIN, Some(&mut extreme)), i64::MAX);
+    }
+
+    #[test]
+    fn test_coarsen_slice() {
+        let mut times = [59_999, 60_000, -1, 1_657_877_459_123];
+        coarsen_slice(&mut times, MIN, NO_JITTER);
+        assert_eq!(times, [0, 60_000, -60_000, 1_657_877_400_000]);
+
+        let mut times = [1000, 2000, 61_000];
+        let mut jitter = |bucket_start: i64| bucket_start / 1000 + 7;
+        coarsen_slice(&mut times, MIN, Some(&mut jitter));
+        assert_eq!(times, [7, 7, 60_067]);
+    }
+
+    #[test]
+    #[should_panic(expected = "granularity_ms must be > 0")]
+    fn test_coarsen_zero_granularity() {
+        coarsen(0, 0, NO_JITTER);
+    }
+
+    #[test]
+    fn test_time_ms_to_ticks_seconds() {
+        assert_eq!(time_ms_to_ticks(0, 1000), 0);
+        assert_eq!(time_ms_to_ticks(999, 1000), 0);
+        assert_eq!(time_ms_to_ticks(1000, 1000), 1);
+        assert_eq!(time_ms_to_ticks(1_657_877_459_123, 1000), 1_657_877_459);
+        assert_eq!(ticks_to_time_ms(1_657_877_459, 1000), 1_657_877_459_000);
+    }
+
+    #[test]
+    fn test_time_ms_to_ticks_minutes() {
+        assert_eq!(time_ms_to_ticks(59_999, MIN), 0);
+        assert_eq!(time_ms_to_ticks(60_000, MIN), 1);
+        assert_eq!(time_ms_to_ticks(1_657_877_459_123, MIN), 27_631_290);
+        assert_eq!(ticks_to_time_ms(27_631_290, MIN), 1_657_877_400_000);
+    }
+
+    #[test]
+    fn test_time_ms_to_ticks_negative() {
+        assert_eq!(time_ms_to_ticks(-1, 1000), -1);
+        assert_eq!(time_ms_to_ticks(-1000, 1000), -1);
+        assert_eq!(time_ms_to_ticks(-1001, 1000), -2);
+        assert_eq!(time_ms_to_ticks(-1, MIN), -1);
+        assert_eq!(ticks_to_time_ms(-2, 1000), -2000);
+        assert_eq!(time_ms_to_ticks(i64::MIN, 1), i64::MIN);
+    }
+
+    #[test]
+    fn test_ticks_to_time_ms_saturates() {
+        assert_eq!(ticks_to_time_ms(i64::MAX, 1000), i64::MAX);
+        assert_eq!(ticks_to_time_ms(i64::MIN, 1000), i64::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "tick_ms must be > 0")]
+    fn test_time_ms_to_ticks_zero_tick() {
+        time_ms_to_ticks(0, 0);
+    }
+
+    #[test]
+    fn test_is_aligned_to_interval() {
+        const HOUR: i64 = 60 * MIN;
+        assert!(is_aligned_to_interval(3_600_000, HOUR));
+        assert!(!is_aligned_to_interval(3_600_001, HOUR));
+        assert_eq!(misalignment_ms(3_600_001, HOUR), 1);
+        assert!(is_aligned_to_interval(0, HOUR));
+        assert!(is_aligned_to_interval(1234, 1));
+        assert_eq!(misalignment_ms(HOUR - 1, HOUR), HOUR - 1);
+    }
+
+    #[test]
+    fn test_is_aligned_to_interval_negative() {
+        assert!(is_aligned_to_interval(-60_000, MIN));
+        assert!(!is_aligned_to_interval(-1, MIN));
+        assert_eq!(misalignment_ms(-1, MIN), MIN - 1);
+        assert_eq!(misalignment_ms(-60_001, MIN), MIN - 1);
+        assert_eq!(misalignment_ms(i64::MIN, 2), 0);
+        assert_eq!(misalignment_ms(i64::MAX, 2), 1);
+
+        // Consistent with coarsen
+        for t in [-123_456, -1, 0, 59_999, 1_657_877_459_123] {
+            assert_eq!(coarsen(t, MIN, NO_JITTER) + misalignment_ms(t, MIN), t);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "interval_ms must be > 0")]
+    fn test_is_aligned_to_interval_negative_interval() {
+        is_aligned_to_interval(0, -1000);
+    }
+
+    #[test]
+    fn test_phase_offset_ms() {
+        let cases = [
+            // time_ms, schedule_start_ms, period_ms, expected
+            (1_100, 0, 1_000, 100),
+            (900, 0, 1_000, -100),
+            (1_000, 0, 1_000, 0),
+            // The tie is late for the earlier tick
+            (1_500, 0, 1_000, 500),
+            (1_501, 0, 1_000, -499),
+            (-500, 0, 1_000, 500),
+            (-501, 0, 1_000, 499),
+            // Before the epoch and the schedule start
+            (-1, 0, 1_000, -1),
+            (-1_100, 0, 1_000, -100),
+            (-900, 0, 1_000, 100),
+            (50, 250, 1_000, -200),
+            (-2_000, 250, 1_000, -250),
+            // An odd period has no tie
+            (500, 0, 1_001, 500),
+            (501, 0, 1_001, -500),
+            (3, 0, 1, 0),
+            (3, 0, 2, 1),
+        ];
+        for (time_ms, start, period, expected) in cases {
+            assert_eq!(
+                phase_offset_ms(time_ms, start, period),
+                expected,
+                "{time_ms} {start} {period}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_phase_offset_ms_limits() {
+        // The difference doesn't fit an i64
+        assert_eq!(
+            phase_offset_ms(i64::MAX, i64::MIN, 1_000),
+            (u64::MAX % 1_000) as i64 - 1_000
+        );
+        assert_eq!(
+            phase_offset_ms(i64::MIN, i64::MAX, 1_000),
+            1_000 - (u64::MAX % 1_000) as i64
+        );
+        assert_eq!(phase_offset_ms(i64::MAX, 0, i64::MAX), 0);
+        assert_eq!(phase_offset_ms(i64::MIN, 0, i64::MAX), -1);
+        assert_eq!(
+            phase_offset_ms(i64::MAX / 2 + 1, 0, i64::MAX),
+            -(i64::MAX / 2)
+        );
+
+        // Every time is within half a period of a tick
+        for t in [-123_456_789, -1, 0, 1, 1_657_886_459_123] {
+            for period in [1, 2, 3, 1_000, 1_001, 3_600_000] {
+                let offset = phase_offset_ms(t, 1_657_886_400_000, period);
+                assert!(-period < 2 * offset && 2 * offset <= period);
+                assert_eq!(misalignment_ms(t - 1_657_886_400_000 - offset, period), 0);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "phase_offset_ms: period_ms must be > 0")]
+    fn test_phase_offset_ms_zero_period() {
+        phase_offset_ms(0, 0, 0);
+    }
+
+    #[test]
+    fn test_truncate_time_ms_to_day_hour_minute() {
+        // 2022-07-15T12:34:56.789Z
+        let tms = 1_657_888_496_789;
+        assert_eq!(truncate_time_ms_to_day(tms), 1_657_843_200_000);
+        assert_eq!(truncate_time_ms_to_hour(tms), 1_657_886_400_000);
+        assert_eq!(truncate_time_ms_to_minute(tms), 1_657_888_440_000);
+
+        for f in [
+            truncate_time_ms_to_day,
+            truncate_time_ms_to_hour,
+            truncate_time_ms_to_minute,
+        ] {
+            assert_eq!(f(0), 0);
+            assert_eq!(f(f(tms)), f(tms));
+        }
+    }
+
+    #[test]
+    fn test_truncate_time_ms_negative() {
+        // 1969-12-31T00:00:00Z, not the epoch as truncating toward zero gives
+        const DEC_31_1969: i64 = -86_400_000;
+        assert_eq!(truncate_time_ms_to_day(-1), DEC_31_1969);
+        assert_eq!(truncate_time_ms_to_day(DEC_31_1969), DEC_31_1969);
+        assert_eq!(truncate_time_ms_to_day(DEC_31_1969 - 1), 2 * DEC_31_1969);
+
+        // 1969-12-31T23:00:00Z and 1969-12-31T23:59:00Z
+        assert_eq!(truncate_time_ms_to_hour(-1), -3_600_000);
+        assert_eq!(truncate_time_ms_to_minute(-1), -60_000);
+        assert_eq!(truncate_time_ms_to_minute(-60_000), -60_000);
+
+        assert_eq!(truncate_time_ms_to_day(i64::MIN), i64::MIN);
+    }
+
+    #[test]
+    fn test_align_time_ms() {
+        const FIVE_MIN: i64 = 5 * MIN;
+        const HOUR: i64 = 60 * MIN;
+
+        // 2022-07-15T12:34:56.789Z
+        let tms = 1_657_888_496_789;
+        assert_eq!(align_time_ms(tms, FIVE_MIN), 1_657_888_200_000);
+        assert_eq!(align_time_ms(tms, HOUR), truncate_time_ms_to_hour(tms));
+        assert_eq!(align_time_ms(tms, 24 * HOUR), truncate_time_ms_to_day(tms));
+        assert_eq!(align_time_ms(tms, 1), tms);
+        assert_eq!(align_time_ms(FIVE_MIN, FIVE_MIN), FIVE_MIN);
+        assert_eq!(align_time_ms(FIVE_MIN - 1, FIVE_MIN), 0);
+    }
+
+    #[test]
+    fn test_align_time_ms_negative() {
+        const FIVE_MIN: i64 = 5 * MIN;
+        assert_eq!(align_time_ms(-1, FIVE_MIN), -FIVE_MIN);
+        assert_eq!(align_time_ms(-FIVE_MIN, FIVE_MIN), -FIVE_MIN);
+        assert_eq!(align_time_ms(-FIVE_MIN - 1, FIVE_MIN), -2 * FIVE_MIN);
+        assert_eq!(align_time_ms(i64::MIN, FIVE_MIN), i64::MIN);
+
+        for t in [-123_456_789, -1, 0, 1, 1_657_888_496_789] {
+            let aligned = align_time_ms(t, FIVE_MIN);
+            assert!(is_aligned_to_interval(aligned, FIVE_MIN));
+            assert_eq!(aligned + misalignment_ms(t, FIVE_MIN), t);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "align_time_ms: interval_ms must be > 0")]
+    fn test_align_time_ms_zero_interval() {
+        align_time_ms(1000, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "align_time_ms: interval_ms must be > 0")]
+    fn test_align_time_ms_negative_interval() {
+        align_time_ms(1000, -300_000);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_boundaries_crossed() {
+        assert_eq!(boundaries_crossed(500, 2500, 1000), [1000, 2000]);
+        assert_eq!(boundaries_crossed(500, 2000, 1000), [1000, 2000]);
+        assert!(boundaries_crossed(1000, 1999, 1000).is_empty());
+        assert!(boundaries_crossed(1000, 1000, 1000).is_empty());
+        assert_eq!(boundaries_crossed(999, 1000, 1000), [1000]);
+        assert_eq!(boundaries_crossed(-2500, 500, 1000), [-2000, -1000, 0]);
+        assert_eq!(boundaries_crossed(0, 3 * MIN, MIN), [MIN, 2 * MIN, 3 * MIN]);
+
+        // Consecutive ranges report each boundary once
+        let mut all = boundaries_crossed(-1500, 700, 500);
+        all.extend(boundaries_crossed(700, 2100, 500));
+        assert_eq!(all, boundaries_crossed(-1500, 2100, 500));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_boundaries_crossed_limits() {
+        assert!(boundaries_crossed(i64::MAX - 1, i64::MAX, 1000).is_empty());
+        assert_eq!(
+            boundaries_crossed(i64::MIN, i64::MIN + 1000, 1000),
+            [i64::MIN + 808]
+        );
+
+        // An interval_ms that doesn't fit a 32 bit usize
+        let interval_ms = (1 << 32) + 1000;
+        assert_eq!(
+            boundaries_crossed(-1, 2 * interval_ms, interval_ms),
+            [0, interval_ms, 2 * interval_ms]
+        );
+        assert_eq!(
+            boundaries_crossed(i64::MAX - 3 * interval_ms, i64::MAX, interval_ms).len(),
+            3
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "boundaries_crossed: interval_ms must be > 0")]
+    fn test_boundaries_crossed_zero_interval() {
+        boundaries_crossed(0, 1000, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "boundaries_crossed: to_ms must be >= from_ms")]
+    fn test_boundaries_crossed_backwards() {
+        boundaries_crossed(1000, 0, 100);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_busiest_bucket() {
+        const HOUR: i64 = 60 * MIN;
+
+        // A clear peak in the 2nd hour, unsorted
+        let times = [
+            5 * MIN,
+            HOUR + 59 * MIN,
+            HOUR,
+            3 * HOUR,
+            HOUR + 30 * MIN,
+            -1,
+        ];
+        assert_eq!(busiest_bucket(&times, HOUR), Some((HOUR, 3)));
+
+        // A single time
+        assert_eq!(busiest_bucket(&[-1], 1000), Some((-1000, 1)));
+        assert_eq!(
+            busiest_bucket(&[i64::MIN, i64::MAX], 1000),
+            Some((i64::MIN, 1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_busiest_bucket_tie() {
+        // Two buckets with 2 each, the earliest wins regardless of order
+        let mut times = [2500, 2100, -900, -100, 7000];
+        assert_eq!(busiest_bucket(&times, 1000), Some((-1000, 2)));
+        times.reverse();
+        assert_eq!(busiest_bucket(&times, 1000), Some((-1000, 2)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_busiest_bucket_empty() {
+        assert_eq!(busiest_bucket(&[], 1000), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "busiest_bucket: bucket_ms must be > 0")]
+    fn test_busiest_bucket_zero_bucket() {
+        busiest_bucket(&[0], 0);
+    }
+
+    #[test]
+    fn test_floor_time_ms_to() {
+        // 2022-07-15T12:34:56.789Z
+        let tms = 1_657_888_496_789;
+        assert_eq!(floor_time_ms_to(tms, TimeUnit::Second), 1_657_888_496_000);
+        assert_eq!(floor_time_ms_to(tms, TimeUnit::Minute), 1_657_888_440_000);
+        assert_eq!(floor_time_ms_to(tms, TimeUnit::Hour), 1_657_886_400_000);
+        assert_eq!(floor_time_ms_to(tms, TimeUnit::Day), 1_657_843_200_000);
+
+        assert_eq!(floor_time_ms_to(0, TimeUnit::Day), 0);
+        assert_eq!(floor_time_ms_to(i64::MIN, TimeUnit::Day), i64::MIN);
+    }
+
+    #[test]
+    fn test_floor_time_ms_to_pre_epoch() {
+        assert_eq!(floor_time_ms_to(-1, TimeUnit::Second), -1000);
+        assert_eq!(floor_time_ms_to(-1, TimeUnit::Minute), -MIN);
+        assert_eq!(floor_time_ms_to(-1, TimeUnit::Hour), -60 * MIN);
+
+        // 1969-07-20T20:17:40Z is in the day starting 1969-07-20T00:00:00Z,
+        // dividing would give 1969-07-21T00:00:00Z
+        let moon_landing = -14_182_940_000;
+        let midnight = -14_256_000_000;
+        assert_eq!(floor_time_ms_to(moon_landing, TimeUnit::Day), midnight);
+        assert_ne!(moon_landing / MS_PER_DAY * MS_PER_DAY, midnight);
+        assert_eq!(floor_time_ms_to(midnight, TimeUnit::Day), midnight);
+        assert_eq!(
+            floor_time_ms_to(midnight - 1, TimeUnit::Day),
+            midnight - MS_PER_DAY
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_floor_time_ms_to_day_is_utc_midnight() {
+        use crate::{time_ms_to_utc, utc_to_time_ms};
+
+        for tms in [
+            -14_182_940_000,
+            -1,
+            0,
+            1,
+            1_657_888_496_789,
+            -62_135_596_800_001,
+        ] {
+            let date = time_ms_to_utc(tms).date_naive();
+            let midnight = utc_to_time_ms(&date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+            assert_eq!(floor_time_ms_to(tms, TimeUnit::Day), midnight, "{tms}");
+        }
+    }
+
+    #[test]
+    fn test_ceil_time_ms_to() {
+        // 2022-07-15T12:34:56.789Z
+        let tms = 1_657_888_496_789;
+        assert_eq!(ceil_time_ms_to(tms, TimeUnit::Second), 1_657_888_497_000);
+        assert_eq!(ceil_time_ms_to(tms, TimeUnit::Minute), 1_657_888_500_000);
+        assert_eq!(ceil_time_ms_to(tms, TimeUnit::Hour), 1_657_890_000_000);
+        assert_eq!(ceil_time_ms_to(tms, TimeUnit::Day), 1_657_929_600_000);
+
+        // Aligned values are unchanged
+        for unit in [
+            TimeUnit::Second,
+            TimeUnit::Minute,
+            TimeUnit::Hour,
+            TimeUnit::Day,
+        ] {
+            let floor = floor_time_ms_to(tms, unit);
+            assert_eq!(ceil_time_ms_to(floor, unit), floor);
+            assert_eq!(ceil_time_ms_to(floor + 1, unit), floor + unit.ms());
+            assert_eq!(ceil_time_ms_to(floor - 1, unit), floor);
+        }
+
+        assert_eq!(ceil_time_ms_to(i64::MAX, TimeUnit::Day), i64::MAX);
+        assert_eq!(ceil_time_ms_to(i64::MIN, TimeUnit::Second), i64::MIN + 808);
+    }
+
+    #[test]
+    fn test_ceil_time_ms_to_pre_epoch() {
+        // One ms before midnight is exactly midnight
+        assert_eq!(ceil_time_ms_to(-1, TimeUnit::Day), 0);
+        assert_eq!(ceil_time_ms_to(-MS_PER_DAY - 1, TimeUnit::Day), -MS_PER_DAY);
+        assert_eq!(ceil_time_ms_to(-MS_PER_DAY, TimeUnit::Day), -MS_PER_DAY);
+        assert_eq!(ceil_time_ms_to(-MS_PER_DAY + 1, TimeUnit::Day), 0);
+        assert_eq!(ceil_time_ms_to(-1, TimeUnit::Second), 0);
+        assert_eq!(ceil_time_ms_to(-1001, TimeUnit::Second), -1000);
+
+        // floor <= time_ms <= ceil and they're a unit apart unless aligned
+        for tms in [-14_182_940_000, -86_400_001, -1, 0, 1, 1_657_888_496_789] {
+            let (floor, ceil) = (
+                floor_time_ms_to(tms, TimeUnit::Day),
+                ceil_time_ms_to(tms, TimeUnit::Day),
+            );
+            assert!(floor <= tms && tms <= ceil, "{tms}");
+            if floor == tms {
+                assert_eq!(ceil, tms);
+            } else {
+                assert_eq!(ceil - floor, MS_PER_DAY, "{tms}");
+            }
+        }
+    }
+}
@@ -0,0 +1,672 @@
+use chrono::{NaiveTime, Timelike, Weekday};
+
+use crate::{MS_PER_DAY, MS_PER_MIN, MS_PER_SEC};
+
+/// Milli-seconds since midnight of a NaiveTime, a leap second is clamped to .999
+pub(crate) fn naive_time_to_ms_of_day(time: NaiveTime) -> i64 {
+    let millis = (time.nanosecond() / 1_000_000).min(999);
+    time.num_seconds_from_midnight() as i64 * MS_PER_SEC + millis as i64
+}
+
+/// Milli-seconds of `[start_ms, end_ms)` that fall within the daily
+/// UTC `[open, close)` window on days that are not in `weekend`.
+///
+/// Returns 0 if `end_ms <= start_ms` or `close <= open`. Whole weeks are
+/// counted without visiting each day so any range is fast, the result
+/// saturates at i64::MAX.
+///
+/// # Example
+/// ```
+/// use chrono::{NaiveTime, Weekday};
+/// use time_ms_conversions::{business_ms_between, dt_str_to_utc_time_ms, TzMassaging};
+///
+/// let open = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+/// let close = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+/// let weekend = [Weekday::Sat, Weekday::Sun];
+///
+/// // Friday 12:00 to Monday 12:00 is 5h on Friday and 3h on Monday
+/// let fri = dt_str_to_utc_time_ms("2022-07-15T12:00:00", TzMassaging::CondAddTzUtc).unwrap();
+/// let mon = dt_str_to_utc_time_ms("2022-07-18T12:00:00", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!(business_ms_between(fri, mon, open, close, &weekend), 8 * 60 * 60 * 1000);
+/// ```
+pub fn business_ms_between(
+    start_ms: i64,
+    end_ms: i64,
+    open: NaiveTime,
+    close: NaiveTime,
+    weekend: &[Weekday],
+) -> i64 {
+    if end_ms <= start_ms || close <= open {
+        return 0;
+    }
+
+    // i128 as the day boundaries and total can overflow an i64
+    let (start_ms, end_ms) = (start_ms as i128, end_ms as i128);
+    let day_ms = MS_PER_DAY as i128;
+    let open_ms = naive_time_to_ms_of_day(open) as i128;
+    let close_ms = naive_time_to_ms_of_day(close) as i128;
+
+    // Day 0, 1970-01-01, was a Thursday and Monday is 0
+    let is_business_day = |day: i128| {
+        let weekday = (day + 3).rem_euclid(7) as u32;
+        !weekend.iter().any(|w| w.num_days_from_monday() == weekday)
+    };
+
+    // The window of `day` within [start_ms, end_ms)
+    let day_window_ms = |day: i128| {
+        if !is_business_day(day) {
+            return 0;
+        }
+        let lo = start_ms.max(day * day_ms + open_ms);
+        let hi = end_ms.min(day * day_ms + close_ms);
+        (hi - lo).max(0)
+    };
+
+    let first_day = start_ms.div_euclid(day_ms);
+    let last_day = (end_ms - 1).div_euclid(day_ms);
+    let total = if last_day - first_day < 14 {
+        (first_day..=last_day).map(day_window_ms).sum()
+    } else {
+        // The days between the first and last are whole, each week of them
+        // has the same business days
+        let whole_days = last_day - first_day - 1;
+        let business_days_per_week = (0..7).filter(|&day| is_business_day(day)).count() as i128;
+        let week_ms = business_days_per_week * (close_ms - open_ms);
+        let partial_week = (first_day + 1..first_day + 1 + whole_days % 7)
+            .map(day_window_ms)
+            .sum::<i128>();
+
+        day_window_ms(first_day) + whole_days / 7 * week_ms + partial_week + day_window_ms(last_day)
+    };
+
+    total.min(i64::MAX as i128) as i64
+}
+
+/// Fraction of `interval`, `(start_ms, end_ms)`, that falls within the
+/// daily UTC `[open, close)` window on days not in `weekend`.
+///
+/// This is [`business_ms_between`] divided by the interval length,
+/// an empty interval returns 0.0.
+///
+/// # Example
+/// ```
+/// use chrono::{NaiveTime, Weekday};
+/// use time_ms_conversions::schedule_coverage_fraction;
+///
+/// let open = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+/// let close = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+///
+/// // 1970-01-01 was a Thursday, half of it is in the window
+/// let f = schedule_coverage_fraction((0, 86_400_000), open, close, &[Weekday::Sat, Weekday::Sun]);
+/// assert_eq!(f, 0.5);
+/// ```
+pub fn schedule_coverage_fraction(
+    interval: (i64, i64),
+    open: NaiveTime,
+    close: NaiveTime,
+    weekend: &[Weekday],
+) -> f64 {
+    let (start_ms, end_ms) = interval;
+    if end_ms <= start_ms {
+        return 0.0;
+    }
+    let business_ms = business_ms_between(start_ms, end_ms, open, close, weekend);
+
+    business_ms as f64 / (end_ms as f64 - start_ms as f64)
+}
+
+/// The milli-seconds of the UTC day starting at `day_start_ms` within the
+/// daily `[open_min, close_min)` window, minutes after midnight.
+///
+/// A window with `close_min < open_min` wraps midnight, 22:00 to 06:00 is
+/// the complement of 06:00 to 22:00. A UTC day is always 24 hours so the
+/// result only depends on the window and `day_start_ms` may be any time_ms
+/// of the day.
+///
+/// # Panics
+///
+/// If `open_min` or `close_min` is greater than 1440
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::ms_in_daily_window;
+///
+/// assert_eq!(ms_in_daily_window(0, 9 * 60, 17 * 60), 28_800_000);
+/// assert_eq!(ms_in_daily_window(0, 22 * 60, 6 * 60), 28_800_000);
+/// ```
+pub fn ms_in_daily_window(day_start_ms: i64, open_min: u32, close_min: u32) -> i64 {
+    const MIN_PER_DAY: u32 = 24 * 60;
+    assert!(
+        open_min <= MIN_PER_DAY && close_min <= MIN_PER_DAY,
+        "ms_in_daily_window: open_min and close_min must be <= 1440"
+    );
+
+    // Every UTC day is 24 hours so the day doesn't change the result
+    let _ = day_start_ms;
+
+    let minutes = if close_min >= open_min {
+        close_min - open_min
+    } else {
+        MIN_PER_DAY - (open_min - close_min)
+    };
+
+    minutes as i64 * MS_PER_MIN
+}
+
+/// The time_ms `fraction` of the way through the daily UTC `[open, close)`
+/// window on the UTC day of `date_ms`, 0.0 is `open` and 1.0 is `close`.
+///
+/// `fraction` is clamped to `[0.0, 1.0]` and the result is rounded to the
+/// nearest milli-second with halves rounded up.
+///
+/// # Panics
+///
+/// If `close` is before `open` or `fraction` is NaN
+///
+/// # Example
+/// ```
+/// use chrono::NaiveTime;
+/// use time_ms_conversions::{business_time_of_day_utc, time_ms_from_utc_parts};
+///
+/// let open = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+/// let close = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+/// let date = time_ms_from_utc_parts(2022, 7, 15, 20, 0, 0, 0).unwrap();
+/// let midpoint = time_ms_from_utc_parts(2022, 7, 15, 12, 45, 0, 0).unwrap();
+/// assert_eq!(business_time_of_day_utc(date, open, close, 0.5), midpoint);
+/// ```
+pub fn business_time_of_day_utc(
+    date_ms: i64,
+    open: NaiveTime,
+    close: NaiveTime,
+    fraction: f64,
+) -> i64 {
+    assert!(
+        close >= open,
+        "business_time_of_day_utc: close must not be before open"
+    );
+    assert!(
+        !fraction.is_nan(),
+        "business_time_of_day_utc: fraction must not be NaN"
+    );
+
+    let open_ms = naive_time_to_ms_of_day(open);
+    let window_ms = naive_time_to_ms_of_day(close) - open_ms;
+    let offset_ms = (fraction.clamp(0.0, 1.0) * window_ms as f64 + 0.5).floor() as i64;
+
+    date_ms.div_euclid(MS_PER_DAY) * MS_PER_DAY + open_ms + offset_ms
+}
+
+/// Advance `time_ms` by `days` UTC business days, Monday through Friday,
+/// keeping the time of day. Negative `days` go back.
+///
+/// A start on a Saturday or Sunday first rolls forward to the following
+/// Monday, so 0 business days from a Saturday is the Monday and -1 is the
+/// Friday before the weekend.
+///
+/// The result saturates at i64::MIN and i64::MAX.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{add_business_days, dt_str_to_utc_time_ms, TzMassaging};
+///
+/// let fri = dt_str_to_utc_time_ms("2022-07-15T09:30:00", TzMassaging::CondAddTzUtc).unwrap();
+/// let mon = dt_str_to_utc_time_ms("2022-07-18T09:30:00", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!(add_business_days(fri, 1), mon);
+/// assert_eq!(add_business_days(mon, -1), fri);
+/// ```
+pub fn add_business_days(time_ms: i64, days: i64) -> i64 {
+    // Day 0, 1970-01-01, was a Thursday and Monday is 0
+    let weekday = |day: i128| (day + 3).rem_euclid(7);
+    let is_weekend = |day: i128| weekday(day) >= 5;
+
+    // i128 so large days or a time_ms near the limits can't overflow
+    let (time_ms, days, day_ms) = (time_ms as i128, days as i128, MS_PER_DAY as i128);
+    let mut day = time_ms.div_euclid(day_ms);
+    let ms_of_day = time_ms.rem_euclid(day_ms);
+
+    // Roll a weekend start forward to Monday
+    while is_weekend(day) {
+        day += 1;
+    }
+
+    // Whole weeks keep the weekday, then step the remainder a day at a time
+    day += days / 5 * 7;
+    let step = days.signum();
+    for _ in 0..(days % 5).abs() {
+        day += step;
+        while is_weekend(day) {
+            day += step;
+        }
+    }
+
+    (day * day_ms + ms_of_day).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+/// Start of the `bucket_ms` sized bucket containing time_ms where the
+/// buckets are aligned to the daily UTC `session_open` rather than midnight.
+///
+/// The session open at or before time_ms is found, today's if time_ms is at
+/// or after the open or yesterday's otherwise, and time_ms is floored to a
+/// multiple of `bucket_ms` after it. The alignment restarts at each session
+/// open, so if `bucket_ms` doesn't evenly divide a day the last bucket
+/// before an open is short.
+///
+/// # Panics
+///
+/// If `bucket_ms <= 0`
+///
+/// # Example
+/// ```
+/// use chrono::NaiveTime;
+/// use time_ms_conversions::{dt_str_to_utc_time_ms, session_aligned_bucket_start, TzMassaging};
+///
+/// let open = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+/// let t = dt_str_to_utc_time_ms("2022-07-15T09:33:00", TzMassaging::CondAddTzUtc).unwrap();
+/// let start = dt_str_to_utc_time_ms("2022-07-15T09:30:00", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!(session_aligned_bucket_start(t, 5 * 60 * 1000, open), start);
+/// ```
+pub fn session_aligned_bucket_start(time_ms: i64, bucket_ms: i64, session_open: NaiveTime) -> i64 {
+    assert!(
+        bucket_ms > 0,
+        "session_aligned_bucket_start: bucket_ms must be > 0"
+    );
+
+    let open_ms = naive_time_to_ms_of_day(session_open);
+    let mut session_start = time_ms.div_euclid(MS_PER_DAY) * MS_PER_DAY + open_ms;
+    if session_start > time_ms {
+        session_start -= MS_PER_DAY;
+    }
+
+    let since_open = time_ms - session_start;
+    session_start + since_open - since_open % bucket_ms
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{dt_str_to_utc_time_ms, TzMassaging};
+    use chrono::Datelike;
+
+    const WEEKEND: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+    fn tms(dt_str: &str) -> i64 {
+        dt_str_to_utc_time_ms(dt_str, TzMassaging::CondAddTzUtc).expect("Bad time format")
+    }
+
+    fn hm(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_business_ms_between() {
+        // Monday 2022-07-11 through Monday 2022-07-18 is five full business days
+        let ms = business_ms_between(
+            tms("2022-07-11T00:00:00"),
+            tms("2022-07-18T00:00:00"),
+            hm(9, 0),
+            hm(17, 0),
+            &WEEKEND,
+        );
+        assert_eq!(ms, 5 * 8 * 60 * 60 * 1000);
+
+        // Weekend only
+        let ms = business_ms_between(
+            tms("2022-07-16T00:00:00"),
+            tms("2022-07-18T00:00:00"),
+            hm(9, 0),
+            hm(17, 0),
+            &WEEKEND,
+        );
+        assert_eq!(ms, 0);
+
+        // Empty interval and empty window
+        assert_eq!(business_ms_between(10, 10, hm(9, 0), hm(17, 0), &[]), 0);
+        assert_eq!(business_ms_between(0, 10, hm(9, 0), hm(9, 0), &[]), 0);
+    }
+
+    #[test]
+    fn test_business_ms_between_negative_time_ms() {
+        // 1969-12-31 was a Wednesday
+        let ms = business_ms_between(
+            tms("1969-12-31T00:00:00"),
+            tms("1970-01-01T00:00:00"),
+            hm(9, 0),
+            hm(17, 0),
+            &WEEKEND,
+        );
+        assert_eq!(ms, 8 * 60 * 60 * 1000);
+    }
+
+    // Reference a day at a time
+    fn business_ms_between_by_day(
+        start_ms: i64,
+        end_ms: i64,
+        open: NaiveTime,
+        close: NaiveTime,
+        weekend: &[Weekday],
+    ) -> i64 {
+        let (open_ms, close_ms) = (
+            naive_time_to_ms_of_day(open),
+            naive_time_to_ms_of_day(close),
+        );
+        let mut total = 0;
+        let mut day_start = start_ms.div_euclid(MS_PER_DAY) * MS_PER_DAY;
+        while day_start < end_ms {
+            if !weekend.contains(&crate::time_ms_to_utc(day_start).weekday()) {
+                let lo = start_ms.max(day_start + open_ms);
+                let hi = end_ms.min(day_start + close_ms);
+                total += (hi - lo).max(0);
+            }
+            day_start += MS_PER_DAY;
+        }
+
+        total
+    }
+
+    #[test]
+    fn test_business_ms_between_agrees_with_by_day() {
+        let base = tms("2022-07-13T10:17:00");
+        let weekends: [&[Weekday]; 4] = [
+            &WEEKEND,
+            &[],
+            &[Weekday::Fri, Weekday::Sat, Weekday::Fri],
+            &[
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ],
+        ];
+        for weekend in weekends {
+            for (open, close) in [(hm(9, 0), hm(17, 0)), (hm(0, 0), hm(23, 59))] {
+                for start_offset in [-3 * MS_PER_DAY - 1, -1, 0, 5 * 3_600_000] {
+                    for len in [
+                        1,
+                        3_600_000,
+                        2 * MS_PER_DAY,
+                        13 * MS_PER_DAY,
+                        15 * MS_PER_DAY + 7,
+                        100 * MS_PER_DAY + 123,
+                    ] {
+                        let start_ms = base + start_offset;
+                        let end_ms = start_ms + len;
+                        assert_eq!(
+                            business_ms_between(start_ms, end_ms, open, close, weekend),
+                            business_ms_between_by_day(start_ms, end_ms, open, close, weekend),
+                            "{start_ms} {end_ms} {weekend:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_business_ms_between_full_range() {
+        // Fast and no panic though most of the range is out of chrono's
+        let ms = business_ms_between(i64::MIN, i64::MAX, hm(9, 0), hm(17, 0), &WEEKEND);
+        let expected = (i64::MAX as f64 - i64::MIN as f64) * 5.0 / 7.0 / 3.0;
+        assert!((ms as f64 - expected).abs() / expected < 1e-9, "{ms}");
+
+        // Saturates
+        assert_eq!(
+            business_ms_between(i64::MIN, i64::MAX, hm(0, 0), hm(23, 59), &[]),
+            i64::MAX
+        );
+
+        let f = schedule_coverage_fraction((i64::MIN, i64::MAX), hm(9, 0), hm(17, 0), &WEEKEND);
+        assert!((f - 5.0 / 21.0).abs() < 1e-9, "{f}");
+    }
+
+    #[test]
+    fn test_schedule_coverage_fraction_within() {
+        let f = schedule_coverage_fraction(
+            (tms("2022-07-13T10:00:00"), tms("2022-07-13T11:30:00")),
+            hm(9, 30),
+            hm(16, 0),
+            &WEEKEND,
+        );
+        assert_eq!(f, 1.0);
+    }
+
+    #[test]
+    fn test_schedule_coverage_fraction_outside() {
+        // Before the open on a weekday
+        let f = schedule_coverage_fraction(
+            (tms("2022-07-13T01:00:00"), tms("2022-07-13T09:30:00")),
+            hm(9, 30),
+            hm(16, 0),
+            &WEEKEND,
+        );
+        assert_eq!(f, 0.0);
+
+        // During "hours" but on a Saturday
+        let f = schedule_coverage_fraction(
+            (tms("2022-07-16T10:00:00"), tms("2022-07-16T11:00:00")),
+            hm(9, 30),
+            hm(16, 0),
+            &WEEKEND,
+        );
+        assert_eq!(f, 0.0);
+    }
+
+    #[test]
+    fn test_schedule_coverage_fraction_straddling() {
+        // 15:00 to 17:00, one of the two hours is before the close
+        let f = schedule_coverage_fraction(
+            (tms("2022-07-13T15:00:00"), tms("2022-07-13T17:00:00")),
+            hm(9, 0),
+            hm(16, 0),
+            &WEEKEND,
+        );
+        assert_eq!(f, 0.5);
+
+        // Friday 16:00 to Monday 10:00, one hour each side of the weekend
+        let f = schedule_coverage_fraction(
+            (tms("2022-07-15T16:00:00"), tms("2022-07-18T10:00:00")),
+            hm(9, 0),
+            hm(17, 0),
+            &WEEKEND,
+        );
+        assert_eq!(f, 2.0 / 66.0);
+    }
+
+    #[test]
+    fn test_schedule_coverage_fraction_empty_interval() {
+        let f = schedule_coverage_fraction((100, 100), hm(9, 0), hm(17, 0), &WEEKEND);
+        assert_eq!(f, 0.0);
+    }
+
+    #[test]
+    fn test_add_business_days() {
+        let fri = tms("2022-07-15T09:30:00.123");
+        let mon = tms("2022-07-18T09:30:00.123");
+        assert_eq!(add_business_days(fri, 0), fri);
+        assert_eq!(add_business_days(fri, 1), mon);
+        assert_eq!(add_business_days(mon, -1), fri);
+        assert_eq!(add_business_days(mon, 5), tms("2022-07-25T09:30:00.123"));
+        assert_eq!(add_business_days(mon, -5), tms("2022-07-11T09:30:00.123"));
+        assert_eq!(add_business_days(mon, 4), tms("2022-07-22T09:30:00.123"));
+        assert_eq!(add_business_days(mon, 6), tms("2022-07-26T09:30:00.123"));
+        assert_eq!(add_business_days(fri, -6), tms("2022-07-07T09:30:00.123"));
+        assert_eq!(add_business_days(fri, 260), tms("2023-07-14T09:30:00.123"));
+    }
+
+    #[test]
+    fn test_add_business_days_weekend_start() {
+        let sat = tms("2022-07-16T12:00:00");
+        let sun = tms("2022-07-17T12:00:00");
+        assert_eq!(add_business_days(sat, 0), tms("2022-07-18T12:00:00"));
+        assert_eq!(add_business_days(sun, 0), tms("2022-07-18T12:00:00"));
+        assert_eq!(add_business_days(sat, 1), tms("2022-07-19T12:00:00"));
+        assert_eq!(add_business_days(sun, -1), tms("2022-07-15T12:00:00"));
+    }
+
+    #[test]
+    fn test_add_business_days_saturates() {
+        let fri = tms("2022-07-15T09:30:00.123");
+        assert_eq!(add_business_days(fri, i64::MAX), i64::MAX);
+        assert_eq!(add_business_days(fri, i64::MIN), i64::MIN);
+        assert_eq!(add_business_days(i64::MAX, 10), i64::MAX);
+        assert_eq!(add_business_days(i64::MIN, -10), i64::MIN);
+
+        // Not saturated when in range
+        let tms = add_business_days(i64::MIN, 10);
+        assert!(tms > i64::MIN && tms < i64::MIN + 20 * MS_PER_DAY, "{tms}");
+    }
+
+    #[test]
+    fn test_add_business_days_negative_time_ms() {
+        // 1969-12-31 was a Wednesday
+        let wed = tms("1969-12-31T23:59:59.999");
+        assert_eq!(add_business_days(wed, 2), tms("1970-01-02T23:59:59.999"));
+        assert_eq!(add_business_days(wed, 3), tms("1970-01-05T23:59:59.999"));
+        assert_eq!(
+            add_business_days(tms("1970-01-05T00:00:00"), -1),
+            tms("1970-01-02T00:00:00")
+        );
+        assert_eq!(
+            add_business_days(tms("1970-01-05T00:00:00"), -3),
+            tms("1969-12-31T00:00:00")
+        );
+    }
+
+    #[test]
+    fn test_session_aligned_bucket_start() {
+        const FIVE_MIN: i64 = 5 * 60 * 1000;
+        let open = hm(9, 30);
+        let start = |t: &str| session_aligned_bucket_start(tms(t), FIVE_MIN, open);
+
+        assert_eq!(start("2022-07-15T09:33:00"), tms("2022-07-15T09:30:00"));
+        assert_eq!(start("2022-07-15T09:30:00"), tms("2022-07-15T09:30:00"));
+        assert_eq!(start("2022-07-15T09:34:59.999"), tms("2022-07-15T09:30:00"));
+        assert_eq!(start("2022-07-15T09:35:00"), tms("2022-07-15T09:35:00"));
+        assert_eq!(start("2022-07-15T09:29:59.999"), tms("2022-07-15T09:25:00"));
+        assert_eq!(start("1969-12-31T09:31:00"), tms("1969-12-31T09:30:00"));
+    }
+
+    #[test]
+    fn test_session_aligned_bucket_start_uneven_bucket() {
+        // 7 hour buckets from 09:30 are 09:30, 16:30, 23:30 and a short
+        // 06:30 bucket until the next open
+        const SEVEN_HOURS: i64 = 7 * 60 * 60 * 1000;
+        let open = hm(9, 30);
+        let start = |t: &str| session_aligned_bucket_start(tms(t), SEVEN_HOURS, open);
+
+        assert_eq!(start("2022-07-15T17:00:00"), tms("2022-07-15T16:30:00"));
+        assert_eq!(start("2022-07-16T01:00:00"), tms("2022-07-15T23:30:00"));
+        assert_eq!(start("2022-07-16T09:00:00"), tms("2022-07-16T06:30:00"));
+        assert_eq!(start("2022-07-16T09:30:00"), tms("2022-07-16T09:30:00"));
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket_ms must be > 0")]
+    fn test_session_aligned_bucket_start_zero_bucket() {
+        session_aligned_bucket_start(0, 0, hm(9, 30));
+    }
+
+    #[test]
+    fn test_ms_in_daily_window() {
+        let day = tms("2022-07-15T00:00:00");
+        assert_eq!(ms_in_daily_window(day, 9 * 60, 17 * 60), 28_800_000);
+        assert_eq!(ms_in_daily_window(day, 0, 1440), MS_PER_DAY);
+        assert_eq!(ms_in_daily_window(day, 600, 600), 0);
+        assert_eq!(ms_in_daily_window(day, 1440, 1440), 0);
+        assert_eq!(ms_in_daily_window(day, 0, 1), 60_000);
+        assert_eq!(ms_in_daily_window(-MS_PER_DAY, 9 * 60, 17 * 60), 28_800_000);
+    }
+
+    #[test]
+    fn test_ms_in_daily_window_wraps_midnight() {
+        let day = tms("2022-07-15T00:00:00");
+        assert_eq!(ms_in_daily_window(day, 22 * 60, 6 * 60), 28_800_000);
+        assert_eq!(ms_in_daily_window(day, 1439, 1), 2 * 60_000);
+        assert_eq!(ms_in_daily_window(day, 1440, 0), 0);
+
+        // A window and its wrapping complement cover the day
+        for (open, close) in [(9 * 60, 17 * 60), (1, 1439), (0, 720)] {
+            assert_eq!(
+                ms_in_daily_window(day, open, close) + ms_in_daily_window(day, close, open),
+                MS_PER_DAY
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "must be <= 1440")]
+    fn test_ms_in_daily_window_bad_minutes() {
+        ms_in_daily_window(0, 9 * 60, 1441);
+    }
+
+    #[test]
+    fn test_ms_in_daily_window_not_midnight() {
+        let time_ms = tms("2022-07-15T13:45:12.345");
+        assert_eq!(ms_in_daily_window(time_ms, 9 * 60, 17 * 60), 28_800_000);
+        assert_eq!(ms_in_daily_window(-1, 22 * 60, 6 * 60), 28_800_000);
+    }
+
+    #[test]
+    fn test_business_time_of_day_utc() {
+        let (open, close) = (hm(9, 0), hm(17, 0));
+        let date = tms("2022-07-15T20:00:00");
+        let at = |fraction| business_time_of_day_utc(date, open, close, fraction);
+        assert_eq!(at(0.0), tms("2022-07-15T09:00:00"));
+        assert_eq!(at(0.5), tms("2022-07-15T13:00:00"));
+        assert_eq!(at(1.0), tms("2022-07-15T17:00:00"));
+        assert_eq!(at(0.25), tms("2022-07-15T11:00:00"));
+
+        // Clamped
+        assert_eq!(at(-0.5), at(0.0));
+        assert_eq!(at(1.5), at(1.0));
+        assert_eq!(at(f64::INFINITY), at(1.0));
+
+        // Any time in the day gives the same result
+        let midnight = tms("2022-07-15T00:00:00");
+        let end_of_day = tms("2022-07-15T23:59:59.999");
+        assert_eq!(
+            business_time_of_day_utc(midnight, open, close, 0.5),
+            at(0.5)
+        );
+        assert_eq!(
+            business_time_of_day_utc(end_of_day, open, close, 0.5),
+            at(0.5)
+        );
+    }
+
+    #[test]
+    fn test_business_time_of_day_utc_pre_epoch_and_rounding() {
+        let (open, close) = (hm(9, 0), hm(17, 0));
+        assert_eq!(
+            business_time_of_day_utc(-1, open, close, 0.5),
+            tms("1969-12-31T13:00:00")
+        );
+
+        // 3ms window, a third is 1ms
+        let open = NaiveTime::from_hms_milli_opt(9, 0, 0, 0).unwrap();
+        let close = NaiveTime::from_hms_milli_opt(9, 0, 0, 3).unwrap();
+        let nine = tms("1970-01-01T09:00:00");
+        assert_eq!(
+            business_time_of_day_utc(0, open, close, 1.0 / 3.0),
+            nine + 1
+        );
+        assert_eq!(business_time_of_day_utc(0, open, close, 0.5), nine + 2);
+
+        // An empty window is always open
+        assert_eq!(business_time_of_day_utc(0, open, open, 0.7), nine);
+    }
+
+    #[test]
+    #[should_panic(expected = "close must not be before open")]
+    fn test_business_time_of_day_utc_close_before_open() {
+        business_time_of_day_utc(0, hm(17, 0), hm(9, 0), 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction must not be NaN")]
+    fn test_business_time_of_day_utc_nan() {
+        business_time_of_day_utc(0, hm(9, 0), hm(17, 0), f64::NAN);
+    }
+}
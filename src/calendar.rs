@@ -0,0 +1,784 @@
+use chrono::{Datelike, Local, NaiveDate, Timelike, Weekday};
+
+use crate::{
+    dt_str_to_utc_time_ms, time_ms_from_utc_parts, time_ms_to_utc, TimeConversionError,
+    TzMassaging, MS_PER_DAY,
+};
+
+/// The sorted and deduplicated UTC dates of `times`
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use time_ms_conversions::distinct_dates_utc;
+///
+/// let dates = distinct_dates_utc(&[86_400_000, -1, 0, 1]);
+/// assert_eq!(
+///     dates,
+///     vec![
+///         NaiveDate::from_ymd_opt(1969, 12, 31).unwrap(),
+///         NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+///         NaiveDate::from_ymd_opt(1970, 1, 2).unwrap(),
+///     ]
+/// );
+/// ```
+pub fn distinct_dates_utc(times: &[i64]) -> Vec<NaiveDate> {
+    let mut dates: Vec<NaiveDate> = times.iter().map(|&t| time_ms_to_naive_date(t)).collect();
+    dates.sort_unstable();
+    dates.dedup();
+
+    dates
+}
+
+/// The UTC date of `time_ms`, floored so -1 is 1969-12-31
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use time_ms_conversions::time_ms_to_naive_date;
+///
+/// assert_eq!(time_ms_to_naive_date(0), NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+/// assert_eq!(time_ms_to_naive_date(-1), NaiveDate::from_ymd_opt(1969, 12, 31).unwrap());
+/// ```
+pub fn time_ms_to_naive_date(time_ms: i64) -> NaiveDate {
+    time_ms_to_utc(time_ms).date_naive()
+}
+
+/// The start of the UTC month of `time_ms`, as [`truncate_time_ms_to_day`]
+/// is the start of its day
+///
+/// [`truncate_time_ms_to_day`]: crate::truncate_time_ms_to_day
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_from_utc_parts, truncate_time_ms_to_month};
+///
+/// let tms = time_ms_from_utc_parts(2022, 7, 15, 12, 0, 0, 123).unwrap();
+/// assert_eq!(truncate_time_ms_to_month(tms), time_ms_from_utc_parts(2022, 7, 1, 0, 0, 0, 0).unwrap());
+/// assert_eq!(truncate_time_ms_to_month(-1), time_ms_from_utc_parts(1969, 12, 1, 0, 0, 0, 0).unwrap());
+/// ```
+pub fn truncate_time_ms_to_month(time_ms: i64) -> i64 {
+    let date = time_ms_to_naive_date(time_ms);
+    time_ms_from_utc_parts(date.year(), date.month(), 1, 0, 0, 0, 0)
+        .expect("The first of a month in range is in range")
+}
+
+// The start of the UTC month after the one starting at month_start_ms,
+// None if it's out of the range of DateTime<Utc>
+pub(crate) fn next_month_start(month_start_ms: i64) -> Option<i64> {
+    let date = time_ms_to_naive_date(month_start_ms);
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    time_ms_from_utc_parts(year, month, 1, 0, 0, 0, 0)
+}
+
+/// The UTC weekday of `time_ms`
+///
+/// # Example
+/// ```
+/// use chrono::Weekday;
+/// use time_ms_conversions::time_ms_weekday;
+///
+/// assert_eq!(time_ms_weekday(0), Weekday::Thu);
+/// assert_eq!(time_ms_weekday(-1), Weekday::Wed);
+/// ```
+pub fn time_ms_weekday(time_ms: i64) -> Weekday {
+    time_ms_to_utc(time_ms).weekday()
+}
+
+/// The UTC ISO 8601 week of `time_ms` as `(iso_year, week)`
+///
+/// The ISO year can differ from the calendar year for the first and
+/// last few days of a year.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms, time_ms_to_iso_week, TzMassaging};
+///
+/// let tms = dt_str_to_utc_time_ms("2021-01-01T00:00:00", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!(time_ms_to_iso_week(tms), (2020, 53));
+/// ```
+pub fn time_ms_to_iso_week(time_ms: i64) -> (i32, u32) {
+    let week = time_ms_to_utc(time_ms).iso_week();
+
+    (week.year(), week.week())
+}
+
+/// The UTC day of the year of `time_ms`, 1 for January 1st
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_ordinal;
+///
+/// assert_eq!(time_ms_to_ordinal(0), 1);
+/// assert_eq!(time_ms_to_ordinal(-1), 365);
+/// ```
+pub fn time_ms_to_ordinal(time_ms: i64) -> u32 {
+    time_ms_to_utc(time_ms).ordinal()
+}
+
+/// True if the UTC year of `time_ms` is a leap year
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_from_utc_parts, time_ms_is_leap_year};
+///
+/// assert!(time_ms_is_leap_year(time_ms_from_utc_parts(2000, 6, 1, 0, 0, 0, 0).unwrap()));
+/// assert!(!time_ms_is_leap_year(time_ms_from_utc_parts(1900, 6, 1, 0, 0, 0, 0).unwrap()));
+/// ```
+pub fn time_ms_is_leap_year(time_ms: i64) -> bool {
+    time_ms_to_utc(time_ms).date_naive().leap_year()
+}
+
+/// The number of days in the UTC month of `time_ms`, 28 to 31
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_days_in_month, time_ms_from_utc_parts};
+///
+/// assert_eq!(time_ms_days_in_month(time_ms_from_utc_parts(2000, 2, 10, 0, 0, 0, 0).unwrap()), 29);
+/// assert_eq!(time_ms_days_in_month(time_ms_from_utc_parts(1900, 2, 10, 0, 0, 0, 0).unwrap()), 28);
+/// assert_eq!(time_ms_days_in_month(0), 31);
+/// ```
+pub fn time_ms_days_in_month(time_ms: i64) -> u32 {
+    let dt = time_ms_to_utc(time_ms);
+    match dt.month() {
+        2 if dt.date_naive().leap_year() => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The UTC time_ms `fraction` of the way through the UTC month of
+/// `time_ms`, for apportioning across months of 28 to 31 days.
+///
+/// `fraction` is clamped to 0.0..=1.0, 0.0 is the start of the month and
+/// 1.0 is its end, the start of the next month. The result is rounded to
+/// the nearest milli-second with halves rounded up.
+///
+/// # Panics
+///
+/// If `fraction` is NaN or time_ms is out of the range of DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_at_fraction_of_month_utc, time_ms_from_utc_parts};
+///
+/// let feb = time_ms_from_utc_parts(2021, 2, 20, 0, 0, 0, 0).unwrap();
+/// assert_eq!(
+///     time_ms_at_fraction_of_month_utc(feb, 0.5),
+///     time_ms_from_utc_parts(2021, 2, 15, 0, 0, 0, 0).unwrap()
+/// );
+/// ```
+pub fn time_ms_at_fraction_of_month_utc(time_ms: i64, fraction: f64) -> i64 {
+    assert!(
+        !fraction.is_nan(),
+        "time_ms_at_fraction_of_month_utc: fraction must not be NaN"
+    );
+    let month_start = truncate_time_ms_to_month(time_ms);
+    let month_ms = time_ms_days_in_month(time_ms) as i64 * MS_PER_DAY;
+
+    // At most 31 days of milli-seconds so it's exact in an f64
+    month_start + (fraction.clamp(0.0, 1.0) * month_ms as f64 + 0.5).floor() as i64
+}
+
+/// The UTC date and time parts of a time_ms, see [`time_ms_components_utc`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTimeComponents {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+    pub weekday: Weekday,
+
+    /// Day of the year, 1 for January 1st
+    pub ordinal: u32,
+}
+
+/// The UTC [`DateTimeComponents`] of time_ms in one call
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_components_utc;
+///
+/// let c = time_ms_components_utc(1_657_886_400_123);
+/// assert_eq!((c.year, c.month, c.day), (2022, 7, 15));
+/// assert_eq!((c.hour, c.minute, c.second, c.millisecond), (12, 0, 0, 123));
+/// ```
+pub fn time_ms_components_utc(time_ms: i64) -> DateTimeComponents {
+    let dt = time_ms_to_utc(time_ms);
+
+    DateTimeComponents {
+        year: dt.year(),
+        month: dt.month(),
+        day: dt.day(),
+        hour: dt.hour(),
+        minute: dt.minute(),
+        second: dt.second(),
+        millisecond: dt.timestamp_subsec_millis(),
+        weekday: dt.weekday(),
+        ordinal: dt.ordinal(),
+    }
+}
+
+/// The UTC time_ms of the date and time parts of `c`, the inverse of
+/// [`time_ms_components_utc`]. The derived `weekday` and `ordinal` fields
+/// are ignored.
+///
+/// # Errors
+///
+/// `OutOfRange` if the parts aren't a valid date and time, such as month
+/// 13, day 0 or millisecond 1000.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_components_utc, time_ms_from_components_utc};
+///
+/// let c = time_ms_components_utc(1_657_886_400_123);
+/// assert_eq!(time_ms_from_components_utc(&c), Ok(1_657_886_400_123));
+/// ```
+pub fn time_ms_from_components_utc(c: &DateTimeComponents) -> Result<i64, TimeConversionError> {
+    time_ms_from_utc_parts(
+        c.year,
+        c.month,
+        c.day,
+        c.hour,
+        c.minute,
+        c.second,
+        c.millisecond,
+    )
+    .ok_or(TimeConversionError::OutOfRange)
+}
+
+/// [`dt_str_to_utc_time_ms`] for a feed that also states the weekday,
+/// returning an error if the UTC weekday of the time isn't `expected` so
+/// transcription errors are caught.
+///
+/// # Example
+/// ```
+/// use chrono::Weekday;
+/// use time_ms_conversions::{parse_and_verify_weekday, TzMassaging};
+///
+/// let tms = parse_and_verify_weekday("2022-07-15T12:00:00Z", Weekday::Fri, TzMassaging::HasTz);
+/// assert_eq!(tms, Ok(1_657_886_400_000));
+///
+/// let err = parse_and_verify_weekday("2022-07-15T12:00:00Z", Weekday::Thu, TzMassaging::HasTz);
+/// assert!(err.unwrap_err().to_string().contains("weekday is Fri, expected Thu"));
+/// ```
+pub fn parse_and_verify_weekday(
+    s: &str,
+    expected: Weekday,
+    tz_massaging: TzMassaging,
+) -> Result<i64, TimeConversionError> {
+    let time_ms = dt_str_to_utc_time_ms(s, tz_massaging)?;
+
+    let weekday = time_ms_weekday(time_ms);
+    if weekday != expected {
+        return Err(TimeConversionError::parse(
+            s,
+            format!("the UTC weekday is {weekday}, expected {expected}"),
+        ));
+    }
+
+    Ok(time_ms)
+}
+
+/// The time_ms `fraction` of the way through UTC `year`, the start of the
+/// year plus `fraction` of its length, 366 days in a leap year, rounded
+/// to the nearest milli-second with halves rounded up.
+///
+/// # Panics
+///
+/// If `fraction` isn't in `0.0..1.0` or `year` is out of the range of
+/// `DateTime<Utc>`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_from_utc_parts, year_fraction_to_time_ms};
+///
+/// assert_eq!(year_fraction_to_time_ms(1970, 0.0), 0);
+///
+/// // 2020 is a leap year so half way is 183 days in
+/// let july_2 = time_ms_from_utc_parts(2020, 7, 2, 0, 0, 0, 0).unwrap();
+/// assert_eq!(year_fraction_to_time_ms(2020, 0.5), july_2);
+/// ```
+pub fn year_fraction_to_time_ms(year: i32, fraction: f64) -> i64 {
+    assert!(
+        (0.0..1.0).contains(&fraction),
+        "year_fraction_to_time_ms: fraction must be in 0.0..1.0"
+    );
+    let start_ms = time_ms_from_utc_parts(year, 1, 1, 0, 0, 0, 0)
+        .expect("year_fraction_to_time_ms: year out of range");
+
+    // From the leap year, not the start of the next year, which is out of
+    // range in chrono's last year
+    let year_days = if time_ms_is_leap_year(start_ms) {
+        366
+    } else {
+        365
+    };
+    let year_length_ms = year_days * MS_PER_DAY;
+
+    start_ms + (fraction * year_length_ms as f64 + 0.5).floor() as i64
+}
+
+/// A fuzzy part of the day, see [`time_ms_part_of_day_utc`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartOfDay {
+    /// 00:00 to 05:59
+    Night,
+
+    /// 06:00 to 11:59
+    Morning,
+
+    /// 12:00 to 17:59
+    Afternoon,
+
+    /// 18:00 to 23:59
+    Evening,
+}
+
+impl PartOfDay {
+    fn from_hour(hour: u32) -> Self {
+        match hour {
+            0..=5 => PartOfDay::Night,
+            6..=11 => PartOfDay::Morning,
+            12..=17 => PartOfDay::Afternoon,
+            _ => PartOfDay::Evening,
+        }
+    }
+}
+
+/// The [`PartOfDay`] of the UTC hour of time_ms
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_from_utc_parts, time_ms_part_of_day_utc, PartOfDay};
+///
+/// let tms = time_ms_from_utc_parts(2022, 7, 15, 9, 30, 0, 0).unwrap();
+/// assert_eq!(time_ms_part_of_day_utc(tms), PartOfDay::Morning);
+/// ```
+pub fn time_ms_part_of_day_utc(time_ms: i64) -> PartOfDay {
+    PartOfDay::from_hour(time_ms_to_utc(time_ms).hour())
+}
+
+/// The [`PartOfDay`] of the local hour of time_ms
+pub fn time_ms_part_of_day_local(time_ms: i64) -> PartOfDay {
+    PartOfDay::from_hour(time_ms_to_utc(time_ms).with_timezone(&Local).hour())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{dt_str_to_utc_time_ms, TzMassaging};
+
+    fn tms(dt_str: &str) -> i64 {
+        dt_str_to_utc_time_ms(dt_str, TzMassaging::CondAddTzUtc).expect("Bad time format")
+    }
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_distinct_dates_utc_two_days() {
+        let times = [
+            tms("2022-07-16T01:00:00"),
+            tms("2022-07-15T23:59:59.999"),
+            tms("2022-07-16T00:00:00"),
+        ];
+        assert_eq!(
+            distinct_dates_utc(&times),
+            vec![ymd(2022, 7, 15), ymd(2022, 7, 16)]
+        );
+    }
+
+    #[test]
+    fn test_distinct_dates_utc_same_day() {
+        let times = [
+            tms("2022-07-15T00:00:00"),
+            tms("2022-07-15T12:00:00"),
+            tms("2022-07-15T23:59:59.999"),
+        ];
+        assert_eq!(distinct_dates_utc(&times), vec![ymd(2022, 7, 15)]);
+    }
+
+    #[test]
+    fn test_distinct_dates_utc_empty() {
+        assert!(distinct_dates_utc(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_time_ms_to_naive_date() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(time_ms_to_naive_date(0), date(1970, 1, 1));
+        assert_eq!(time_ms_to_naive_date(86_399_999), date(1970, 1, 1));
+        assert_eq!(time_ms_to_naive_date(86_400_000), date(1970, 1, 2));
+        assert_eq!(time_ms_to_naive_date(-1), date(1969, 12, 31));
+        assert_eq!(time_ms_to_naive_date(-86_400_000), date(1969, 12, 31));
+        assert_eq!(time_ms_to_naive_date(-86_400_001), date(1969, 12, 30));
+        assert_eq!(
+            time_ms_to_naive_date(tms("1900-02-28T23:59:59.999")),
+            date(1900, 2, 28)
+        );
+    }
+
+    #[test]
+    fn test_truncate_time_ms_to_month() {
+        let utc = |y, m, d| time_ms_from_utc_parts(y, m, d, 0, 0, 0, 0).unwrap();
+        assert_eq!(truncate_time_ms_to_month(0), 0);
+        assert_eq!(truncate_time_ms_to_month(utc(1970, 2, 1) - 1), 0);
+        assert_eq!(truncate_time_ms_to_month(utc(1970, 2, 1)), utc(1970, 2, 1));
+        assert_eq!(truncate_time_ms_to_month(-1), utc(1969, 12, 1));
+        assert_eq!(
+            truncate_time_ms_to_month(tms("2020-02-29T23:59:59.999")),
+            utc(2020, 2, 1)
+        );
+
+        assert_eq!(next_month_start(utc(2020, 2, 1)), Some(utc(2020, 3, 1)));
+        assert_eq!(next_month_start(utc(1969, 12, 1)), Some(0));
+    }
+
+    #[test]
+    fn test_time_ms_weekday() {
+        // 1970-01-01 was a Thursday
+        assert_eq!(time_ms_weekday(0), Weekday::Thu);
+        assert_eq!(time_ms_weekday(86_400_000), Weekday::Fri);
+        assert_eq!(time_ms_weekday(-1), Weekday::Wed);
+        assert_eq!(time_ms_weekday(-86_400_000), Weekday::Wed);
+        assert_eq!(time_ms_weekday(-86_400_001), Weekday::Tue);
+        assert_eq!(time_ms_weekday(-7 * 86_400_000), Weekday::Thu);
+        assert_eq!(
+            time_ms_weekday(tms("2022-07-15T23:59:59.999")),
+            Weekday::Fri
+        );
+        assert_eq!(time_ms_weekday(tms("1900-01-01T00:00:00")), Weekday::Mon);
+    }
+
+    #[test]
+    fn test_time_ms_to_iso_week() {
+        assert_eq!(time_ms_to_iso_week(tms("2021-01-01T00:00:00")), (2020, 53));
+        assert_eq!(
+            time_ms_to_iso_week(tms("2021-01-03T23:59:59.999")),
+            (2020, 53)
+        );
+        assert_eq!(time_ms_to_iso_week(tms("2021-01-04T00:00:00")), (2021, 1));
+        assert_eq!(time_ms_to_iso_week(tms("2019-12-30T00:00:00")), (2020, 1));
+        assert_eq!(time_ms_to_iso_week(tms("2022-07-15T12:00:00")), (2022, 28));
+
+        // 1970-01-01 was a Thursday so it's in week 1
+        assert_eq!(time_ms_to_iso_week(0), (1970, 1));
+        assert_eq!(time_ms_to_iso_week(-1), (1970, 1));
+    }
+
+    #[test]
+    fn test_time_ms_to_ordinal() {
+        assert_eq!(time_ms_to_ordinal(tms("2022-01-01T00:00:00")), 1);
+        assert_eq!(time_ms_to_ordinal(tms("2022-07-15T12:00:00")), 196);
+        assert_eq!(time_ms_to_ordinal(tms("2022-12-31T23:59:59.999")), 365);
+        assert_eq!(time_ms_to_ordinal(tms("2020-12-31T00:00:00")), 366);
+        assert_eq!(time_ms_to_ordinal(-1), 365);
+    }
+
+    #[test]
+    fn test_time_ms_part_of_day_utc() {
+        let at = |hour, minute, millis| {
+            time_ms_part_of_day_utc(
+                time_ms_from_utc_parts(2022, 7, 15, hour, minute, 0, millis).unwrap(),
+            )
+        };
+        assert_eq!(at(0, 0, 0), PartOfDay::Night);
+        assert_eq!(at(3, 0, 0), PartOfDay::Night);
+        assert_eq!(at(5, 59, 999), PartOfDay::Night);
+        assert_eq!(at(6, 0, 0), PartOfDay::Morning);
+        assert_eq!(at(11, 59, 999), PartOfDay::Morning);
+        assert_eq!(at(12, 0, 0), PartOfDay::Afternoon);
+        assert_eq!(at(17, 59, 999), PartOfDay::Afternoon);
+        assert_eq!(at(18, 0, 0), PartOfDay::Evening);
+        assert_eq!(at(23, 59, 999), PartOfDay::Evening);
+
+        // 1969-12-31T23:59:59.999Z
+        assert_eq!(time_ms_part_of_day_utc(-1), PartOfDay::Evening);
+    }
+
+    #[test]
+    fn test_time_ms_part_of_day_local() {
+        // The local hour depends on the time zone of the machine
+        for hour in 0..24 {
+            let tms = time_ms_from_utc_parts(2022, 7, 15, hour, 30, 0, 0).unwrap();
+            let local_hour = time_ms_to_utc(tms).with_timezone(&Local).hour();
+            assert_eq!(
+                time_ms_part_of_day_local(tms),
+                PartOfDay::from_hour(local_hour)
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_ms_components_utc_epoch() {
+        assert_eq!(
+            time_ms_components_utc(0),
+            DateTimeComponents {
+                year: 1970,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+                millisecond: 0,
+                weekday: Weekday::Thu,
+                ordinal: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_time_ms_components_utc_negative() {
+        assert_eq!(
+            time_ms_components_utc(-1),
+            DateTimeComponents {
+                year: 1969,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 59,
+                millisecond: 999,
+                weekday: Weekday::Wed,
+                ordinal: 365,
+            }
+        );
+    }
+
+    #[test]
+    fn test_time_ms_from_components_utc_round_trip() {
+        for tms in [0, -1, -86_400_001, 1_657_886_400_123, 951_782_400_000] {
+            let c = time_ms_components_utc(tms);
+            assert_eq!(time_ms_from_components_utc(&c), Ok(tms));
+        }
+
+        // weekday and ordinal are ignored
+        let c = DateTimeComponents {
+            weekday: Weekday::Sun,
+            ordinal: 0,
+            ..time_ms_components_utc(-1)
+        };
+        assert_eq!(time_ms_from_components_utc(&c), Ok(-1));
+    }
+
+    #[test]
+    fn test_time_ms_from_components_utc_invalid() {
+        let epoch = time_ms_components_utc(0);
+        for c in [
+            DateTimeComponents { month: 13, ..epoch },
+            DateTimeComponents { month: 0, ..epoch },
+            DateTimeComponents { day: 0, ..epoch },
+            DateTimeComponents {
+                month: 2,
+                day: 29,
+                ..epoch
+            },
+            DateTimeComponents { hour: 24, ..epoch },
+            DateTimeComponents {
+                millisecond: 1000,
+                ..epoch
+            },
+        ] {
+            assert_eq!(
+                time_ms_from_components_utc(&c),
+                Err(TimeConversionError::OutOfRange),
+                "{c:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_and_verify_weekday() {
+        use TzMassaging::*;
+
+        // 1970-01-01 was a Thursday
+        assert_eq!(
+            parse_and_verify_weekday("1970-01-01T00:00:00Z", Weekday::Thu, HasTz),
+            Ok(0)
+        );
+        assert_eq!(
+            parse_and_verify_weekday("1969-12-31 23:59:59.999", Weekday::Wed, CondAddTzUtc),
+            Ok(-1)
+        );
+
+        // The weekday is checked in UTC, Thursday evening in PST is Friday UTC
+        assert_eq!(
+            parse_and_verify_weekday("2022-07-14T20:00:00-08:00", Weekday::Fri, CondAddTzUtc),
+            Ok(1_657_857_600_000)
+        );
+        assert!(
+            parse_and_verify_weekday("2022-07-14T20:00:00-08:00", Weekday::Thu, CondAddTzUtc)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_and_verify_weekday_errors() {
+        let err = parse_and_verify_weekday("2022-07-15", Weekday::Mon, TzMassaging::CondAddTzUtc)
+            .unwrap_err();
+        dbg!(&err);
+        assert_eq!(
+            err,
+            TimeConversionError::Parse {
+                value: "2022-07-15".into(),
+                reason: "the UTC weekday is Fri, expected Mon".into(),
+            }
+        );
+
+        // A string that doesn't parse is the parse error
+        let err = parse_and_verify_weekday("2022-07-32", Weekday::Mon, TzMassaging::CondAddTzUtc)
+            .unwrap_err();
+        assert!(!err.to_string().contains("weekday"), "{err}");
+    }
+
+    #[test]
+    fn test_year_fraction_to_time_ms() {
+        const DAY: i64 = 24 * 60 * 60 * 1000;
+        assert_eq!(year_fraction_to_time_ms(1970, 0.0), 0);
+        assert_eq!(year_fraction_to_time_ms(1970, 0.5), 365 * DAY / 2);
+        assert_eq!(year_fraction_to_time_ms(1969, 0.0), -365 * DAY);
+        assert_eq!(year_fraction_to_time_ms(1969, 364.0 / 365.0), -DAY);
+
+        // The leap day makes the middle of 2020 a day later than 2021
+        let july_2_2020 = time_ms_from_utc_parts(2020, 7, 2, 0, 0, 0, 0).unwrap();
+        assert_eq!(year_fraction_to_time_ms(2020, 0.5), july_2_2020);
+        let july_2_2021 = time_ms_from_utc_parts(2021, 7, 2, 12, 0, 0, 0).unwrap();
+        assert_eq!(year_fraction_to_time_ms(2021, 0.5), july_2_2021);
+
+        // Just before the end of the year
+        let end_of_2020 = time_ms_from_utc_parts(2021, 1, 1, 0, 0, 0, 0).unwrap();
+        let tms = year_fraction_to_time_ms(2020, 0.999_999_999_9);
+        assert!(tms < end_of_2020 && tms > end_of_2020 - 10, "{tms}");
+
+        // chrono's first and last years
+        let (min_year, max_year) = (NaiveDate::MIN.year(), NaiveDate::MAX.year());
+        let dec_31 = time_ms_from_utc_parts(max_year, 12, 31, 0, 0, 0, 0).unwrap();
+        let tms = year_fraction_to_time_ms(max_year, 0.999_999_999_9);
+        assert!(
+            tms > dec_31 && time_ms_to_utc(tms).year() == max_year,
+            "{tms}"
+        );
+        let jan_1 = time_ms_from_utc_parts(min_year, 1, 1, 0, 0, 0, 0).unwrap();
+        assert_eq!(year_fraction_to_time_ms(min_year, 0.0), jan_1);
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction must be in 0.0..1.0")]
+    fn test_year_fraction_to_time_ms_one() {
+        year_fraction_to_time_ms(2020, 1.0);
+    }
+
+    #[test]
+    fn test_time_ms_is_leap_year() {
+        for (year, leap) in [
+            (1900, false),
+            (1969, false),
+            (1972, true),
+            (2000, true),
+            (2023, false),
+            (2024, true),
+            (2100, false),
+        ] {
+            let jan_1 = time_ms_from_utc_parts(year, 1, 1, 0, 0, 0, 0).unwrap();
+            let dec_31 = time_ms_from_utc_parts(year, 12, 31, 23, 59, 59, 999).unwrap();
+            assert_eq!(time_ms_is_leap_year(jan_1), leap, "{year}");
+            assert_eq!(time_ms_is_leap_year(dec_31), leap, "{year}");
+        }
+
+        // The UTC year, -1 is in 1969
+        assert!(!time_ms_is_leap_year(-1));
+    }
+
+    #[test]
+    fn test_time_ms_days_in_month() {
+        let days = |year, month| {
+            time_ms_days_in_month(time_ms_from_utc_parts(year, month, 1, 12, 0, 0, 0).unwrap())
+        };
+        assert_eq!(days(2000, 2), 29);
+        assert_eq!(days(1900, 2), 28);
+        assert_eq!(days(2024, 2), 29);
+        assert_eq!(days(2023, 2), 28);
+
+        let lengths: Vec<u32> = (1..=12).map(|month| days(2023, month)).collect();
+        assert_eq!(lengths, [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]);
+
+        // The last milli-second of Feb 29 is still in February
+        let feb_29_end = time_ms_from_utc_parts(2000, 2, 29, 23, 59, 59, 999).unwrap();
+        assert_eq!(time_ms_days_in_month(feb_29_end), 29);
+        assert_eq!(time_ms_days_in_month(feb_29_end + 1), 31);
+    }
+
+    #[test]
+    fn test_time_ms_at_fraction_of_month_utc() {
+        let at = |year, month, day, hour| {
+            time_ms_from_utc_parts(year, month, day, hour, 0, 0, 0).unwrap()
+        };
+        let cases = [
+            // A time in the month, fraction, expected
+            // 28 days
+            (at(2021, 2, 10, 6), 0.0, at(2021, 2, 1, 0)),
+            (at(2021, 2, 10, 6), 0.5, at(2021, 2, 15, 0)),
+            (at(2021, 2, 10, 6), 1.0, at(2021, 3, 1, 0)),
+            // 29 days
+            (at(2024, 2, 29, 23), 0.5, at(2024, 2, 15, 12)),
+            (at(2024, 2, 29, 23), 1.0, at(2024, 3, 1, 0)),
+            // 30 days
+            (at(2021, 4, 1, 0), 0.5, at(2021, 4, 16, 0)),
+            // 31 days
+            (at(2021, 1, 31, 0), 0.0, at(2021, 1, 1, 0)),
+            (at(2021, 1, 31, 0), 0.5, at(2021, 1, 16, 12)),
+            (at(2021, 1, 31, 0), 1.0, at(2021, 2, 1, 0)),
+            (at(2021, 12, 1, 0), 1.0, at(2022, 1, 1, 0)),
+            // Before the epoch
+            (-1, 0.0, at(1969, 12, 1, 0)),
+            (-1, 0.5, at(1969, 12, 16, 12)),
+            (-1, 1.0, 0),
+            // Clamped
+            (at(2021, 2, 10, 6), -0.5, at(2021, 2, 1, 0)),
+            (at(2021, 2, 10, 6), 1.5, at(2021, 3, 1, 0)),
+            (at(2021, 2, 10, 6), f64::NEG_INFINITY, at(2021, 2, 1, 0)),
+            (at(2021, 2, 10, 6), f64::INFINITY, at(2021, 3, 1, 0)),
+        ];
+        for (tms, fraction, expected) in cases {
+            assert_eq!(
+                time_ms_at_fraction_of_month_utc(tms, fraction),
+                expected,
+                "{tms} {fraction}"
+            );
+        }
+
+        // Rounded to the nearest milli-second, a 28 day month is
+        // 2_419_200_000ms so 1.5ms is this fraction
+        let feb = at(2021, 2, 1, 0);
+        assert_eq!(
+            time_ms_at_fraction_of_month_utc(feb, 1.5 / 2_419_200_000.0),
+            feb + 2
+        );
+        assert_eq!(
+            time_ms_at_fraction_of_month_utc(feb, 1.4 / 2_419_200_000.0),
+            feb + 1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "time_ms_at_fraction_of_month_utc: fraction must not be NaN")]
+    fn test_time_ms_at_fraction_of_month_utc_nan() {
+        time_ms_at_fraction_of_month_utc(0, f64::NAN);
+    }
+}
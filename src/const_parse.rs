@@ -0,0 +1,273 @@
+use crate::{MS_PER_DAY, MS_PER_HOUR, MS_PER_MIN, MS_PER_SEC};
+
+/// A literal UTC date time string converted to time_ms at compile time,
+/// the build fails if the string is malformed or not UTC.
+///
+/// The string must have the canonical shape accepted by
+/// [`canonical_utc_str_to_time_ms`], "2024-06-01T00:00:00Z" or
+/// "2024-06-01T00:00:00.123Z".
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::time_ms;
+///
+/// const BUILD_CUTOFF: i64 = time_ms!("2024-06-01T00:00:00Z");
+/// assert_eq!(BUILD_CUTOFF, 1_717_200_000_000);
+///
+/// // Constants can be used in match arms
+/// let built_after_cutoff = |tms: i64| match tms {
+///     BUILD_CUTOFF.. => true,
+///     _ => false,
+/// };
+/// assert!(built_after_cutoff(time_ms!("2024-06-01T00:00:00.001Z")));
+/// ```
+///
+/// A time zone other than UTC fails the build:
+/// ```compile_fail
+/// use time_ms_conversions::time_ms;
+///
+/// const BUILD_CUTOFF: i64 = time_ms!("2024-06-01T00:00:00-07:00");
+/// ```
+///
+/// As does an invalid date:
+/// ```compile_fail
+/// use time_ms_conversions::time_ms;
+///
+/// const BUILD_CUTOFF: i64 = time_ms!("2023-02-29T00:00:00Z");
+/// ```
+#[macro_export]
+macro_rules! time_ms {
+    ($dt_str:literal) => {{
+        const TIME_MS: i64 = match $crate::canonical_utc_str_to_time_ms($dt_str) {
+            Ok(time_ms) => time_ms,
+            Err(reason) => panic!("{}", reason),
+        };
+        TIME_MS
+    }};
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date, from Howard
+// Hinnant's days_from_civil
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+
+    // Months start in March so the leap day is last
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+const fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The value of the `len` ASCII digits at `idx`, None if any isn't a digit
+const fn digits(bytes: &[u8], idx: usize, len: usize) -> Option<i64> {
+    let mut value = 0;
+    let mut i = idx;
+    while i < idx + len {
+        if !bytes[i].is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + (bytes[i] - b'0') as i64;
+        i += 1;
+    }
+
+    Some(value)
+}
+
+/// Convert a UTC date time string in the strict canonical shape,
+/// "YYYY-MM-DDTHH:MM:SSZ" with optional 3 digit milli-seconds,
+/// "YYYY-MM-DDTHH:MM:SS.sssZ", to time_ms.
+///
+/// This is a `const fn` so it can be used in constants, see [`time_ms!`],
+/// and the error is a static description of the problem. The year is 4
+/// digits, 0000 to 9999, and only the "Z" time zone is accepted. Use
+/// [`dt_str_to_utc_time_ms`](crate::dt_str_to_utc_time_ms) for other
+/// shapes.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::canonical_utc_str_to_time_ms;
+///
+/// const TIME_MS: Result<i64, &str> = canonical_utc_str_to_time_ms("1969-12-31T23:59:59.999Z");
+/// assert_eq!(TIME_MS, Ok(-1));
+/// assert!(canonical_utc_str_to_time_ms("1970-01-01T00:00:00+01:00").is_err());
+/// ```
+pub const fn canonical_utc_str_to_time_ms(dt_str: &str) -> Result<i64, &'static str> {
+    let bytes = dt_str.as_bytes();
+
+    // 2024-06-01T00:00:00Z
+    // 01234567890123456789
+    if bytes.len() < 20 {
+        return Err("expected a date time such as \"2024-06-01T00:00:00Z\"");
+    }
+    if bytes[4] != b'-' || bytes[7] != b'-' {
+        return Err("expected a '-' between the year, month and day");
+    }
+    if bytes[10] != b'T' {
+        return Err("expected a 'T' between the date and time");
+    }
+    if bytes[13] != b':' || bytes[16] != b':' {
+        return Err("expected a ':' between the hours, minutes and seconds");
+    }
+
+    let (Some(year), Some(month), Some(day)) = (
+        digits(bytes, 0, 4),
+        digits(bytes, 5, 2),
+        digits(bytes, 8, 2),
+    ) else {
+        return Err("expected a YYYY-MM-DD date");
+    };
+    let (Some(hour), Some(minute), Some(second)) = (
+        digits(bytes, 11, 2),
+        digits(bytes, 14, 2),
+        digits(bytes, 17, 2),
+    ) else {
+        return Err("expected a HH:MM:SS time");
+    };
+    if month < 1 || month > 12 {
+        return Err("month out of range");
+    }
+    if day < 1 || day > days_in_month(year, month) {
+        return Err("day out of range");
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err("time out of range");
+    }
+
+    let (millis, tz_idx) = if bytes[19] == b'.' {
+        if bytes.len() < 24 {
+            return Err("expected 3 digits of milli-seconds");
+        }
+        match digits(bytes, 20, 3) {
+            Some(millis) if !bytes[23].is_ascii_digit() => (millis, 23),
+            _ => return Err("expected 3 digits of milli-seconds"),
+        }
+    } else {
+        (0, 19)
+    };
+    match bytes[tz_idx] {
+        b'Z' if bytes.len() == tz_idx + 1 => {}
+        b'Z' => return Err("unexpected characters after the 'Z'"),
+        b'+' | b'-' => return Err("only the UTC time zone 'Z' is accepted"),
+        _ => return Err("expected the UTC time zone 'Z'"),
+    }
+
+    Ok(days_from_civil(year, month, day) * MS_PER_DAY
+        + hour * MS_PER_HOUR
+        + minute * MS_PER_MIN
+        + second * MS_PER_SEC
+        + millis)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPOCH: i64 = time_ms!("1970-01-01T00:00:00Z");
+    const BEFORE_EPOCH: i64 = time_ms!("1969-12-31T23:59:59.999Z");
+    const BUILD_CUTOFF: i64 = time_ms!("2024-06-01T00:00:00Z");
+    const YEAR_0: i64 = time_ms!("0000-01-01T00:00:00Z");
+
+    #[test]
+    fn test_time_ms_macro() {
+        assert_eq!(EPOCH, 0);
+        assert_eq!(BEFORE_EPOCH, -1);
+        assert_eq!(BUILD_CUTOFF, 1_717_200_000_000);
+        assert_eq!(time_ms!("2022-07-15T12:00:00.123Z"), 1_657_886_400_123);
+    }
+
+    #[test]
+    fn test_time_ms_macro_match_arms() {
+        let era = |tms: i64| match tms {
+            YEAR_0..BEFORE_EPOCH => "ancient",
+            BEFORE_EPOCH => "just before",
+            EPOCH..BUILD_CUTOFF => "before the cutoff",
+            BUILD_CUTOFF.. => "after the cutoff",
+            _ => "out of range",
+        };
+        assert_eq!(era(-2), "ancient");
+        assert_eq!(era(-1), "just before");
+        assert_eq!(era(0), "before the cutoff");
+        assert_eq!(era(BUILD_CUTOFF), "after the cutoff");
+        assert_eq!(era(i64::MIN), "out of range");
+    }
+
+    #[test]
+    fn test_canonical_utc_str_to_time_ms() {
+        let cases = [
+            ("1970-01-01T00:00:00Z", 0),
+            ("1970-01-01T00:00:00.001Z", 1),
+            ("1969-12-31T23:59:59.999Z", -1),
+            ("2000-02-29T00:00:00Z", 951_782_400_000),
+            ("2024-06-01T00:00:00Z", 1_717_200_000_000),
+            ("9999-12-31T23:59:59.999Z", 253_402_300_799_999),
+            ("0000-01-01T00:00:00Z", -62_167_219_200_000),
+            ("1600-03-01T00:00:00Z", -11_670_912_000_000),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(canonical_utc_str_to_time_ms(s), Ok(expected), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_canonical_utc_str_to_time_ms_errors() {
+        let cases = [
+            ("", "expected a date time"),
+            ("2024-06-01", "expected a date time"),
+            ("2024-06-01 00:00:00Z", "expected a 'T'"),
+            ("2024/06/01T00:00:00Z", "expected a '-'"),
+            ("2024-06-01T00-00-00Z", "expected a ':'"),
+            ("2O24-06-01T00:00:00Z", "expected a YYYY-MM-DD date"),
+            ("2024-06-01T0a:00:00Z", "expected a HH:MM:SS time"),
+            ("2024-13-01T00:00:00Z", "month out of range"),
+            ("2024-00-01T00:00:00Z", "month out of range"),
+            ("2023-02-29T00:00:00Z", "day out of range"),
+            ("1900-02-29T00:00:00Z", "day out of range"),
+            ("2024-04-31T00:00:00Z", "day out of range"),
+            ("2024-06-01T24:00:00Z", "time out of range"),
+            ("2016-12-31T23:59:60Z", "time out of range"),
+            ("2024-06-01T00:00:00.1Z", "expected 3 digits"),
+            ("2024-06-01T00:00:00.12345Z", "expected 3 digits"),
+            ("2024-06-01T00:00:00.123", "expected 3 digits"),
+            ("2024-06-01T00:00:00ZZ", "after the 'Z'"),
+            ("2024-06-01T00:00:00-07:00", "only the UTC time zone"),
+            ("2024-06-01T00:00:00+00:00", "only the UTC time zone"),
+            ("2024-06-01T00:00:00z", "expected the UTC time zone"),
+            ("2024-06-01T00:00:00 ", "expected the UTC time zone"),
+        ];
+        for (s, reason) in cases {
+            let err = canonical_utc_str_to_time_ms(s).unwrap_err();
+            assert!(err.contains(reason), "{s}: {err}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_canonical_utc_str_to_time_ms_agrees_with_dt_str() {
+        use crate::{dt_str_to_utc_time_ms, time_ms_to_utc_z_string, TzMassaging};
+
+        // Every day of 4 years around a leap year plus some far dates
+        let day_starts =
+            (0..4 * 366).map(|day| time_ms!("1999-01-01T00:00:00Z") + day * MS_PER_DAY);
+        for tms in day_starts.chain([1_657_886_400_123, -62_167_219_200_000, 253_402_300_799_999]) {
+            let s = time_ms_to_utc_z_string(tms);
+            assert_eq!(canonical_utc_str_to_time_ms(&s), Ok(tms), "{s}");
+            assert_eq!(
+                dt_str_to_utc_time_ms(&s, TzMassaging::HasTz),
+                Ok(tms),
+                "{s}"
+            );
+        }
+    }
+}
@@ -0,0 +1,2308 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone,
+    Utc,
+};
+
+use std::borrow::Cow;
+use std::time::Instant;
+
+use crate::{
+    nanos_to_time_ms_rounded, time_ms_to_secs_nsecs, ParseTrace, RoundingMode, TimeConversionError,
+    TimeMsError,
+};
+
+fn nanos_to_time_ms(nanos: i128) -> i64 {
+    nanos_to_time_ms_rounded(nanos, RoundingMode::HalfUp)
+}
+
+// A leap second, 23:59:60.5, is represented by chrono as 23:59:59 with
+// 1_500_000_000 sub-second nanos so it folds into the next second,
+// 00:00:00.5, which time_ms can represent as it has no leap seconds
+pub(crate) fn date_time_to_nanos<Tz: TimeZone>(date_time: &DateTime<Tz>) -> i128 {
+    date_time.timestamp() as i128 * 1_000_000_000 + date_time.timestamp_subsec_nanos() as i128
+}
+
+/// Convert a DateTime<FixedOffset> to utc time_ms, the reverse of
+/// [`time_ms_to_fixed_offset`]
+///
+/// # Example
+/// ```
+/// use chrono::DateTime;
+/// use time_ms_conversions::{fixed_offset_to_time_ms, time_ms_to_fixed_offset};
+///
+/// let dt = DateTime::parse_from_rfc3339("1969-12-31T16:00:00.001-08:00").unwrap();
+/// assert_eq!(fixed_offset_to_time_ms(&dt), 1);
+/// assert_eq!(time_ms_to_fixed_offset(1, *dt.offset()), dt);
+/// ```
+pub fn fixed_offset_to_time_ms(date_time: &DateTime<FixedOffset>) -> i64 {
+    nanos_to_time_ms(date_time_to_nanos(date_time))
+}
+
+pub fn time_ms_to_utc_string(time_ms: i64) -> String {
+    time_ms_to_utc_string_opts(time_ms, SecondsFormat::Millis, false)
+}
+
+pub fn time_ms_to_utc_z_string(time_ms: i64) -> String {
+    time_ms_to_utc_string_opts(time_ms, SecondsFormat::Millis, true)
+}
+
+/// Convert time_ms to an RFC 3339 UTC string with `seconds_format`
+/// fractional seconds, ending in "Z" if `use_z` else "+00:00", as
+/// chrono's `DateTime::to_rfc3339_opts`.
+///
+/// time_ms has no sub milli-second data so `Micros` and `Nanos` are the
+/// milli-seconds padded with zeros, `Secs` drops the milli-seconds,
+/// flooring the time, and `AutoSi` shows milli-seconds only when they're
+/// non-zero.
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+///
+/// # Example
+/// ```
+/// use chrono::SecondsFormat;
+/// use time_ms_conversions::time_ms_to_utc_string_opts;
+///
+/// assert_eq!(
+///     time_ms_to_utc_string_opts(1, SecondsFormat::Micros, true),
+///     "1970-01-01T00:00:00.001000Z"
+/// );
+/// assert_eq!(
+///     time_ms_to_utc_string_opts(-1, SecondsFormat::Secs, false),
+///     "1969-12-31T23:59:59+00:00"
+/// );
+/// ```
+pub fn time_ms_to_utc_string_opts(
+    time_ms: i64,
+    seconds_format: SecondsFormat,
+    use_z: bool,
+) -> String {
+    time_ms_to_utc(time_ms).to_rfc3339_opts(seconds_format, use_z)
+}
+
+/// Convert time_ms to an RFC 3339 string in the machine's local time zone,
+/// "2022-03-01T01:00:00.000-08:00"
+///
+/// The offset is the local offset at that instant, so it changes across
+/// a daylight saving time boundary.
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, as with
+/// [`time_ms_to_utc_string`], see [`try_time_ms_to_utc`]
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms, time_ms_to_local_string, TzMassaging};
+///
+/// let s = time_ms_to_local_string(1_646_125_200_000);
+/// assert_eq!(dt_str_to_utc_time_ms(&s, TzMassaging::HasTz).unwrap(), 1_646_125_200_000);
+/// ```
+pub fn time_ms_to_local_string(time_ms: i64) -> String {
+    time_ms_to_local(time_ms).to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+/// True if time_ms formatted as a local time without an offset, as
+/// `TzMassaging::LocalTz` parses, is ambiguous in the machine's local time
+/// zone so it can't be parsed back to the same time_ms.
+///
+/// This is the repeated hour when the clocks fall back at the end of
+/// daylight saving time, in America/Los_Angeles 2022-11-06 01:00 to 01:59
+/// happens twice, both 2022-11-06T08:30:00Z and 09:30:00Z are 01:30 local.
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::local_formatting_is_ambiguous;
+///
+/// // 2022-07-15T12:00:00Z isn't near a daylight saving time transition
+/// assert!(!local_formatting_is_ambiguous(1_657_886_400_000));
+/// ```
+pub fn local_formatting_is_ambiguous(time_ms: i64) -> bool {
+    // The naive local time is what formatting without an offset keeps
+    let naive_local = time_ms_to_local(time_ms).naive_local();
+    matches!(
+        Local.from_local_datetime(&naive_local),
+        LocalResult::Ambiguous(_, _)
+    )
+}
+
+/// Get Utc::now() and convert to time_ms
+///
+/// # Example
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use time_ms_conversions::utc_now_to_time_ms;
+///
+/// let before: i64 = Utc::now().timestamp_millis();
+///
+/// assert!(utc_now_to_time_ms() >= before);
+/// ```
+pub fn utc_now_to_time_ms() -> i64 {
+    utc_to_time_ms(&Utc::now())
+}
+
+/// Convert time_ms to DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use time_ms_conversions::{utc_to_time_ms, time_ms_to_utc};
+///
+/// let epoch: DateTime<Utc> = time_ms_to_utc(0);
+/// assert_eq!(utc_to_time_ms(&epoch), 0);
+/// ```
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+pub fn time_ms_to_utc(time_ms: i64) -> DateTime<Utc> {
+    try_time_ms_to_utc(time_ms).expect("time_ms out of range for DateTime<Utc>")
+}
+
+/// Convert time_ms to DateTime<Utc>, returning `TimeMsError::OutOfRange`
+/// rather than panicking if it's out of the range of DateTime<Utc>,
+/// about +/-262,000 years.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_to_utc, try_time_ms_to_utc, TimeMsError};
+///
+/// assert_eq!(try_time_ms_to_utc(0), Ok(time_ms_to_utc(0)));
+/// assert_eq!(try_time_ms_to_utc(i64::MAX), Err(TimeMsError::OutOfRange));
+/// ```
+pub fn try_time_ms_to_utc(time_ms: i64) -> Result<DateTime<Utc>, TimeMsError> {
+    let (secs, nsecs) = time_ms_to_secs_nsecs(time_ms);
+    DateTime::from_timestamp(secs, nsecs).ok_or(TimeMsError::OutOfRange)
+}
+
+/// Convert time_ms to DateTime<Local>, the machine's local time zone
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_to_local, time_ms_to_utc};
+///
+/// assert_eq!(time_ms_to_local(0), time_ms_to_utc(0));
+/// ```
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+pub fn time_ms_to_local(time_ms: i64) -> DateTime<Local> {
+    time_ms_to_utc(time_ms).with_timezone(&Local)
+}
+
+/// Convert time_ms to DateTime<FixedOffset> in `offset`, independent of
+/// the machine's local time zone
+///
+/// # Example
+/// ```
+/// use chrono::FixedOffset;
+/// use time_ms_conversions::{time_ms_to_fixed_offset, utc_to_time_ms};
+///
+/// let jst = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+/// let dt = time_ms_to_fixed_offset(0, jst);
+/// assert_eq!(dt.to_string(), "1970-01-01 09:00:00 +09:00");
+/// assert_eq!(utc_to_time_ms(&dt.to_utc()), 0);
+/// ```
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+pub fn time_ms_to_fixed_offset(time_ms: i64, offset: FixedOffset) -> DateTime<FixedOffset> {
+    time_ms_to_utc(time_ms).with_timezone(&offset)
+}
+
+/// Convert time_ms to an RFC 3339 string in `offset`,
+/// "2022-07-15T21:00:00.123+09:00", the deterministic sibling of
+/// [`time_ms_to_local_string`]
+///
+/// # Example
+/// ```
+/// use chrono::FixedOffset;
+/// use time_ms_conversions::time_ms_to_fixed_offset_string;
+///
+/// let jst = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+/// assert_eq!(
+///     time_ms_to_fixed_offset_string(1_657_886_400_123, jst),
+///     "2022-07-15T21:00:00.123+09:00"
+/// );
+/// ```
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>, see [`try_time_ms_to_utc`]
+pub fn time_ms_to_fixed_offset_string(time_ms: i64, offset: FixedOffset) -> String {
+    time_ms_to_fixed_offset(time_ms, offset).to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+/// Format time_ms in UTC with the strftime format `fmt`, such as
+/// "%Y%m%d_%H%M%S" for a file name, see [`chrono::format::strftime`]
+///
+/// Returns `TimeMsError::Parse` for an invalid format specifier, where
+/// chrono's `format()` would panic, and `TimeMsError::OutOfRange` if
+/// time_ms is out of the range of DateTime<Utc>.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_string_with_fmt;
+///
+/// assert_eq!(
+///     time_ms_to_string_with_fmt(1_657_886_400_123, "%Y%m%d_%H%M%S").unwrap(),
+///     "20220715_120000"
+/// );
+/// assert!(time_ms_to_string_with_fmt(0, "%Q").is_err());
+/// ```
+pub fn time_ms_to_string_with_fmt(time_ms: i64, fmt: &str) -> Result<String, TimeMsError> {
+    format_with_fmt(&try_time_ms_to_utc(time_ms)?, fmt)
+}
+
+/// Convert time_ms to a UTC string formatted with the strftime pattern
+/// `fmt`, such as "%Y/%m/%d %H:%M", see [`chrono::format::strftime`]
+///
+/// # Panics
+///
+/// If `fmt` has an invalid format specifier, chrono's `format()` panics
+/// on display, or if time_ms is out of the range of DateTime<Utc>. See
+/// [`time_ms_to_string_with_fmt`] which returns a Result for both.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_utc_custom_fmt;
+///
+/// assert_eq!(time_ms_to_utc_custom_fmt(1_609_459_200_000, "%Y/%m/%d %H:%M"), "2021/01/01 00:00");
+/// ```
+pub fn time_ms_to_utc_custom_fmt(time_ms: i64, fmt: &str) -> String {
+    time_ms_to_utc(time_ms).format(fmt).to_string()
+}
+
+/// [`time_ms_to_string_with_fmt`] in `offset` rather than UTC, "%z" is
+/// `offset`
+///
+/// # Example
+/// ```
+/// use chrono::FixedOffset;
+/// use time_ms_conversions::time_ms_to_string_with_fmt_and_offset;
+///
+/// let jst = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+/// assert_eq!(
+///     time_ms_to_string_with_fmt_and_offset(1_657_886_400_123, "%d %b %Y %H:%M %z", jst).unwrap(),
+///     "15 Jul 2022 21:00 +0900"
+/// );
+/// ```
+pub fn time_ms_to_string_with_fmt_and_offset(
+    time_ms: i64,
+    fmt: &str,
+    offset: FixedOffset,
+) -> Result<String, TimeMsError> {
+    format_with_fmt(&try_time_ms_to_utc(time_ms)?.with_timezone(&offset), fmt)
+}
+
+fn format_with_fmt<Tz: TimeZone>(date_time: &DateTime<Tz>, fmt: &str) -> Result<String, TimeMsError>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    use std::fmt::Write;
+
+    // Check the items first, chrono's Display of an invalid item is an error
+    // that format!() and to_string() turn into a panic
+    let items: Vec<Item> = StrftimeItems::new(fmt).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(TimeMsError::parse(fmt, "invalid strftime format specifier"));
+    }
+
+    let mut s = String::new();
+    write!(s, "{}", date_time.format_with_items(items.into_iter()))
+        .map_err(|_| TimeMsError::parse(fmt, "unable to format"))?;
+
+    Ok(s)
+}
+
+/// time_ms of a UTC date and time given as parts, None if any part is invalid
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_from_utc_parts;
+///
+/// assert_eq!(time_ms_from_utc_parts(1970, 1, 1, 0, 0, 0, 1), Some(1));
+/// assert_eq!(time_ms_from_utc_parts(1969, 12, 31, 23, 59, 59, 999), Some(-1));
+/// assert_eq!(time_ms_from_utc_parts(2022, 2, 29, 0, 0, 0, 0), None);
+/// ```
+pub fn time_ms_from_utc_parts(
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    millis: u32,
+) -> Option<i64> {
+    // and_hms_milli_opt allows a leap second as 1000+ms, which isn't a time_ms
+    if millis >= 1000 {
+        return None;
+    }
+    let ndt = NaiveDate::from_ymd_opt(year, month, day)?
+        .and_hms_milli_opt(hour, minute, second, millis)?;
+
+    Some(utc_to_time_ms(&ndt.and_utc()))
+}
+
+/// Convert a DateTime<Utc> to time_ms
+///
+/// # Examples
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use time_ms_conversions::time_ms_to_utc;
+///
+/// let dt: DateTime<Utc> = time_ms_to_utc(0);
+/// assert_eq!(dt.to_string(), "1970-01-01 00:00:00 UTC");
+/// ```
+pub fn utc_to_time_ms(date_time: &DateTime<Utc>) -> i64 {
+    nanos_to_time_ms(date_time_to_nanos(date_time))
+}
+
+/// Convert a DateTime<Utc> to time_ms using `mode` to round
+/// sub milli-second values
+///
+/// # Examples
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use time_ms_conversions::{utc_to_time_ms_rounded, RoundingMode};
+///
+/// // -1.5ms
+/// let dt: DateTime<Utc> = DateTime::from_timestamp(-1, 998_500_000).unwrap();
+/// assert_eq!(utc_to_time_ms_rounded(&dt, RoundingMode::Truncate), -1);
+/// assert_eq!(utc_to_time_ms_rounded(&dt, RoundingMode::Floor), -2);
+/// ```
+pub fn utc_to_time_ms_rounded(date_time: &DateTime<Utc>, mode: RoundingMode) -> i64 {
+    nanos_to_time_ms_rounded(date_time_to_nanos(date_time), mode)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TzMassaging {
+    CondAddTzUtc,
+    HasTz,
+    LocalTz,
+
+    /// As CondAddTzUtc's detection but a missing time zone is
+    /// `TimeConversionError::MissingTimezone` rather than UTC
+    RequireTz,
+}
+
+/// DateTime string converted to utc time_ms with either T or Space seperator
+///
+/// A date only string, "2022-07-15", is converted to midnight. That is
+/// UTC midnight for CondAddTzUtc and HasTz, local midnight for LocalTz and
+/// `TimeConversionError::MissingTimezone` for RequireTz.
+/// As with the date portion of a date time, single digit months and days,
+/// "2022-7-5", are accepted.
+///
+/// The ISO 8601 basic format without separators, "19700101T000000.123Z",
+/// is also accepted if the string doesn't parse in the extended format.
+///
+/// A decimal comma before the fractional seconds, "1970-01-01T00:00:00,123",
+/// is accepted as a period.
+///
+/// A leap second, "2016-12-31T23:59:60.500Z", is folded into the next
+/// second, as time_ms has no leap seconds, so it's the same time_ms as
+/// "2017-01-01T00:00:00.500Z". Times during a leap second therefore
+/// aren't distinct from those in the second after it but the conversion
+/// never goes backwards.
+///
+/// # Examples
+/// ```
+/// use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+/// use time_ms_conversions::{dt_str_to_utc_time_ms, TzMassaging};
+///
+/// // Might not have time zone and if not assume it's UTC
+/// let str_time_no_ms = "1970-01-01 00:00:00";
+/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format");
+/// assert_eq!(ts, 0);
+///
+/// // If it does have UTC that's fine too
+/// let str_time_no_ms = "1970-01-01 00:00:00+00:00";
+/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format");
+/// assert_eq!(ts, 0);
+///
+/// // If timezone is 'Z' for UTC that's fine
+/// let str_time_no_ms = "1970-01-01 00:00:00Z";
+/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format");
+/// assert_eq!(ts, 0);
+///
+/// // If timezone is 'Z' for UTC that's fine
+/// let str_time_no_ms = "1970-01-01T00:00:00Z";
+/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format");
+/// assert_eq!(ts, 0);
+///
+/// // And CondAddTzUtz handles other time zones and converts them to Utc
+/// let str_time_pst = "1969-12-31T16:00:00-0800";
+/// let ts_utc = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format with milliseconds");
+/// assert_eq!(ts_utc, 0);
+///
+/// // Assume there is a timezone, this is more efficient then CondAddTzUtc
+/// let str_time_tz = "1970-01-01 00:00:00+0000";
+/// let ts = dt_str_to_utc_time_ms(str_time_tz, TzMassaging::HasTz).expect("Bad time format");
+/// assert_eq!(ts, 0);
+///
+/// // UTC time_ms 0 as a Date Time string without time zone
+/// let utc_time_ms_0_str = "1970-01-01T00:00:00";
+///
+/// // Get a NaiveDateTime
+/// let ndt: NaiveDateTime = utc_time_ms_0_str.parse().unwrap();
+///
+/// // Convert to the local time of where ever this is running
+/// let ldt = Local.from_local_datetime(&ndt).unwrap();
+///
+/// // Get the offset from local time to utc, a negative value for west of UTC
+/// // This will be -08:00 for PST
+/// let ldt_offset = ldt.offset();
+/// dbg!(ldt_offset);
+///
+/// // Get the value to add to UTC to convert to local time
+/// // This will be -28800000 = (-8 * 60 * 60 * 1000) for PST
+/// let milli_seconds_to_add_to_convert_to_utc = ldt_offset.local_minus_utc() as i64 * 1000;
+/// dbg!(milli_seconds_to_add_to_convert_to_utc);
+///
+/// // Adding milli_seconds_to_add_to_convert_to_utc to tms will equal 0 for all timezones
+/// // For PST tms is 28800000 and adding -28800000 is 0
+/// let tms = dt_str_to_utc_time_ms(utc_time_ms_0_str, TzMassaging::LocalTz)
+///     .expect("Bad time format");
+/// assert_eq!(tms + milli_seconds_to_add_to_convert_to_utc, 0);
+/// ```
+pub fn dt_str_to_utc_time_ms(
+    dt_str: &str,
+    tz_massaging: TzMassaging,
+) -> Result<i64, TimeConversionError> {
+    parse_dt_str(dt_str, tz_massaging, &mut ParseTrace::default())
+        .map(|dtfo| fixed_offset_to_time_ms(&dtfo))
+}
+
+/// [`dt_str_to_utc_time_ms`] also returning the time zone offset of the
+/// DateTime string
+///
+/// The offset is UTC, `FixedOffset::east_opt(0)`, when there is no time
+/// zone with CondAddTzUtc and the local offset in effect with LocalTz.
+///
+/// # Example
+/// ```
+/// use chrono::FixedOffset;
+/// use time_ms_conversions::{dt_str_to_utc_time_ms_with_offset, TzMassaging};
+///
+/// let (tms, offset) =
+///     dt_str_to_utc_time_ms_with_offset("1969-12-31T16:00:00-08:00", TzMassaging::HasTz).unwrap();
+/// assert_eq!(tms, 0);
+/// assert_eq!(offset, FixedOffset::west_opt(8 * 60 * 60).unwrap());
+///
+/// let (tms, offset) =
+///     dt_str_to_utc_time_ms_with_offset("1970-01-01 00:00:00", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!((tms, offset.local_minus_utc()), (0, 0));
+/// ```
+pub fn dt_str_to_utc_time_ms_with_offset(
+    dt_str: &str,
+    tz_massaging: TzMassaging,
+) -> Result<(i64, FixedOffset), TimeConversionError> {
+    let dtfo = parse_dt_str(dt_str, tz_massaging, &mut ParseTrace::default())?;
+
+    Ok((fixed_offset_to_time_ms(&dtfo), *dtfo.offset()))
+}
+
+/// [`dt_str_to_utc_time_ms`] recording the branch taken and the time it
+/// took in `trace`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms_traced, DtSeparator, ParseTrace, TzMassaging};
+///
+/// let mut trace = ParseTrace::default();
+/// let tms = dt_str_to_utc_time_ms_traced("1970-01-01 00:00:00+0000", TzMassaging::CondAddTzUtc, &mut trace);
+/// assert_eq!(tms, Ok(0));
+/// assert_eq!(trace.branch.separator, Some(DtSeparator::Space));
+/// assert!(trace.branch.has_offset);
+/// assert_eq!(trace.branch.format, Some("%Y-%m-%d %H:%M:%S%.f%#z"));
+/// ```
+pub fn dt_str_to_utc_time_ms_traced(
+    dt_str: &str,
+    tz_massaging: TzMassaging,
+    trace: &mut ParseTrace,
+) -> Result<i64, TimeMsError> {
+    *trace = ParseTrace::default();
+    let start = Instant::now();
+    let result =
+        parse_dt_str(dt_str, tz_massaging, trace).map(|dtfo| fixed_offset_to_time_ms(&dtfo));
+    trace.elapsed_nanos = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+    trace.ok = result.is_ok();
+
+    result
+}
+
+pub(crate) fn parse_dt_str(
+    dt_str: &str,
+    tz_massaging: TzMassaging,
+    trace: &mut ParseTrace,
+) -> Result<DateTime<FixedOffset>, TimeConversionError> {
+    // A date without a time, "2022-07-15", is midnight. As there is no
+    // time zone it's UTC midnight except for LocalTz where it's local midnight.
+    let date_str = dt_str.trim();
+    if !date_str.contains(['T', 't', ' ', ':']) {
+        trace.branch.format = Some(DATE_FORMAT);
+        let ndt = NaiveDate::parse_from_str(date_str, DATE_FORMAT)
+            .map_err(|e| TimeConversionError::parse(date_str, e))?
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is always valid");
+        return match tz_massaging {
+            TzMassaging::LocalTz => naive_local_to_fo(date_str, &ndt),
+            TzMassaging::CondAddTzUtc | TzMassaging::HasTz => Ok(ndt.and_utc().fixed_offset()),
+            TzMassaging::RequireTz => {
+                Err(TimeConversionError::MissingTimezone(date_str.to_string()))
+            }
+        };
+    }
+
+    // Report which separator was attempted so a failure isn't mistaken
+    // for one of the other format.
+    let separator = detect_separator(date_str)?;
+    parse_dt_str_with_separator(date_str, separator, tz_massaging, trace)
+}
+
+// parse_dt_str after the separator is known, normalizing a decimal comma
+// and naming the separator in a parse error
+fn parse_dt_str_with_separator(
+    dt_str: &str,
+    separator: DtSeparator,
+    tz_massaging: TzMassaging,
+    trace: &mut ParseTrace,
+) -> Result<DateTime<FixedOffset>, TimeConversionError> {
+    trace.branch.separator = Some(separator);
+    let dt_str = normalize_decimal_comma(dt_str.trim());
+    dt_str_with_separator_to_fo(&dt_str, separator, tz_massaging, trace).map_err(|e| match e {
+        TimeConversionError::Parse { value, reason } => TimeConversionError::Parse {
+            value,
+            reason: format!("with a {} separator, {reason}", separator.name()),
+        },
+        e => e,
+    })
+}
+
+// Some European sources use a decimal comma for the fractional seconds,
+// "1970-01-01T00:00:00,123". A single comma between the seconds and the
+// fraction is changed to a period, any other comma is left to be rejected.
+pub(crate) fn normalize_decimal_comma(dt_str: &str) -> Cow<'_, str> {
+    let mut commas = dt_str.match_indices(',');
+    let (Some((idx, _)), None) = (commas.next(), commas.next()) else {
+        return Cow::Borrowed(dt_str);
+    };
+
+    let (before, after) = (&dt_str.as_bytes()[..idx], &dt_str.as_bytes()[idx + 1..]);
+    let after_seconds = before.len() >= 2
+        && before[before.len() - 2..].iter().all(u8::is_ascii_digit)
+        && (before.contains(&b':') || before.contains(&b'T'));
+    if after_seconds && after.first().is_some_and(u8::is_ascii_digit) {
+        Cow::Owned(format!("{}.{}", &dt_str[..idx], &dt_str[idx + 1..]))
+    } else {
+        Cow::Borrowed(dt_str)
+    }
+}
+
+// Exactly one 'T', or no 'T' and a space, must separate the date and time
+pub(crate) fn detect_separator(dt_str: &str) -> Result<DtSeparator, TimeConversionError> {
+    match dt_str.matches('T').count() {
+        1 => Ok(DtSeparator::T),
+        0 if dt_str.contains(' ') => Ok(DtSeparator::Space),
+        0 => Err(TimeConversionError::parse(
+            dt_str,
+            "no 'T' or ' ' separator between the date and time",
+        )),
+        n => Err(TimeConversionError::parse(
+            dt_str,
+            format!("expected one 'T' separator between the date and time, found {n}"),
+        )),
+    }
+}
+
+/// The separator between the date and time of a DateTime string
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DtSeparator {
+    /// "2022-07-15T09:30:00"
+    T,
+
+    /// "2022-07-15 09:30:00"
+    Space,
+}
+
+impl DtSeparator {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            DtSeparator::T => "'T'",
+            DtSeparator::Space => "' '",
+        }
+    }
+}
+
+/// Convert many DateTime strings just as [`dt_str_to_utc_time_ms`] does,
+/// one result per string
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_strs_to_utc_time_ms, TzMassaging};
+///
+/// let results = dt_strs_to_utc_time_ms(
+///     &["1970-01-01T00:00:01", "1970-01-01 00:00:02Z", "bad"],
+///     TzMassaging::CondAddTzUtc,
+/// );
+/// assert_eq!(results[0], Ok(1000));
+/// assert_eq!(results[1], Ok(2000));
+/// assert!(results[2].is_err());
+/// ```
+pub fn dt_strs_to_utc_time_ms(
+    dt_strs: &[&str],
+    tz_massaging: TzMassaging,
+) -> Vec<Result<i64, TimeConversionError>> {
+    dt_strs
+        .iter()
+        .map(|dt_str| dt_str_to_utc_time_ms(dt_str, tz_massaging))
+        .collect()
+}
+
+/// Split `s` on `delimiter` and convert each trimmed DateTime string as
+/// [`dt_str_to_utc_time_ms`] does, such as for a list given on the
+/// command line. An empty or all whitespace `s` is an empty list.
+///
+/// Stops at the first string that doesn't convert and returns its index
+/// with the error.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{parse_time_ms_list, TzMassaging};
+///
+/// let list = "1970-01-01T00:00:01, 1970-01-01T00:00:02Z,1970-01-01";
+/// assert_eq!(parse_time_ms_list(list, ',', TzMassaging::CondAddTzUtc), Ok(vec![1000, 2000, 0]));
+///
+/// let (idx, _e) = parse_time_ms_list("1970-01-01;bad", ';', TzMassaging::CondAddTzUtc).unwrap_err();
+/// assert_eq!(idx, 1);
+/// ```
+pub fn parse_time_ms_list(
+    s: &str,
+    delimiter: char,
+    tz_massaging: TzMassaging,
+) -> Result<Vec<i64>, (usize, TimeConversionError)> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    s.split(delimiter)
+        .enumerate()
+        .map(|(idx, dt_str)| {
+            dt_str_to_utc_time_ms(dt_str.trim(), tz_massaging).map_err(|e| (idx, e))
+        })
+        .collect()
+}
+
+/// Convert many DateTime strings that all use `separator`, skipping the
+/// per string detection of [`dt_strs_to_utc_time_ms`].
+///
+/// Date only strings aren't accepted, with `DtSeparator::T` the ISO 8601
+/// basic format is still accepted, as is a decimal comma.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_strs_with_separator_to_utc_time_ms, DtSeparator, TzMassaging};
+///
+/// let results = dt_strs_with_separator_to_utc_time_ms(
+///     &["1970-01-01T00:00:01", "19700101T000002Z"],
+///     DtSeparator::T,
+///     TzMassaging::CondAddTzUtc,
+/// );
+/// assert_eq!(results, vec![Ok(1000), Ok(2000)]);
+/// ```
+pub fn dt_strs_with_separator_to_utc_time_ms(
+    dt_strs: &[&str],
+    separator: DtSeparator,
+    tz_massaging: TzMassaging,
+) -> Vec<Result<i64, TimeConversionError>> {
+    dt_strs
+        .iter()
+        .map(|dt_str| {
+            parse_dt_str_with_separator(dt_str, separator, tz_massaging, &mut ParseTrace::default())
+                .map(|dtfo| fixed_offset_to_time_ms(&dtfo))
+        })
+        .collect()
+}
+
+// A chrono format without and with a trailing time zone
+struct DtFormat {
+    naive: &'static str,
+    with_tz: &'static str,
+}
+
+const T_FORMAT: DtFormat = DtFormat {
+    naive: "%Y-%m-%dT%H:%M:%S%.f",
+    with_tz: "%Y-%m-%dT%H:%M:%S%.f%#z",
+};
+
+const SPACE_FORMAT: DtFormat = DtFormat {
+    naive: "%Y-%m-%d %H:%M:%S%.f",
+    with_tz: "%Y-%m-%d %H:%M:%S%.f%#z",
+};
+
+// A date without a time
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// ISO 8601 basic format, 19700101T000000
+const BASIC_FORMAT: DtFormat = DtFormat {
+    naive: "%Y%m%dT%H%M%S%.f",
+    with_tz: "%Y%m%dT%H%M%S%.f%#z",
+};
+
+fn dt_str_with_separator_to_fo(
+    dt_str: &str,
+    separator: DtSeparator,
+    tz_massaging: TzMassaging,
+    trace: &mut ParseTrace,
+) -> Result<DateTime<FixedOffset>, TimeConversionError> {
+    match separator {
+        DtSeparator::T => {
+            match dt_str_with_fmt_to_fo(dt_str, &T_FORMAT, tz_massaging, trace) {
+                Ok(dtfo) => Ok(dtfo),
+                Err(e) => {
+                    // Fall back to the ISO 8601 basic format only if the
+                    // date has no separators.
+                    if !has_basic_date(dt_str.trim()) {
+                        return Err(e);
+                    }
+                    dt_str_with_fmt_to_fo(dt_str, &BASIC_FORMAT, tz_massaging, trace)
+                }
+            }
+        }
+        DtSeparator::Space => dt_str_with_fmt_to_fo(dt_str, &SPACE_FORMAT, tz_massaging, trace),
+    }
+}
+
+// The date of the ISO 8601 basic format has no separators, 19700101
+pub(crate) fn has_basic_date(date_str: &str) -> bool {
+    date_str.len() > 8 && date_str.as_bytes()[..8].iter().all(u8::is_ascii_digit)
+}
+
+// A 'Z', a '+' or a '-' after the date means there is a time zone
+pub(crate) fn has_tz_offset(dt_str: &str) -> bool {
+    // Check if we have a Z|z timezone
+    let last_char = dt_str.chars().last();
+    if last_char == Some('Z') || last_char == Some('z') {
+        return true;
+    }
+
+    // No, see if there is a '+' then there "must be" a time zone
+    let has_pos_tz = dt_str.matches('+').count() > 0;
+
+    // If there is a '-' after the "year" then there must be a time zone
+    let mut rmtchr = dt_str.rmatch_indices('-');
+    let first_rmatch = rmtchr.next();
+    let has_neg_tz = if let Some((idx, _s)) = first_rmatch {
+        // If there is a '-' after index 7 then assume there is a negative time zone
+        //     2020-01-01T...
+        //     01234567
+        idx > 7
+    } else {
+        // No numeric timezone
+        false
+    };
+
+    has_pos_tz || has_neg_tz
+}
+
+fn dt_str_with_fmt_to_fo(
+    dt_str: &str,
+    fmt: &DtFormat,
+    tz_massaging: TzMassaging,
+    trace: &mut ParseTrace,
+) -> Result<DateTime<FixedOffset>, TimeConversionError> {
+    let dt_str = dt_str.trim();
+    let parse_err = |e| TimeConversionError::parse(dt_str, e);
+    trace.branch.has_offset = false;
+    trace.branch.appended_utc = false;
+    match tz_massaging {
+        TzMassaging::HasTz => {
+            trace.branch.has_offset = true;
+            trace.branch.format = Some(fmt.with_tz);
+            DateTime::parse_from_str(dt_str, fmt.with_tz).map_err(parse_err)
+        }
+        TzMassaging::CondAddTzUtc => {
+            trace.branch.has_offset = has_tz_offset(dt_str);
+            trace.branch.appended_utc = !trace.branch.has_offset;
+            if trace.branch.has_offset {
+                trace.branch.format = Some(fmt.with_tz);
+                DateTime::parse_from_str(dt_str, fmt.with_tz).map_err(parse_err)
+            } else {
+                // UTC, parsed as naive so no "+0000" is appended to a copy
+                trace.branch.format = Some(fmt.naive);
+                let ndt = NaiveDateTime::parse_from_str(dt_str, fmt.naive).map_err(parse_err)?;
+                Ok(ndt.and_utc().fixed_offset())
+            }
+        }
+        TzMassaging::RequireTz => {
+            trace.branch.format = Some(fmt.with_tz);
+            trace.branch.has_offset = has_tz_offset(dt_str);
+            if !trace.branch.has_offset {
+                return Err(TimeConversionError::MissingTimezone(dt_str.to_string()));
+            }
+            DateTime::parse_from_str(dt_str, fmt.with_tz).map_err(parse_err)
+        }
+        TzMassaging::LocalTz => {
+            // Convert datetime string to DateTime<Local>
+            // from: https://stackoverflow.com/questions/65820170/parsing-a-datetime-string-to-local-time-in-rust-chrono?rq=1
+            trace.branch.format = Some(fmt.naive);
+            let ndt = NaiveDateTime::parse_from_str(dt_str, fmt.naive).map_err(parse_err)?;
+            naive_local_to_fo(dt_str, &ndt)
+        }
+    }
+}
+
+pub(crate) fn naive_local_to_fo(
+    dt_str: &str,
+    ndt: &NaiveDateTime,
+) -> Result<DateTime<FixedOffset>, TimeConversionError> {
+    let ldt = match Local.from_local_datetime(ndt) {
+        chrono::LocalResult::None => {
+            return Err(TimeConversionError::parse(dt_str, "No result"));
+        }
+        chrono::LocalResult::Single(dt) => dt,
+        chrono::LocalResult::Ambiguous(_, _) => {
+            return Err(TimeConversionError::parse(dt_str, "Ambigious result"));
+        }
+    };
+
+    // Keep the local offset that was in effect
+    Ok(ldt.fixed_offset())
+}
+
+/// RFC 2822 date time string, such as an email `Date:` header, converted to utc time_ms
+///
+/// The day of week is optional and obsolete forms chrono accepts are
+/// tolerated, the "GMT"/"UT" and US zone names and two digit years,
+/// 00-49 are 20xx and 50-99 are 19xx.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::rfc2822_str_to_utc_time_ms;
+///
+/// let tms = rfc2822_str_to_utc_time_ms("Tue, 1 Jul 2003 10:52:37 +0200").unwrap();
+/// assert_eq!(tms, 1_057_049_557_000);
+/// assert_eq!(rfc2822_str_to_utc_time_ms("1 Jul 03 08:52:37 GMT").unwrap(), tms);
+/// ```
+pub fn rfc2822_str_to_utc_time_ms(s: &str) -> Result<i64, TimeMsError> {
+    let dtfo = DateTime::parse_from_rfc2822(s.trim()).map_err(|e| TimeMsError::parse(s, e))?;
+
+    Ok(fixed_offset_to_time_ms(&dtfo))
+}
+
+/// RFC 3339 date time string converted to utc time_ms, only if its offset
+/// is UTC, "Z", "+00:00" or "-00:00"
+///
+/// For a strict ingest that forbids non-UTC submissions, any other offset,
+/// even one that converts to the same time_ms, is a
+/// `TimeConversionError::NonUtcOffset`.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{rfc3339_require_utc_to_time_ms, TimeConversionError};
+///
+/// assert_eq!(rfc3339_require_utc_to_time_ms("2022-07-15T12:00:00.123Z"), Ok(1_657_886_400_123));
+/// assert!(matches!(
+///     rfc3339_require_utc_to_time_ms("2022-07-15T04:00:00.123-08:00"),
+///     Err(TimeConversionError::NonUtcOffset { offset_seconds: -28_800, .. })
+/// ));
+/// ```
+pub fn rfc3339_require_utc_to_time_ms(s: &str) -> Result<i64, TimeConversionError> {
+    let s = s.trim();
+    let dtfo = DateTime::parse_from_rfc3339(s).map_err(|e| TimeConversionError::parse(s, e))?;
+    let offset_seconds = dtfo.offset().local_minus_utc();
+    if offset_seconds != 0 {
+        return Err(TimeConversionError::NonUtcOffset {
+            value: s.to_string(),
+            offset_seconds,
+        });
+    }
+
+    Ok(fixed_offset_to_time_ms(&dtfo))
+}
+
+/// Convert time_ms to an RFC 2822 string in UTC, "Tue, 1 Jul 2003 08:52:37 +0000"
+///
+/// RFC 2822 has no fractional seconds so the milli-seconds are dropped,
+/// flooring the time.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::time_ms_to_rfc2822_string;
+///
+/// assert_eq!(time_ms_to_rfc2822_string(1_057_049_557_999), "Tue, 1 Jul 2003 08:52:37 +0000");
+/// assert_eq!(time_ms_to_rfc2822_string(-1), "Wed, 31 Dec 1969 23:59:59 +0000");
+/// ```
+pub fn time_ms_to_rfc2822_string(time_ms: i64) -> String {
+    time_ms_to_utc(time_ms).to_rfc2822()
+}
+
+/// Time only string, "09:30:00.250", converted to utc time_ms on the date of `date_ms`
+///
+/// The time string is HH:MM:SS with optional fractional seconds and time
+/// zone, a string containing a date is rejected. The date is the UTC date
+/// of `date_ms` except for LocalTz where it's the local date, so a
+/// `date_ms` from a date only string parsed with the same TzMassaging
+/// gives that date. The combined date and time is then converted just as
+/// `dt_str_to_utc_time_ms` does.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms, time_str_to_utc_time_ms, TzMassaging};
+///
+/// let date_ms = dt_str_to_utc_time_ms("2022-07-15", TzMassaging::CondAddTzUtc).unwrap();
+/// let tms = time_str_to_utc_time_ms("09:30:00.250", date_ms, TzMassaging::CondAddTzUtc)
+///     .expect("Bad time format");
+/// assert_eq!(tms, date_ms + (9 * 60 + 30) * 60 * 1000 + 250);
+///
+/// assert!(time_str_to_utc_time_ms("2022-07-15T09:30:00", date_ms, TzMassaging::CondAddTzUtc).is_err());
+/// ```
+pub fn time_str_to_utc_time_ms(
+    time_str: &str,
+    date_ms: i64,
+    tz_massaging: TzMassaging,
+) -> Result<i64, TimeConversionError> {
+    let time_str = time_str.trim();
+
+    // A time starts with the hour, so there must be a ':' and no
+    // date separator before it.
+    let colon = time_str
+        .find(':')
+        .ok_or_else(|| TimeConversionError::parse(time_str, "Time string has no ':'"))?;
+    if time_str.contains(['T', 't', ' ']) || time_str[..colon].contains(['-', '/']) {
+        return Err(TimeConversionError::parse(
+            time_str,
+            "Time string contains a date",
+        ));
+    }
+
+    let date = match tz_massaging {
+        TzMassaging::LocalTz => time_ms_to_utc(date_ms).with_timezone(&Local).date_naive(),
+        TzMassaging::CondAddTzUtc | TzMassaging::HasTz | TzMassaging::RequireTz => {
+            time_ms_to_utc(date_ms).date_naive()
+        }
+    };
+
+    dt_str_to_utc_time_ms(&format!("{date}T{time_str}"), tz_massaging)
+}
+
+/// A compact numeric UTC timestamp, "YYYYMMDDHHMMSS" with an optional
+/// 3 digit milli-seconds, such as SCADA systems emit, converted to time_ms
+///
+/// Exactly 14 or 17 digits are required so an epoch integer, such as a
+/// 13 digit time_ms, isn't mistaken for one.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::compact_numeric_to_time_ms;
+///
+/// assert_eq!(compact_numeric_to_time_ms("19700101000000"), Ok(0));
+/// assert_eq!(compact_numeric_to_time_ms("20220715093000250"), Ok(1_657_877_400_250));
+/// assert!(compact_numeric_to_time_ms("1657877400250").is_err());
+/// ```
+pub fn compact_numeric_to_time_ms(s: &str) -> Result<i64, TimeConversionError> {
+    let s = s.trim();
+    if !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(TimeConversionError::parse(
+            s,
+            "Not compact numeric, expected only digits",
+        ));
+    }
+    let fmt = match s.len() {
+        14 => "%Y%m%d%H%M%S",
+        17 => "%Y%m%d%H%M%S%3f",
+        n => {
+            return Err(TimeConversionError::parse(
+                s,
+                format!("Not compact numeric, expected 14 or 17 digits, found {n}"),
+            ))
+        }
+    };
+    let ndt =
+        NaiveDateTime::parse_from_str(s, fmt).map_err(|e| TimeConversionError::parse(s, e))?;
+
+    Ok(utc_to_time_ms(&ndt.and_utc()))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::SecondsFormat;
+
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_utc_now_to_time_ms() {
+        let start = Instant::now();
+
+        // Because we use integer arithmetic we must
+        // see 2 milli-second time ticks to see a minimum
+        // duration of > 1ms.
+        let tms1 = utc_now_to_time_ms();
+        let mut tms2 = tms1;
+        while tms2 < (tms1 + 2) {
+            tms2 = utc_now_to_time_ms();
+        }
+        let done = Instant::now();
+        let duration = done.duration_since(start);
+
+        println!(
+            "tms1: {} tms2: {} done: {:?} - start {:?} = {}ns or {}ms",
+            tms1,
+            tms2,
+            done,
+            start,
+            duration.as_nanos(),
+            duration.as_millis()
+        );
+
+        assert!(tms2 >= (tms1 + 2));
+        assert!(duration.as_millis() >= 1);
+
+        // The duration.as_millis should be < 2ms. But with Tarpaulin
+        // I've seen durations over 4ms so we skip this test.
+        // assert!(duration.as_millis() < 2);
+    }
+
+    #[test]
+    fn test_dt_str_with_tee_to_utc_time_ms() {
+        let str_time_no_ms = "1970-01-01T00:00:00";
+        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_with_ms = "1970-01-01T00:00:00.123";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+
+        let str_time_with_ms = "1970-01-01T00:00:00.123Z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds with Z");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+
+        let str_time_with_ms = "1970-01-01T00:00:00.123z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds with z");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+    }
+
+    #[test]
+    fn test_dt_str_with_space_to_utc_time_ms() {
+        let str_time_no_ms = "1970-01-01 00:00:00";
+        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_with_ms = "1970-01-01 00:00:00z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with seconds with z");
+        dbg!(tms);
+        assert_eq!(tms, 0);
+
+        let str_time_with_ms = "1970-01-01 00:00:00.123Z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+
+        let str_time_with_ms = "1970-01-01T00:00:00.123z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds and z");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+    }
+
+    #[test]
+    fn test_dt_str_with_leading_trailing_spaces_to_utc_time_ms() {
+        let str_time_no_ms = " 1970-01-01 00:00:00";
+        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_with_ms = "1970-01-01 00:00:00.123 ";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+        let str_time_no_ms = " 1970-01-01T00:00:00z  ";
+        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_with_ms = "  1970-01-01T00:00:00.123Z  ";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format with milliseconds");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+    }
+
+    #[test]
+    fn test_dt_str_addtzutc_with_utc() {
+        let str_time_tz = "1970-01-01 00:00:00+00";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+        let str_time_tz = "1970-01-01T00:00:00.1+00";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 100);
+
+        let str_time_tz = "1970-01-01T00:00:00.123+0000";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 123);
+
+        let str_time_tz = "1970-01-01 00:00:00+00:00";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_tz = "1970-01-01 00:00:00.456+00:00";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 456);
+    }
+
+    #[test]
+    fn test_dt_str_with_tz_to_utc_time_ms() {
+        let str_time_no_ms = "1970-01-01T00:00:00+0000";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::HasTz).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_no_ms = "1970-01-01T00:00:00Z";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::HasTz).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_with_ms = "1970-01-01T00:00:00.123z";
+        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::HasTz)
+            .expect("Bad time format with milliseconds");
+        dbg!(tms);
+        assert_eq!(tms, 123);
+    }
+
+    #[test]
+    fn test_dt_str_both_hastz() {
+        let str_time_tz = "1970-01-01T00:00:00+0000";
+        let ts = dt_str_to_utc_time_ms(str_time_tz, TzMassaging::HasTz).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_pst = "1969-12-31T16:00:00-0800";
+        let ts_pst = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::HasTz)
+            .expect("Bad time format with milliseconds");
+        dbg!(ts_pst);
+        assert_eq!(ts, ts_pst);
+    }
+
+    #[test]
+    fn test_dt_str_addtzutc_hastz() {
+        let str_time_tz = "1970-01-01T00:00:00";
+        let ts =
+            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
+        dbg!(ts);
+        assert_eq!(ts, 0);
+
+        let str_time_pst = "1969-12-31T16:00:00-0800";
+        let ts_pst = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::HasTz)
+            .expect("Bad time format with milliseconds");
+        dbg!(ts_pst);
+        assert_eq!(ts, ts_pst);
+    }
+
+    #[test]
+    fn test_dt_str_to_utc_time_ms_using_localtz() {
+        // UTC time_ms 0 as a Date Time string without time zone
+        let utc_time_ms_0_str = "1970-01-01T00:00:00";
+
+        // Get a NaiveDateTime
+        let ndt: NaiveDateTime = utc_time_ms_0_str.parse().unwrap();
+        dbg!(ndt);
+
+        // Convert to local time so at PST this is 1970-10-01T00:00:00-08:00
+        let ldt = Local.from_local_datetime(&ndt).unwrap();
+        dbg!(ldt);
+
+        // Get the offset from local time to utc, a negative value for west of UTC
+        // This will be -08:00 for PST
+        let ldt_offset = ldt.offset();
+        dbg!(ldt_offset);
+
+        // Get the value to add to UTC to convert to local time
+        // This will be -28800000 = (-8 * 60 * 60 * 1000) for PST
+        let milli_seconds_to_add_to_convert_to_utc = ldt_offset.local_minus_utc() as i64 * 1000;
+        dbg!(milli_seconds_to_add_to_convert_to_utc);
+
+        // Adding milli_seconds_to_add_to_convert_to_utc to tms will equal 0 for all timezones
+        // For PST tms is 28800000 and adding -28800000 is 0
+        let tms = dt_str_to_utc_time_ms(utc_time_ms_0_str, TzMassaging::LocalTz)
+            .expect("Bad time format");
+        dbg!(tms);
+        assert_eq!(tms + milli_seconds_to_add_to_convert_to_utc, 0);
+    }
+
+    #[test]
+    fn test_dt_str_date_only_to_utc_time_ms() {
+        let tms = dt_str_to_utc_time_ms("2022-07-15", TzMassaging::CondAddTzUtc)
+            .expect("Bad date format");
+        assert_eq!(tms, 1_657_843_200_000);
+        assert_eq!(time_ms_to_utc_z_string(tms), "2022-07-15T00:00:00.000Z");
+
+        let tms =
+            dt_str_to_utc_time_ms(" 1970-01-01 ", TzMassaging::HasTz).expect("Bad date format");
+        assert_eq!(tms, 0);
+
+        let tms = dt_str_to_utc_time_ms("1969-12-31", TzMassaging::CondAddTzUtc)
+            .expect("Bad date format");
+        assert_eq!(tms, -86_400_000);
+
+        // Single digit month and day are accepted just as they are with a time
+        let tms =
+            dt_str_to_utc_time_ms("2022-7-5", TzMassaging::CondAddTzUtc).expect("Bad date format");
+        assert_eq!(
+            tms,
+            dt_str_to_utc_time_ms("2022-07-05", TzMassaging::CondAddTzUtc).unwrap()
+        );
+        assert_eq!(
+            tms,
+            dt_str_to_utc_time_ms("2022-7-5T00:00:00", TzMassaging::CondAddTzUtc).unwrap()
+        );
+
+        // Not a date
+        assert!(dt_str_to_utc_time_ms("2022-07", TzMassaging::CondAddTzUtc).is_err());
+        assert!(dt_str_to_utc_time_ms("2022-13-01", TzMassaging::CondAddTzUtc).is_err());
+        assert!(dt_str_to_utc_time_ms("20220715", TzMassaging::CondAddTzUtc).is_err());
+        assert!(dt_str_to_utc_time_ms("", TzMassaging::CondAddTzUtc).is_err());
+    }
+
+    #[test]
+    fn test_dt_str_date_only_to_utc_time_ms_using_localtz() {
+        let tms =
+            dt_str_to_utc_time_ms("1970-01-01", TzMassaging::LocalTz).expect("Bad date format");
+        let full = dt_str_to_utc_time_ms("1970-01-01T00:00:00", TzMassaging::LocalTz)
+            .expect("Bad time format");
+        assert_eq!(tms, full);
+    }
+
+    #[test]
+    fn test_dt_str_separator_errors() {
+        // No time is midnight, not a misparse
+        assert_eq!(
+            dt_str_to_utc_time_ms("1970-01-01", TzMassaging::CondAddTzUtc),
+            Ok(0)
+        );
+
+        let reason = |dt_str: &str| match dt_str_to_utc_time_ms(dt_str, TzMassaging::CondAddTzUtc) {
+            Err(TimeMsError::Parse { value, reason }) => {
+                assert_eq!(value, dt_str.trim());
+                dbg!(reason)
+            }
+            r => panic!("{dt_str:?} should be a parse error, got {r:?}"),
+        };
+
+        assert_eq!(
+            reason("1970-01-01T00:00:00T"),
+            "expected one 'T' separator between the date and time, found 2"
+        );
+        assert_eq!(
+            reason("1970-01-01TT00:00:00"),
+            "expected one 'T' separator between the date and time, found 2"
+        );
+        assert_eq!(
+            reason("1970-01-01_00:00:00"),
+            "no 'T' or ' ' separator between the date and time"
+        );
+
+        // The attempted separator is in the reason
+        assert!(reason("1970-01-01T").starts_with("with a 'T' separator, "));
+        assert!(reason("1970-01-01 00:00").starts_with("with a ' ' separator, "));
+    }
+
+    #[test]
+    fn test_dt_str_iso8601_basic_to_utc_time_ms() {
+        let pairs = [
+            ("19700101T000000Z", "1970-01-01T00:00:00Z"),
+            ("19700101T000000.123+0000", "1970-01-01T00:00:00.123+0000"),
+            ("19700101T000000.123", "1970-01-01T00:00:00.123"),
+            ("19691231T160000-0800", "1969-12-31T16:00:00-08:00"),
+            ("20220715T093000.250z", "2022-07-15T09:30:00.250z"),
+        ];
+        for (basic, extended) in pairs {
+            let basic_tms = dt_str_to_utc_time_ms(basic, TzMassaging::CondAddTzUtc)
+                .expect("Bad basic time format");
+            let extended_tms = dt_str_to_utc_time_ms(extended, TzMassaging::CondAddTzUtc)
+                .expect("Bad extended time format");
+            dbg!(basic, basic_tms);
+            assert_eq!(basic_tms, extended_tms);
+        }
+
+        let tms = dt_str_to_utc_time_ms("19700101T000000.123Z", TzMassaging::HasTz)
+            .expect("Bad basic time format");
+        assert_eq!(tms, 123);
+
+        // The extended format error is reported when the date has separators
+        assert!(dt_str_to_utc_time_ms("1970-01-01T000000", TzMassaging::CondAddTzUtc).is_err());
+        assert!(dt_str_to_utc_time_ms("19701301T000000", TzMassaging::CondAddTzUtc).is_err());
+    }
+
+    #[test]
+    fn test_dt_strs_to_utc_time_ms() {
+        let dt_strs = [
+            "1970-01-01T00:00:00.001",
+            "1970-01-01 00:00:00.002Z",
+            "1969-12-31T16:00:00.003-0800",
+            "19700101T000000.004",
+            "1970-01-02",
+            "1970-13-01T00:00:00",
+            "",
+        ];
+        let results = dt_strs_to_utc_time_ms(&dt_strs, TzMassaging::CondAddTzUtc);
+        dbg!(&results);
+        assert_eq!(results.len(), dt_strs.len());
+        for (dt_str, result) in dt_strs.iter().zip(&results) {
+            assert_eq!(
+                *result,
+                dt_str_to_utc_time_ms(dt_str, TzMassaging::CondAddTzUtc)
+            );
+        }
+        assert_eq!(results[..5], [Ok(1), Ok(2), Ok(3), Ok(4), Ok(86_400_000)]);
+        assert!(matches!(results[5], Err(TimeMsError::Parse { .. })));
+        assert!(results[6].is_err());
+    }
+
+    #[test]
+    fn test_parse_time_ms_list() {
+        let list = "1970-01-01T00:00:01.000Z,1970-01-01 00:00:02, 1969-12-31T23:59:59.999 ";
+        assert_eq!(
+            parse_time_ms_list(list, ',', TzMassaging::CondAddTzUtc),
+            Ok(vec![1000, 2000, -1])
+        );
+        assert_eq!(
+            parse_time_ms_list("1970-01-01T00:00:01Z", ',', TzMassaging::HasTz),
+            Ok(vec![1000])
+        );
+        assert_eq!(
+            parse_time_ms_list("  ", ',', TzMassaging::CondAddTzUtc),
+            Ok(vec![])
+        );
+
+        // A space separated date and time can be delimited by a '|'
+        assert_eq!(
+            parse_time_ms_list(
+                "1970-01-01 00:00:01|1970-01-02",
+                '|',
+                TzMassaging::CondAddTzUtc
+            ),
+            Ok(vec![1000, 86_400_000])
+        );
+    }
+
+    #[test]
+    fn test_parse_time_ms_list_errors() {
+        let list = "1970-01-01T00:00:01,1970-13-01T00:00:02,1970-01-01T00:00:03";
+        let (idx, e) = parse_time_ms_list(list, ',', TzMassaging::CondAddTzUtc).unwrap_err();
+        dbg!(&e);
+        assert_eq!(idx, 1);
+        assert!(e.to_string().contains("1970-13-01T00:00:02"));
+
+        // An empty element
+        let (idx, _e) =
+            parse_time_ms_list("1970-01-01,,1970-01-02", ',', TzMassaging::CondAddTzUtc)
+                .unwrap_err();
+        assert_eq!(idx, 1);
+
+        // The first failure is reported
+        let (idx, _e) =
+            parse_time_ms_list("1970-01-01,x,y", ',', TzMassaging::CondAddTzUtc).unwrap_err();
+        assert_eq!(idx, 1);
+    }
+
+    #[test]
+    fn test_dt_strs_with_separator_to_utc_time_ms() {
+        let t = ["1970-01-01T00:00:00.001", "19700101T000000.002Z"];
+        assert_eq!(
+            dt_strs_with_separator_to_utc_time_ms(&t, DtSeparator::T, TzMassaging::CondAddTzUtc),
+            vec![Ok(1), Ok(2)]
+        );
+
+        let space = [
+            "1970-01-01 00:00:00.001+0000",
+            "1970-01-01T00:00:00.002+0000",
+        ];
+        let results =
+            dt_strs_with_separator_to_utc_time_ms(&space, DtSeparator::Space, TzMassaging::HasTz);
+        assert_eq!(results[0], Ok(1));
+        assert!(results[1].is_err());
+
+        // No date only strings
+        let results = dt_strs_with_separator_to_utc_time_ms(
+            &["1970-01-01"],
+            DtSeparator::T,
+            TzMassaging::CondAddTzUtc,
+        );
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn test_dt_strs_with_separator_to_utc_time_ms_decimal_comma() {
+        // The batch and single string conversions agree
+        let cases = [
+            (
+                "2022-07-15T12:00:00,123Z",
+                DtSeparator::T,
+                TzMassaging::HasTz,
+            ),
+            (
+                "2022-07-15T12:00:00,5",
+                DtSeparator::T,
+                TzMassaging::CondAddTzUtc,
+            ),
+            (
+                " 2022-07-15 12:00:00,123-07:00 ",
+                DtSeparator::Space,
+                TzMassaging::HasTz,
+            ),
+            (
+                "19700101T000000,250Z",
+                DtSeparator::T,
+                TzMassaging::CondAddTzUtc,
+            ),
+        ];
+        for (s, separator, tz_massaging) in cases {
+            let single = dt_str_to_utc_time_ms(s, tz_massaging);
+            assert!(single.is_ok(), "{s}");
+            assert_eq!(
+                dt_strs_with_separator_to_utc_time_ms(&[s], separator, tz_massaging),
+                vec![single],
+                "{s}"
+            );
+        }
+
+        // Errors name the separator just as the single string conversion does
+        let s = "2022-07-15T12:00:00,12,3Z";
+        let batch = dt_strs_with_separator_to_utc_time_ms(&[s], DtSeparator::T, TzMassaging::HasTz);
+        assert_eq!(batch, vec![dt_str_to_utc_time_ms(s, TzMassaging::HasTz)]);
+        assert!(batch[0]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("with a 'T' separator"));
+    }
+
+    #[test]
+    fn test_rfc2822_str_to_utc_time_ms() {
+        // 2003-07-01T08:52:37Z
+        let expected = 1_057_049_557_000;
+        for s in [
+            "Tue, 1 Jul 2003 10:52:37 +0200",
+            "1 Jul 2003 10:52:37 +0200",
+            "Tue, 01 Jul 2003 08:52:37 GMT",
+            "Tue, 1 Jul 2003 08:52:37 UT",
+            "Tue, 1 Jul 2003 08:52:37 Z",
+            "Tue, 1 Jul 2003 04:52:37 EDT",
+            "Tue, 1 Jul 03 08:52:37 GMT",
+            " Tue, 1 Jul 2003 10:52:37 +0200 ",
+        ] {
+            let tms = rfc2822_str_to_utc_time_ms(s).expect("Bad RFC 2822 format");
+            dbg!(s, tms);
+            assert_eq!(tms, expected);
+        }
+
+        // Two digit years 50-99 are 19xx
+        let tms = rfc2822_str_to_utc_time_ms("Wed, 31 Dec 69 23:59:59 GMT").unwrap();
+        assert_eq!(tms, -1000);
+    }
+
+    #[test]
+    fn test_rfc2822_str_to_utc_time_ms_errors() {
+        for s in [
+            "",
+            "2003-07-01T08:52:37Z",
+            "Wed, 1 Jul 2003 10:52:37 +0200", // Wrong day of week
+            "Tue, 32 Jul 2003 10:52:37 +0200",
+        ] {
+            dbg!(s);
+            assert!(matches!(
+                rfc2822_str_to_utc_time_ms(s),
+                Err(TimeMsError::Parse { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rfc3339_require_utc_to_time_ms() {
+        for s in [
+            "2022-07-15T12:00:00.123Z",
+            "2022-07-15T12:00:00.123z",
+            "2022-07-15T12:00:00.123+00:00",
+            "2022-07-15T12:00:00.123-00:00",
+            " 2022-07-15t12:00:00.123Z ",
+        ] {
+            assert_eq!(
+                rfc3339_require_utc_to_time_ms(s),
+                Ok(1_657_886_400_123),
+                "{s}"
+            );
+        }
+        assert_eq!(
+            rfc3339_require_utc_to_time_ms("1969-12-31T23:59:59.999Z"),
+            Ok(-1)
+        );
+    }
+
+    #[test]
+    fn test_rfc3339_require_utc_to_time_ms_errors() {
+        let e = rfc3339_require_utc_to_time_ms("2022-07-15T04:00:00-08:00").unwrap_err();
+        dbg!(&e);
+        assert_eq!(
+            e,
+            TimeConversionError::NonUtcOffset {
+                value: "2022-07-15T04:00:00-08:00".to_string(),
+                offset_seconds: -8 * 60 * 60,
+            }
+        );
+        assert_eq!(
+            e.to_string(),
+            "non-UTC offset -08:00 in \"2022-07-15T04:00:00-08:00\""
+        );
+
+        let e = rfc3339_require_utc_to_time_ms("2022-07-15T17:30:00+05:30").unwrap_err();
+        assert!(e.to_string().starts_with("non-UTC offset +05:30"), "{e}");
+
+        // Not RFC 3339, no offset or not a date time
+        for s in ["2022-07-15T12:00:00", "2022-07-15 12:00:00+0000", "bad"] {
+            let e = rfc3339_require_utc_to_time_ms(s).unwrap_err();
+            assert!(matches!(e, TimeConversionError::Parse { .. }), "{s}: {e:?}");
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_rfc2822_string_round_trip() {
+        for tms in [0, -1000, 1_057_049_557_000, 253_402_300_799_000] {
+            let s = time_ms_to_rfc2822_string(tms);
+            dbg!(&s);
+            assert_eq!(rfc2822_str_to_utc_time_ms(&s), Ok(tms));
+        }
+        assert_eq!(
+            time_ms_to_rfc2822_string(0),
+            "Thu, 1 Jan 1970 00:00:00 +0000"
+        );
+
+        // Milli-seconds are dropped
+        assert_eq!(
+            rfc2822_str_to_utc_time_ms(&time_ms_to_rfc2822_string(1999)),
+            Ok(1000)
+        );
+        assert_eq!(
+            rfc2822_str_to_utc_time_ms(&time_ms_to_rfc2822_string(-1)),
+            Ok(-1000)
+        );
+    }
+
+    #[test]
+    fn test_time_str_to_utc_time_ms() {
+        let date_ms = dt_str_to_utc_time_ms("2022-07-15T17:45:00", TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+
+        for time_str in [
+            "09:30:00",
+            "09:30:00.250",
+            "00:00:00",
+            "23:59:59.999",
+            "9:30:00",
+        ] {
+            let tms = time_str_to_utc_time_ms(time_str, date_ms, TzMassaging::CondAddTzUtc)
+                .expect("Bad time format");
+            let full =
+                dt_str_to_utc_time_ms(&format!("2022-07-15T{time_str}"), TzMassaging::CondAddTzUtc)
+                    .expect("Bad time format");
+            dbg!(time_str, tms);
+            assert_eq!(tms, full);
+        }
+
+        // date_ms + parsed offset
+        let midnight = dt_str_to_utc_time_ms("2022-07-15", TzMassaging::CondAddTzUtc).unwrap();
+        let tms = time_str_to_utc_time_ms("09:30:00.250", date_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        assert_eq!(tms, midnight + 34_200_250);
+    }
+
+    #[test]
+    fn test_time_str_to_utc_time_ms_with_tz() {
+        let date_ms = dt_str_to_utc_time_ms("1970-01-01", TzMassaging::CondAddTzUtc).unwrap();
+
+        let tms = time_str_to_utc_time_ms("16:00:00-0800", date_ms, TzMassaging::HasTz)
+            .expect("Bad time format");
+        assert_eq!(tms, 24 * 60 * 60 * 1000);
+
+        let tms = time_str_to_utc_time_ms("00:00:00.123Z", date_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        assert_eq!(tms, 123);
+
+        let tms = time_str_to_utc_time_ms("01:00:00+01:00", date_ms, TzMassaging::CondAddTzUtc)
+            .expect("Bad time format");
+        assert_eq!(tms, 0);
+    }
+
+    #[test]
+    fn test_time_str_to_utc_time_ms_using_localtz() {
+        let date_ms = dt_str_to_utc_time_ms("1970-01-02", TzMassaging::LocalTz).unwrap();
+        let tms = time_str_to_utc_time_ms("09:30:00", date_ms, TzMassaging::LocalTz)
+            .expect("Bad time format");
+        let full = dt_str_to_utc_time_ms("1970-01-02T09:30:00", TzMassaging::LocalTz)
+            .expect("Bad time format");
+        assert_eq!(tms, full);
+    }
+
+    #[test]
+    fn test_time_str_to_utc_time_ms_rejects_dates() {
+        for s in [
+            "2022-07-15T09:30:00",
+            "2022-07-15 09:30:00",
+            "2022-07-15",
+            "07/15/2022 09:30",
+            "",
+            "09",
+        ] {
+            dbg!(s);
+            assert!(time_str_to_utc_time_ms(s, 0, TzMassaging::CondAddTzUtc).is_err());
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_utc() {
+        let dt = time_ms_to_utc(0i64);
+        assert_eq!(
+            dt.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "1970-01-01T00:00:00.000Z"
+        );
+        assert_eq!(
+            dt.to_rfc3339_opts(SecondsFormat::Millis, false),
+            "1970-01-01T00:00:00.000+00:00"
+        );
+    }
+
+    #[test]
+    fn test_utc_to_time_ms_negative() {
+        for tms in [-86_400_000, -1001, -1000, -999, -1] {
+            assert_eq!(utc_to_time_ms(&time_ms_to_utc(tms)), tms);
+        }
+    }
+
+    #[test]
+    fn test_utc_to_time_ms_rounded() {
+        use RoundingMode::*;
+
+        let pos_1_5 = DateTime::from_timestamp(0, 1_500_000).unwrap();
+        let neg_1_5 = DateTime::from_timestamp(-1, 998_500_000).unwrap();
+        let expected = [
+            (HalfUp, 2, -1),
+            (Truncate, 1, -1),
+            (HalfEven, 2, -2),
+            (Ceil, 2, -1),
+            (Floor, 1, -2),
+        ];
+        for (mode, pos, neg) in expected {
+            dbg!(mode);
+            assert_eq!(utc_to_time_ms_rounded(&pos_1_5, mode), pos);
+            assert_eq!(utc_to_time_ms_rounded(&neg_1_5, mode), neg);
+        }
+
+        // HalfEven rounds 2.5 down and -2.5 up
+        let pos_2_5 = DateTime::from_timestamp(0, 2_500_000).unwrap();
+        let neg_2_5 = DateTime::from_timestamp(-1, 997_500_000).unwrap();
+        assert_eq!(utc_to_time_ms_rounded(&pos_2_5, HalfEven), 2);
+        assert_eq!(utc_to_time_ms_rounded(&neg_2_5, HalfEven), -2);
+
+        // Not a half so all nearest modes agree
+        let neg_1_6 = DateTime::from_timestamp(-1, 998_400_000).unwrap();
+        assert_eq!(utc_to_time_ms_rounded(&neg_1_6, HalfUp), -2);
+        assert_eq!(utc_to_time_ms_rounded(&neg_1_6, HalfEven), -2);
+        assert_eq!(utc_to_time_ms_rounded(&neg_1_6, Truncate), -1);
+
+        // Exact values are unchanged by every mode
+        let exact = time_ms_to_utc(-1001);
+        for (mode, _, _) in expected {
+            assert_eq!(utc_to_time_ms_rounded(&exact, mode), -1001);
+        }
+
+        // utc_to_time_ms is HalfUp
+        assert_eq!(utc_to_time_ms(&pos_1_5), 2);
+        assert_eq!(utc_to_time_ms(&neg_1_5), -1);
+    }
+
+    #[test]
+    fn test_time_ms_to_utc_string() {
+        let dt = time_ms_to_utc_string(0i64);
+        assert_eq!(dt, "1970-01-01T00:00:00.000+00:00");
+    }
+
+    #[test]
+    fn test_time_ms_from_utc_parts() {
+        assert_eq!(time_ms_from_utc_parts(1970, 1, 1, 0, 0, 0, 0), Some(0));
+        assert_eq!(
+            time_ms_from_utc_parts(2022, 7, 15, 12, 0, 0, 123),
+            Some(1_657_886_400_123)
+        );
+        assert_eq!(
+            time_ms_from_utc_parts(2020, 2, 29, 0, 0, 0, 0),
+            Some(1_582_934_400_000)
+        );
+
+        for (year, month, day, hour, minute, second, millis) in [
+            (2022, 13, 1, 0, 0, 0, 0),
+            (2022, 4, 31, 0, 0, 0, 0),
+            (2022, 1, 1, 24, 0, 0, 0),
+            (2022, 1, 1, 0, 60, 0, 0),
+            (2022, 1, 1, 0, 0, 60, 0),
+            (2022, 1, 1, 0, 0, 59, 1000),
+        ] {
+            assert_eq!(
+                time_ms_from_utc_parts(year, month, day, hour, minute, second, millis),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_local_string() {
+        for tms in [0, -1, 1_657_886_400_123] {
+            let s = time_ms_to_local_string(tms);
+            dbg!(&s);
+            assert_eq!(dt_str_to_utc_time_ms(&s, TzMassaging::HasTz).unwrap(), tms);
+
+            // Milli-seconds and a numeric offset, never 'Z'
+            let offset = &s[s.len() - 6..];
+            assert!(
+                offset.starts_with(['+', '-']) && offset.as_bytes()[3] == b':',
+                "{s}"
+            );
+            assert_eq!(s.as_bytes()[s.len() - 10], b'.', "{s}");
+        }
+    }
+
+    #[test]
+    fn test_local_formatting_is_ambiguous() {
+        const HOUR: i64 = 60 * 60 * 1000;
+
+        // To see an ambiguous case run the tests with a local time zone
+        // that has daylight saving time, TZ=America/Los_Angeles, where
+        // 2022-11-06T08:30:00Z, 01:30 PDT, and 09:30:00Z, 01:30 PST,
+        // are the same local time. With a 1 hour shift a time is ambiguous
+        // if the local time an hour before or after is the same.
+        for s in [
+            "2022-11-06T08:30:00Z",
+            "2022-11-06T09:30:00Z",
+            "2022-11-06T10:30:00Z",
+            "2022-10-30T00:30:00Z",
+            "2022-10-30T01:30:00Z",
+            "2022-03-13T10:30:00Z",
+        ] {
+            let tms = dt_str_to_utc_time_ms(s, TzMassaging::HasTz).unwrap();
+            let naive = |tms| time_ms_to_local(tms).naive_local();
+            let repeated = [tms - HOUR, tms + HOUR]
+                .iter()
+                .any(|&other| naive(other) == naive(tms));
+            dbg!(s, naive(tms), repeated);
+            assert_eq!(local_formatting_is_ambiguous(tms), repeated, "{s}");
+        }
+
+        if std::env::var("TZ").as_deref() == Ok("America/Los_Angeles") {
+            let tms = dt_str_to_utc_time_ms("2022-11-06T08:30:00Z", TzMassaging::HasTz).unwrap();
+            assert!(local_formatting_is_ambiguous(tms));
+            assert!(local_formatting_is_ambiguous(tms + HOUR));
+            assert!(!local_formatting_is_ambiguous(tms + 2 * HOUR));
+        }
+
+        // A normal time isn't ambiguous in any time zone
+        assert!(!local_formatting_is_ambiguous(1_657_886_400_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "time_ms out of range")]
+    fn test_time_ms_to_local_string_out_of_range() {
+        time_ms_to_local_string(i64::MAX);
+    }
+
+    #[test]
+    fn test_time_ms_to_local_string_across_dst() {
+        // The milli-second before and at the 2022 US and EU spring forward
+        // and fall back transitions. The local offset depends on the time
+        // zone of the machine but must be the offset at each instant.
+        for transition in [
+            "2022-03-13T10:00:00Z",
+            "2022-11-06T09:00:00Z",
+            "2022-03-27T01:00:00Z",
+            "2022-10-30T01:00:00Z",
+        ] {
+            let at = dt_str_to_utc_time_ms(transition, TzMassaging::HasTz).unwrap();
+            for tms in [at - 1, at] {
+                let s = time_ms_to_local_string(tms);
+                let offset = Local.offset_from_utc_datetime(&time_ms_to_utc(tms).naive_utc());
+                dbg!(tms, &s, offset);
+                assert!(s.ends_with(&offset.to_string()));
+                assert_eq!(dt_str_to_utc_time_ms(&s, TzMassaging::HasTz).unwrap(), tms);
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_fixed_offset_string() {
+        let hours = |h: i32| FixedOffset::east_opt(h * 60 * 60).unwrap();
+        let tms = 1_657_886_400_123;
+        let cases = [
+            (hours(9), "2022-07-15T21:00:00.123+09:00"),
+            (hours(-8), "2022-07-15T04:00:00.123-08:00"),
+            (
+                FixedOffset::east_opt(5 * 60 * 60 + 30 * 60).unwrap(),
+                "2022-07-15T17:30:00.123+05:30",
+            ),
+            (
+                FixedOffset::west_opt(3 * 60 * 60 + 30 * 60).unwrap(),
+                "2022-07-15T08:30:00.123-03:30",
+            ),
+            (hours(0), "2022-07-15T12:00:00.123+00:00"),
+        ];
+        for (offset, expected) in cases {
+            let s = time_ms_to_fixed_offset_string(tms, offset);
+            assert_eq!(s, expected);
+            assert_eq!(dt_str_to_utc_time_ms(&s, TzMassaging::HasTz), Ok(tms));
+
+            let dt = time_ms_to_fixed_offset(tms, offset);
+            assert_eq!(*dt.offset(), offset);
+            assert_eq!(fixed_offset_to_time_ms(&dt), tms);
+        }
+
+        // The date changes across midnight
+        assert_eq!(
+            time_ms_to_fixed_offset_string(-1, hours(-1)),
+            "1969-12-31T22:59:59.999-01:00"
+        );
+        assert_eq!(
+            time_ms_to_fixed_offset_string(-1, hours(1)),
+            "1970-01-01T00:59:59.999+01:00"
+        );
+    }
+
+    #[test]
+    fn test_dt_str_to_utc_time_ms_require_tz() {
+        let cases = [
+            ("1970-01-01T00:00:00Z", 0),
+            ("1970-01-01T00:00:00z", 0),
+            ("1970-01-01 00:00:00.123+00:00", 123),
+            ("1969-12-31T16:00:00-0800", 0),
+            ("19700101T000000.500Z", 500),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(
+                dt_str_to_utc_time_ms(s, TzMassaging::RequireTz),
+                Ok(expected),
+                "{s}"
+            );
+        }
+
+        for s in [
+            "1970-01-01T00:00:00",
+            " 1970-01-01 00:00:00.123 ",
+            "1970-01-01",
+            "19700101T000000",
+        ] {
+            assert_eq!(
+                dt_str_to_utc_time_ms(s, TzMassaging::RequireTz),
+                Err(TimeConversionError::MissingTimezone(s.trim().to_string())),
+                "{s}"
+            );
+
+            // CondAddTzUtc assumes UTC for the same strings
+            assert!(dt_str_to_utc_time_ms(s, TzMassaging::CondAddTzUtc).is_ok());
+        }
+
+        let err = dt_str_to_utc_time_ms("1970-01-01T00:00:00", TzMassaging::RequireTz).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing time zone in \"1970-01-01T00:00:00\""
+        );
+
+        // A time zone that doesn't parse is still a parse error
+        assert!(matches!(
+            dt_str_to_utc_time_ms("1970-01-01T00:00:00+99", TzMassaging::RequireTz),
+            Err(TimeConversionError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dt_str_to_utc_time_ms_leap_second() {
+        // The leap second at the end of 2016
+        let next_day = time_ms_from_utc_parts(2017, 1, 1, 0, 0, 0, 0).unwrap();
+        assert_eq!(next_day, 1_483_228_800_000);
+
+        let cases = [
+            ("2016-12-31T23:59:59.999Z", next_day - 1),
+            ("2016-12-31T23:59:60Z", next_day),
+            ("2016-12-31T23:59:60.500Z", next_day + 500),
+            ("2016-12-31T23:59:60.999Z", next_day + 999),
+            ("2016-12-31T23:59:60.9996Z", next_day + 1000),
+            ("2016-12-31 23:59:60.123", next_day + 123),
+            ("2017-01-01T08:59:60.250+09:00", next_day + 250),
+            ("2017-01-01T00:00:00.500Z", next_day + 500),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(
+                dt_str_to_utc_time_ms(s, TzMassaging::CondAddTzUtc),
+                Ok(expected),
+                "{s}"
+            );
+        }
+        assert_eq!(
+            rfc3339_require_utc_to_time_ms("2016-12-31T23:59:60.500Z"),
+            Ok(next_day + 500)
+        );
+
+        // Formatting the folded time_ms gives the second after the leap second
+        assert_eq!(
+            time_ms_to_utc_z_string(next_day + 500),
+            "2017-01-01T00:00:00.500Z"
+        );
+    }
+
+    #[test]
+    fn test_fixed_offset_to_time_ms() {
+        let pst = DateTime::parse_from_rfc3339("2022-07-15T04:00:00.123-08:00").unwrap();
+        let utc = DateTime::parse_from_rfc3339("2022-07-15T12:00:00.123Z").unwrap();
+        dbg!(pst, utc);
+        assert_eq!(fixed_offset_to_time_ms(&pst), 1_657_886_400_123);
+        assert_eq!(fixed_offset_to_time_ms(&pst), fixed_offset_to_time_ms(&utc));
+
+        // Round trips, keeping the offset
+        let tms = fixed_offset_to_time_ms(&pst);
+        assert_eq!(time_ms_to_fixed_offset(tms, *pst.offset()), pst);
+        assert_eq!(
+            fixed_offset_to_time_ms(&time_ms_to_fixed_offset(-1, *pst.offset())),
+            -1
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_string_with_fmt() {
+        let tms = 1_657_886_400_123;
+        let cases = [
+            ("%Y%m%d_%H%M%S", "20220715_120000"),
+            ("%d %b %Y %H:%M", "15 Jul 2022 12:00"),
+            ("%Y-%m-%dT%H:%M:%S%.3fZ", "2022-07-15T12:00:00.123Z"),
+            ("%s", "1657886400"),
+            ("%A %j %%", "Friday 196 %"),
+            ("", ""),
+        ];
+        for (fmt, expected) in cases {
+            assert_eq!(
+                time_ms_to_string_with_fmt(tms, fmt).as_deref(),
+                Ok(expected)
+            );
+        }
+        assert_eq!(
+            time_ms_to_string_with_fmt(-1, "%Y-%m-%d %H:%M:%S%.3f %z").as_deref(),
+            Ok("1969-12-31 23:59:59.999 +0000")
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_utc_custom_fmt() {
+        let cases = [
+            (1_609_459_200_000, "%Y/%m/%d %H:%M", "2021/01/01 00:00"),
+            (-1, "%Y-%m-%d %H:%M:%S%.3f", "1969-12-31 23:59:59.999"),
+            (
+                1_657_886_400_123,
+                "%a %d %b %Y %T",
+                "Fri 15 Jul 2022 12:00:00",
+            ),
+            (0, "", ""),
+        ];
+        for (tms, fmt, expected) in cases {
+            assert_eq!(time_ms_to_utc_custom_fmt(tms, fmt), expected);
+
+            // Agrees with the non-panicking version
+            assert_eq!(time_ms_to_string_with_fmt(tms, fmt).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_time_ms_to_utc_custom_fmt_invalid_fmt() {
+        time_ms_to_utc_custom_fmt(0, "%Q");
+    }
+
+    #[test]
+    fn test_time_ms_to_string_with_fmt_and_offset() {
+        let tms = 1_657_886_400_123;
+        let ist = FixedOffset::east_opt(5 * 60 * 60 + 30 * 60).unwrap();
+        let pdt = FixedOffset::west_opt(7 * 60 * 60).unwrap();
+        assert_eq!(
+            time_ms_to_string_with_fmt_and_offset(tms, "%Y%m%d_%H%M%S%z", ist).as_deref(),
+            Ok("20220715_173000+0530")
+        );
+        assert_eq!(
+            time_ms_to_string_with_fmt_and_offset(tms, "%d %b %Y %H:%M %:z", pdt).as_deref(),
+            Ok("15 Jul 2022 05:00 -07:00")
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_string_with_fmt_errors() {
+        for fmt in ["%Q", "%Y-%m-%d %", "%Y %E"] {
+            let e = time_ms_to_string_with_fmt(0, fmt).unwrap_err();
+            dbg!(&e);
+            assert_eq!(
+                e,
+                TimeMsError::parse(fmt, "invalid strftime format specifier")
+            );
+        }
+
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert!(time_ms_to_string_with_fmt_and_offset(0, "%Q", utc).is_err());
+        assert_eq!(
+            time_ms_to_string_with_fmt(i64::MAX, "%Y"),
+            Err(TimeMsError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_utc_z_string() {
+        let dt = time_ms_to_utc_z_string(0i64);
+        assert_eq!(dt, "1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_time_ms_to_utc_string_opts() {
+        let cases = [
+            (
+                SecondsFormat::Secs,
+                "1970-01-01T00:00:00",
+                "1969-12-31T23:59:58",
+            ),
+            (
+                SecondsFormat::Millis,
+                "1970-01-01T00:00:00.001",
+                "1969-12-31T23:59:58.766",
+            ),
+            (
+                SecondsFormat::Micros,
+                "1970-01-01T00:00:00.001000",
+                "1969-12-31T23:59:58.766000",
+            ),
+            (
+                SecondsFormat::Nanos,
+                "1970-01-01T00:00:00.001000000",
+                "1969-12-31T23:59:58.766000000",
+            ),
+            (
+                SecondsFormat::AutoSi,
+                "1970-01-01T00:00:00.001",
+                "1969-12-31T23:59:58.766",
+            ),
+        ];
+        for (seconds_format, one, negative) in cases {
+            assert_eq!(
+                time_ms_to_utc_string_opts(1, seconds_format, true),
+                format!("{one}Z")
+            );
+            assert_eq!(
+                time_ms_to_utc_string_opts(-1234, seconds_format, false),
+                format!("{negative}+00:00")
+            );
+        }
+
+        // AutoSi drops zero milli-seconds
+        assert_eq!(
+            time_ms_to_utc_string_opts(-2000, SecondsFormat::AutoSi, true),
+            "1969-12-31T23:59:58Z"
+        );
+
+        assert_eq!(
+            time_ms_to_utc_string_opts(1, SecondsFormat::Millis, false),
+            time_ms_to_utc_string(1)
+        );
+        assert_eq!(
+            time_ms_to_utc_string_opts(1, SecondsFormat::Millis, true),
+            time_ms_to_utc_z_string(1)
+        );
+    }
+
+    #[test]
+    fn test_date_time_parse_from_rfc3339() {
+        let s = format!("1970-01-01T00:00:00.000{}", "Z");
+        let dt = match DateTime::parse_from_rfc3339(&s) {
+            Ok(v) => v,
+            Err(e) => panic!("shit {e}"),
+        };
+        println!("test_date_time_parse_from_rfc3339: {dt}");
+    }
+
+    #[test]
+    fn test_compact_numeric_to_time_ms() {
+        assert_eq!(compact_numeric_to_time_ms("19700101000000"), Ok(0));
+        assert_eq!(compact_numeric_to_time_ms("19700101000000123"), Ok(123));
+        assert_eq!(compact_numeric_to_time_ms(" 19691231235959999 "), Ok(-1));
+        assert_eq!(
+            compact_numeric_to_time_ms("20220715093000"),
+            dt_str_to_utc_time_ms("2022-07-15T09:30:00Z", TzMassaging::HasTz)
+        );
+    }
+
+    #[test]
+    fn test_compact_numeric_to_time_ms_errors() {
+        // A 13 digit epoch time_ms isn't compact numeric
+        match compact_numeric_to_time_ms("1657877400250") {
+            Err(TimeMsError::Parse { value, reason }) => {
+                assert_eq!(value, "1657877400250");
+                assert!(dbg!(reason).starts_with("Not compact numeric"));
+            }
+            r => panic!("expected a parse error, got {r:?}"),
+        }
+
+        for s in [
+            "",
+            "197001010000001",
+            "1970010100000012",
+            "197001010000001234",
+            "1970-01-01000000",
+            "+9700101000000",
+            "19701301000000",
+            "19700101240000",
+        ] {
+            assert!(compact_numeric_to_time_ms(s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_dt_str_to_utc_time_ms_with_offset() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let pdt = FixedOffset::west_opt(7 * 60 * 60).unwrap();
+        let ist = FixedOffset::east_opt(5 * 60 * 60 + 30 * 60).unwrap();
+
+        for (dt_str, tz_massaging, expected) in [
+            (
+                "2022-07-15T09:30:00Z",
+                TzMassaging::HasTz,
+                (1_657_877_400_000, utc),
+            ),
+            (
+                "2022-07-15T02:30:00-07:00",
+                TzMassaging::HasTz,
+                (1_657_877_400_000, pdt),
+            ),
+            (
+                "2022-07-15 15:00:00.250+0530",
+                TzMassaging::CondAddTzUtc,
+                (1_657_877_400_250, ist),
+            ),
+            (
+                "2022-07-15T09:30:00",
+                TzMassaging::CondAddTzUtc,
+                (1_657_877_400_000, utc),
+            ),
+            (
+                "2022-07-15T09:30:00z",
+                TzMassaging::CondAddTzUtc,
+                (1_657_877_400_000, utc),
+            ),
+            (
+                "20220715T023000-0700",
+                TzMassaging::CondAddTzUtc,
+                (1_657_877_400_000, pdt),
+            ),
+            ("1970-01-01", TzMassaging::CondAddTzUtc, (0, utc)),
+        ] {
+            let result = dt_str_to_utc_time_ms_with_offset(dt_str, tz_massaging);
+            assert_eq!(result, Ok(expected), "{dt_str}");
+            assert_eq!(
+                result.map(|r| r.0),
+                dt_str_to_utc_time_ms(dt_str, tz_massaging)
+            );
+        }
+
+        assert!(
+            dt_str_to_utc_time_ms_with_offset("2022-07-15T09:30:00", TzMassaging::HasTz).is_err()
+        );
+    }
+
+    #[test]
+    fn test_dt_str_to_utc_time_ms_with_offset_using_localtz() {
+        for dt_str in ["2022-01-15T09:30:00", "2022-07-15 09:30:00", "2022-07-15"] {
+            let (tms, offset) =
+                dt_str_to_utc_time_ms_with_offset(dt_str, TzMassaging::LocalTz).unwrap();
+            assert_eq!(Ok(tms), dt_str_to_utc_time_ms(dt_str, TzMassaging::LocalTz));
+            assert_eq!(offset, *time_ms_to_local(tms).offset());
+        }
+    }
+
+    #[test]
+    fn test_dt_str_decimal_comma_to_utc_time_ms() {
+        for (dt_str, tz_massaging, expected) in [
+            ("1970-01-01T00:00:00,123", TzMassaging::CondAddTzUtc, 123),
+            ("1970-01-01 00:00:00,123Z", TzMassaging::CondAddTzUtc, 123),
+            ("1969-12-31T16:00:00,5-08:00", TzMassaging::HasTz, 500),
+            ("19700101T000000,250", TzMassaging::CondAddTzUtc, 250),
+            (" 1970-01-01T00:00:01,001 ", TzMassaging::CondAddTzUtc, 1001),
+        ] {
+            assert_eq!(
+                dt_str_to_utc_time_ms(dt_str, tz_massaging),
+                Ok(expected),
+                "{dt_str}"
+            );
+        }
+
+        let date_ms = dt_str_to_utc_time_ms("2022-07-15", TzMassaging::CondAddTzUtc).unwrap();
+        assert_eq!(
+            time_str_to_utc_time_ms("09:30:00,250", date_ms, TzMassaging::CondAddTzUtc),
+            time_str_to_utc_time_ms("09:30:00.250", date_ms, TzMassaging::CondAddTzUtc)
+        );
+    }
+
+    #[test]
+    fn test_dt_str_decimal_comma_elsewhere_rejected() {
+        for dt_str in [
+            "1,970-01-01T00:00:00",
+            "1970-01,-01T00:00:00.123",
+            "1970-01-01T00:00:00,123,4",
+            "1970-01-01T00:00:00,",
+            "1970-01-01T00:00,00",
+            "1970-01-01T00:00:00,,123",
+        ] {
+            let result = dt_str_to_utc_time_ms(dt_str, TzMassaging::CondAddTzUtc);
+            assert!(
+                matches!(result, Err(TimeMsError::Parse { .. })),
+                "{dt_str} {result:?}"
+            );
+        }
+    }
+}
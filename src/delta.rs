@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+
+/// Delta-of-delta encode `times` for compact time series storage, as in
+/// Facebook's Gorilla.
+///
+/// The result is the same length as `times`, the first time, then the
+/// first delta, then the successive second order differences. A regularly
+/// spaced series encodes to mostly zeros. The arithmetic wraps so every
+/// series round trips through [`delta_of_delta_decode`], even when the
+/// differences overflow an i64.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::delta_of_delta_encode;
+///
+/// assert_eq!(delta_of_delta_encode(&[1000, 2000, 3000, 4000, 5001]), [1000, 1000, 0, 0, 1]);
+/// ```
+pub fn delta_of_delta_encode(times: &[i64]) -> Vec<i64> {
+    let mut encoded = Vec::with_capacity(times.len());
+    let mut prev_time = 0i64;
+    let mut prev_delta = 0i64;
+    for (i, &time) in times.iter().enumerate() {
+        let delta = time.wrapping_sub(prev_time);
+        encoded.push(if i < 2 {
+            // The first time and first delta are stored as is
+            delta
+        } else {
+            delta.wrapping_sub(prev_delta)
+        });
+        prev_time = time;
+        prev_delta = delta;
+    }
+
+    encoded
+}
+
+/// Decode the output of [`delta_of_delta_encode`] back to the times
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{delta_of_delta_decode, delta_of_delta_encode};
+///
+/// let times = [1000, 2000, 3000, 4000, 5001];
+/// assert_eq!(delta_of_delta_decode(&delta_of_delta_encode(&times)), times);
+/// ```
+pub fn delta_of_delta_decode(encoded: &[i64]) -> Vec<i64> {
+    let mut times = Vec::with_capacity(encoded.len());
+    let mut prev_time = 0i64;
+    let mut prev_delta = 0i64;
+    for (i, &value) in encoded.iter().enumerate() {
+        let delta = if i < 2 {
+            value
+        } else {
+            prev_delta.wrapping_add(value)
+        };
+        prev_time = prev_time.wrapping_add(delta);
+        times.push(prev_time);
+        prev_delta = delta;
+    }
+
+    times
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::split_mix64;
+
+    #[test]
+    fn test_delta_of_delta_short() {
+        assert!(delta_of_delta_encode(&[]).is_empty());
+        assert!(delta_of_delta_decode(&[]).is_empty());
+        assert_eq!(delta_of_delta_encode(&[-5]), [-5]);
+        assert_eq!(delta_of_delta_encode(&[-5, 10]), [-5, 15]);
+        assert_eq!(delta_of_delta_decode(&[-5, 15]), [-5, 10]);
+    }
+
+    #[test]
+    fn test_delta_of_delta_regular() {
+        // Every 10s starting at 2022-07-15T12:00:00Z
+        let times: Vec<i64> = (0..100).map(|i| 1_657_886_400_000 + i * 10_000).collect();
+        let encoded = delta_of_delta_encode(&times);
+        assert_eq!(encoded[0], 1_657_886_400_000);
+        assert_eq!(encoded[1], 10_000);
+        assert!(encoded[2..].iter().all(|&dod| dod == 0));
+        assert_eq!(delta_of_delta_decode(&encoded), times);
+    }
+
+    #[test]
+    fn test_delta_of_delta_irregular() {
+        let times = [1000, 2000, 3005, 3995, 5000, 5000, 4000];
+        let encoded = delta_of_delta_encode(&times);
+        assert_eq!(encoded, [1000, 1000, 5, -15, 15, -1005, -1000]);
+        assert_eq!(delta_of_delta_decode(&encoded), times);
+    }
+
+    #[test]
+    fn test_delta_of_delta_limits() {
+        // The differences overflow an i64 but still round trip
+        let times = [i64::MIN, i64::MAX, i64::MIN, 0, i64::MAX, -1];
+        assert_eq!(delta_of_delta_decode(&delta_of_delta_encode(&times)), times);
+    }
+
+    #[test]
+    fn test_delta_of_delta_round_trip_property() {
+        let mut state = 0x5eed_1234_abcd_0001;
+        for len in 0..50 {
+            let mut time = 1_657_886_400_000i64;
+            let times: Vec<i64> = (0..len)
+                .map(|_| {
+                    // Mostly 1s apart with jitter
+                    time += 1_000 + (split_mix64(&mut state) % 21) as i64 - 10;
+                    time
+                })
+                .collect();
+            assert_eq!(delta_of_delta_decode(&delta_of_delta_encode(&times)), times);
+
+            let random: Vec<i64> = (0..len).map(|_| split_mix64(&mut state) as i64).collect();
+            assert_eq!(
+                delta_of_delta_decode(&delta_of_delta_encode(&random)),
+                random
+            );
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{dt_str_to_utc_time_ms, rfc2822_str_to_utc_time_ms, TimeConversionError, TzMassaging};
+
+/// The format of a time string as determined by [`detect_time_format`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// "2022-07-15T09:30:00Z", "2022-07-15 09:30:00.123" or "2022-07-15"
+    Iso8601,
+
+    /// An integer count of milli-seconds since the epoch, "1657877400000"
+    EpochMs,
+
+    /// "Fri, 15 Jul 2022 09:30:00 +0000"
+    Rfc2822,
+
+    Unknown,
+}
+
+/// Determine the format of a time string from its shape.
+///
+/// - An optional '-' followed only by digits is EpochMs
+/// - Four digits followed by '-' is Iso8601
+/// - Anything else with a letter, a day or month name, is Rfc2822
+/// - Everything else is Unknown
+///
+/// Only the shape is checked, the string may still fail to parse.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{detect_time_format, TimeFormat};
+///
+/// assert_eq!(detect_time_format("2022-07-15T09:30:00Z"), TimeFormat::Iso8601);
+/// assert_eq!(detect_time_format("-1000"), TimeFormat::EpochMs);
+/// assert_eq!(detect_time_format("Fri, 15 Jul 2022 09:30:00 +0000"), TimeFormat::Rfc2822);
+/// assert_eq!(detect_time_format("?"), TimeFormat::Unknown);
+/// ```
+pub fn detect_time_format(s: &str) -> TimeFormat {
+    let s = s.trim();
+    let bytes = s.as_bytes();
+
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        TimeFormat::EpochMs
+    } else if bytes.len() > 4 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-' {
+        TimeFormat::Iso8601
+    } else if bytes.iter().any(u8::is_ascii_alphabetic) {
+        TimeFormat::Rfc2822
+    } else {
+        TimeFormat::Unknown
+    }
+}
+
+/// Parse a time string of any [`TimeFormat`] to utc time_ms.
+///
+/// Iso8601 strings are parsed by `dt_str_to_utc_time_ms` with
+/// `TzMassaging::CondAddTzUtc`, so no time zone means UTC.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::parse_any_to_time_ms;
+///
+/// assert_eq!(parse_any_to_time_ms("1970-01-01T00:00:01Z"), Ok(1000));
+/// assert_eq!(parse_any_to_time_ms("1000"), Ok(1000));
+/// assert_eq!(parse_any_to_time_ms("Thu, 1 Jan 1970 00:00:01 +0000"), Ok(1000));
+/// assert!(parse_any_to_time_ms("yesterday").is_err());
+/// ```
+pub fn parse_any_to_time_ms(s: &str) -> Result<i64, TimeConversionError> {
+    let trimmed = s.trim();
+    match detect_time_format(trimmed) {
+        TimeFormat::EpochMs => trimmed
+            .parse::<i64>()
+            .map_err(|e| TimeConversionError::parse(s, e)),
+        TimeFormat::Iso8601 => dt_str_to_utc_time_ms(trimmed, TzMassaging::CondAddTzUtc),
+        TimeFormat::Rfc2822 => rfc2822_str_to_utc_time_ms(s),
+        TimeFormat::Unknown => Err(TimeConversionError::UnknownFormat(s.to_string())),
+    }
+}
+
+/// Parse each of a list of time strings whose formats may differ,
+/// see [`parse_any_to_time_ms`].
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::parse_heterogeneous;
+///
+/// let results = parse_heterogeneous(&["1970-01-01T00:00:01Z", "2000", "garbage!"]);
+/// assert_eq!(results[0], Ok(1000));
+/// assert_eq!(results[1], Ok(2000));
+/// assert!(results[2].is_err());
+/// ```
+pub fn parse_heterogeneous(values: &[&str]) -> Vec<Result<i64, TimeConversionError>> {
+    values.iter().map(|s| parse_any_to_time_ms(s)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect_time_format() {
+        assert_eq!(detect_time_format("1970-01-01"), TimeFormat::Iso8601);
+        assert_eq!(
+            detect_time_format("1970-01-01 00:00:00"),
+            TimeFormat::Iso8601
+        );
+        assert_eq!(
+            detect_time_format(" 1970-01-01T00:00:00.123-0800 "),
+            TimeFormat::Iso8601
+        );
+        assert_eq!(detect_time_format("0"), TimeFormat::EpochMs);
+        assert_eq!(detect_time_format("1657877400000"), TimeFormat::EpochMs);
+        assert_eq!(detect_time_format("-1"), TimeFormat::EpochMs);
+        assert_eq!(
+            detect_time_format("Tue, 1 Jul 2003 10:52:37 +0200"),
+            TimeFormat::Rfc2822
+        );
+        assert_eq!(
+            detect_time_format("1 Jul 2003 10:52:37 GMT"),
+            TimeFormat::Rfc2822
+        );
+        assert_eq!(detect_time_format(""), TimeFormat::Unknown);
+        assert_eq!(detect_time_format("-"), TimeFormat::Unknown);
+        assert_eq!(detect_time_format("12:30"), TimeFormat::Unknown);
+    }
+
+    #[test]
+    fn test_parse_heterogeneous() {
+        let values = [
+            "2003-07-01T08:52:37Z",
+            "1057049557000",
+            "Tue, 1 Jul 2003 10:52:37 +0200",
+            "not a time",
+        ];
+        let results = parse_heterogeneous(&values);
+        dbg!(&results);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], Ok(1_057_049_557_000));
+        assert_eq!(results[1], Ok(1_057_049_557_000));
+        assert_eq!(results[2], Ok(1_057_049_557_000));
+        assert!(matches!(results[3], Err(TimeConversionError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_parse_heterogeneous_errors() {
+        let results = parse_heterogeneous(&["?", "99999999999999999999", "2003-13-01"]);
+        assert_eq!(
+            results[0],
+            Err(TimeConversionError::UnknownFormat("?".to_string()))
+        );
+        assert!(matches!(results[1], Err(TimeConversionError::Parse { .. })));
+        assert!(matches!(results[2], Err(TimeConversionError::Parse { .. })));
+        assert!(parse_heterogeneous(&[]).is_empty());
+    }
+}
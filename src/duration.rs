@@ -0,0 +1,332 @@
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::TimeConversionError;
+use crate::{MS_PER_DAY, MS_PER_HOUR, MS_PER_MIN, MS_PER_SEC};
+
+/// A milli-second duration broken down into units with the sign separate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DurationParts {
+    pub negative: bool,
+    pub days: i64,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub millis: u32,
+}
+
+/// Break a milli-second duration down into days, hours, minutes, seconds
+/// and milli-seconds with each sub-field in its normal range, 0..24 for
+/// hours and so on. The sign is in `negative` and all fields are positive.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{breakdown_duration_ms, DurationParts};
+///
+/// assert_eq!(
+///     breakdown_duration_ms(-183_315_001),
+///     DurationParts {
+///         negative: true,
+///         days: 2,
+///         hours: 2,
+///         minutes: 55,
+///         seconds: 15,
+///         millis: 1,
+///     }
+/// );
+/// ```
+pub fn breakdown_duration_ms(ms: i64) -> DurationParts {
+    // unsigned_abs so i64::MIN doesn't overflow
+    let abs_ms = ms.unsigned_abs();
+
+    DurationParts {
+        negative: ms < 0,
+        days: (abs_ms / MS_PER_DAY as u64) as i64,
+        hours: (abs_ms % MS_PER_DAY as u64 / MS_PER_HOUR as u64) as u32,
+        minutes: (abs_ms % MS_PER_HOUR as u64 / MS_PER_MIN as u64) as u32,
+        seconds: (abs_ms % MS_PER_MIN as u64 / MS_PER_SEC as u64) as u32,
+        millis: (abs_ms % MS_PER_SEC as u64) as u32,
+    }
+}
+
+/// Format a milli-second duration, such as the difference of two time_ms,
+/// as a human readable string like "1d 2h 3m 4s 567ms".
+///
+/// Leading zero units are omitted and negative durations have a leading
+/// '-'. Milli-seconds are shown when non-zero, a duration under a second
+/// is only "ms" and zero is "0ms".
+///
+/// Changed in 0.3.0: the milli-seconds are their own unit, "4s 567ms",
+/// rather than a fraction of the seconds, "4.567s".
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::format_duration_ms;
+///
+/// assert_eq!(format_duration_ms(93_784_567), "1d 2h 3m 4s 567ms");
+/// assert_eq!(format_duration_ms(-61_000), "-1m 1s");
+/// assert_eq!(format_duration_ms(250), "250ms");
+/// assert_eq!(format_duration_ms(0), "0ms");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn format_duration_ms(delta_ms: i64) -> String {
+    let DurationParts {
+        negative,
+        days,
+        hours,
+        minutes,
+        seconds,
+        millis,
+    } = breakdown_duration_ms(delta_ms);
+    let sign = if negative { "-" } else { "" };
+
+    if days == 0 && hours == 0 && minutes == 0 && seconds == 0 {
+        return format!("{sign}{millis}ms");
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    if days > 0 {
+        parts.push(format!("{days}d"));
+    }
+    if !parts.is_empty() || hours > 0 {
+        parts.push(format!("{hours}h"));
+    }
+    if !parts.is_empty() || minutes > 0 {
+        parts.push(format!("{minutes}m"));
+    }
+    parts.push(format!("{seconds}s"));
+    if millis > 0 {
+        parts.push(format!("{millis}ms"));
+    }
+
+    format!("{sign}{}", parts.join(" "))
+}
+
+/// Parse a human readable duration such as "1h30m", "500ms", "2d" or
+/// the output of [`format_duration_ms`], "-1d 2h 3m 4s 567ms", to
+/// milli-seconds.
+///
+/// The string is an optional leading '-' then one or more segments of an
+/// integer and a unit, `d`, `h`, `m`, `s` or `ms`, optionally separated
+/// by whitespace. Each unit may appear once with the units in that order,
+/// the magnitudes aren't limited to their normal range so "90m" is valid.
+///
+/// # Errors
+///
+/// A Parse error for empty input, an unknown or repeated unit or a
+/// missing magnitude and OutOfRange if the duration doesn't fit in an i64.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{format_duration_ms, parse_duration_ms};
+///
+/// assert_eq!(parse_duration_ms("1h30m"), Ok(5_400_000));
+/// assert_eq!(parse_duration_ms("500ms"), Ok(500));
+/// assert_eq!(parse_duration_ms("-2d"), Ok(-172_800_000));
+/// assert_eq!(parse_duration_ms(&format_duration_ms(93_784_567)), Ok(93_784_567));
+/// assert!(parse_duration_ms("1x").is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn parse_duration_ms(s: &str) -> Result<i64, TimeConversionError> {
+    // The units in the order they must appear
+    const UNITS: [(&str, i64); 5] = [
+        ("d", MS_PER_DAY),
+        ("h", MS_PER_HOUR),
+        ("m", MS_PER_MIN),
+        ("s", MS_PER_SEC),
+        ("ms", 1),
+    ];
+
+    let parse_err = |reason: &str| TimeConversionError::parse(s, reason);
+
+    let trimmed = s.trim();
+    let (negative, mut rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    if rest.trim().is_empty() {
+        return Err(parse_err("empty"));
+    }
+
+    // i128 so i64::MIN's magnitude and overflow are handled
+    let mut total: i128 = 0;
+    let mut next_unit = 0;
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(parse_err("expected a number"));
+        }
+        let magnitude: i128 = rest[..digits]
+            .parse()
+            .map_err(|_| TimeConversionError::OutOfRange)?;
+        rest = &rest[digits..];
+
+        let unit_len = rest.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let unit = &rest[..unit_len];
+        let idx = match UNITS.iter().position(|&(name, _)| name == unit) {
+            Some(idx) => idx,
+            None if unit.is_empty() => return Err(parse_err("missing unit")),
+            None => return Err(parse_err("unknown unit, expected d, h, m, s or ms")),
+        };
+        if idx < next_unit {
+            return Err(parse_err("units must be in the order d, h, m, s, ms"));
+        }
+        next_unit = idx + 1;
+        rest = rest[unit_len..].trim_start();
+
+        total = magnitude
+            .checked_mul(UNITS[idx].1 as i128)
+            .and_then(|ms| total.checked_add(ms))
+            .ok_or(TimeConversionError::OutOfRange)?;
+    }
+
+    let total = if negative { -total } else { total };
+    i64::try_from(total).map_err(|_| TimeConversionError::OutOfRange)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_breakdown_duration_ms() {
+        assert_eq!(breakdown_duration_ms(0), DurationParts::default());
+        assert_eq!(
+            breakdown_duration_ms(183_315_000),
+            DurationParts {
+                negative: false,
+                days: 2,
+                hours: 2,
+                minutes: 55,
+                seconds: 15,
+                millis: 0,
+            }
+        );
+        assert_eq!(
+            breakdown_duration_ms(86_399_999),
+            DurationParts {
+                negative: false,
+                days: 0,
+                hours: 23,
+                minutes: 59,
+                seconds: 59,
+                millis: 999,
+            }
+        );
+    }
+
+    #[test]
+    fn test_breakdown_duration_ms_negative() {
+        let parts = breakdown_duration_ms(-183_315_000);
+        assert!(parts.negative);
+        assert_eq!(
+            parts,
+            DurationParts {
+                negative: true,
+                ..breakdown_duration_ms(183_315_000)
+            }
+        );
+
+        let parts = breakdown_duration_ms(i64::MIN);
+        assert!(parts.negative);
+        assert_eq!(parts.days, 106_751_991_167);
+        assert_eq!(parts.millis, 808);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_format_duration_ms() {
+        assert_eq!(format_duration_ms(0), "0ms");
+        assert_eq!(format_duration_ms(1), "1ms");
+        assert_eq!(format_duration_ms(999), "999ms");
+        assert_eq!(format_duration_ms(1000), "1s");
+        assert_eq!(format_duration_ms(1001), "1s 1ms");
+        assert_eq!(format_duration_ms(61_000), "1m 1s");
+        assert_eq!(format_duration_ms(3_723_400), "1h 2m 3s 400ms");
+        assert_eq!(format_duration_ms(90_061_400), "1d 1h 1m 1s 400ms");
+        assert_eq!(format_duration_ms(3_600_000), "1h 0m 0s");
+        assert_eq!(format_duration_ms(93_784_567), "1d 2h 3m 4s 567ms");
+        assert_eq!(format_duration_ms(86_400_500), "1d 0h 0m 0s 500ms");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_format_duration_ms_negative() {
+        assert_eq!(format_duration_ms(-1), "-1ms");
+        assert_eq!(format_duration_ms(-500), "-500ms");
+        assert_eq!(format_duration_ms(-1500), "-1s 500ms");
+        assert_eq!(format_duration_ms(-93_784_567), "-1d 2h 3m 4s 567ms");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_format_duration_ms_large() {
+        assert_eq!(
+            format_duration_ms(i64::MAX),
+            "106751991167d 7h 12m 55s 807ms"
+        );
+        assert_eq!(
+            format_duration_ms(i64::MIN),
+            "-106751991167d 7h 12m 55s 808ms"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_parse_duration_ms() {
+        assert_eq!(parse_duration_ms("1h30m"), Ok(5_400_000));
+        assert_eq!(parse_duration_ms("500ms"), Ok(500));
+        assert_eq!(parse_duration_ms("2d"), Ok(172_800_000));
+        assert_eq!(parse_duration_ms("0ms"), Ok(0));
+        assert_eq!(parse_duration_ms("90m"), Ok(5_400_000));
+        assert_eq!(parse_duration_ms("1m1ms"), Ok(60_001));
+        assert_eq!(parse_duration_ms(" 1d 2h 3m 4s 567ms "), Ok(93_784_567));
+        assert_eq!(parse_duration_ms("-1s 500ms"), Ok(-1500));
+        assert_eq!(parse_duration_ms("-0s"), Ok(0));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_parse_duration_ms_round_trip() {
+        for ms in [
+            0,
+            1,
+            -1,
+            999,
+            1000,
+            -1500,
+            61_000,
+            3_600_000,
+            93_784_567,
+            -93_784_567,
+            86_400_500,
+            i64::MAX,
+            i64::MIN,
+        ] {
+            let s = format_duration_ms(ms);
+            assert_eq!(parse_duration_ms(&s), Ok(ms), "{s}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_parse_duration_ms_errors() {
+        for s in [
+            "", " ", "-", "1x", "1", "h", "1h 2", "1.5h", "1m1h", "1s1s", "1h -1m", "1 h", "+1h",
+            "1msx", "1H",
+        ] {
+            let e = parse_duration_ms(s).unwrap_err();
+            assert!(matches!(e, TimeConversionError::Parse { .. }), "{s}: {e:?}");
+        }
+
+        assert_eq!(
+            parse_duration_ms("106751991167d 7h 12m 55s 808ms"),
+            Err(TimeConversionError::OutOfRange)
+        );
+        assert_eq!(
+            parse_duration_ms("99999999999999999999999999999999999999999d"),
+            Err(TimeConversionError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use core::ops::RangeInclusive;
+
+use crate::{nanos_to_time_ms_rounded, time_us_to_time_ms, RoundingMode, TimeMsError};
+
+/// The unit of an epoch number, see [`epoch_to_time_ms`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EpochUnit {
+    Second,
+    Millisecond,
+    Microsecond,
+    Nanosecond,
+}
+
+// The smallest magnitudes detect_epoch_unit treats as milli-seconds,
+// micro-seconds and nano-seconds
+const MIN_EPOCH_MS: u64 = 100_000_000_000;
+const MIN_EPOCH_US: u64 = 100_000_000_000_000;
+const MIN_EPOCH_NS: u64 = 100_000_000_000_000_000;
+
+/// Convert `value`, since the Unix epoch in `unit`, to time_ms
+///
+/// Micro-seconds and nano-seconds are rounded to the nearest milli-second
+/// with halves rounded up as [`utc_to_time_ms`](crate::utc_to_time_ms)
+/// does. Returns `TimeMsError::OutOfRange` if seconds overflow an i64 of
+/// milli-seconds.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{epoch_to_time_ms, EpochUnit, TimeMsError};
+///
+/// assert_eq!(epoch_to_time_ms(1_657_886_400, EpochUnit::Second), Ok(1_657_886_400_000));
+/// assert_eq!(epoch_to_time_ms(-1_500, EpochUnit::Microsecond), Ok(-1));
+/// assert_eq!(epoch_to_time_ms(i64::MAX, EpochUnit::Second), Err(TimeMsError::OutOfRange));
+/// ```
+pub fn epoch_to_time_ms(value: i64, unit: EpochUnit) -> Result<i64, TimeMsError> {
+    match unit {
+        EpochUnit::Second => value.checked_mul(1000).ok_or(TimeMsError::OutOfRange),
+        EpochUnit::Millisecond => Ok(value),
+        EpochUnit::Microsecond => Ok(time_us_to_time_ms(value)),
+        EpochUnit::Nanosecond => Ok(nanos_to_time_ms_rounded(
+            value as i128,
+            RoundingMode::HalfUp,
+        )),
+    }
+}
+
+/// The unit of the epoch number `value` inferred from its magnitude,
+/// the absolute value so negative values are treated the same way:
+///
+/// | magnitude                                | unit        |
+/// |------------------------------------------|-------------|
+/// | < 100_000_000_000 (1e11)                 | Second      |
+/// | 1e11 to < 100_000_000_000_000 (1e14)     | Millisecond |
+/// | 1e14 to < 100_000_000_000_000_000 (1e17) | Microsecond |
+/// | >= 1e17                                  | Nanosecond  |
+///
+/// So each unit covers about +/-3,200 years around the epoch, 1e11
+/// seconds is the year 5138, except nano-seconds which an i64 limits to
+/// 1677 through 2262. The cost is that a time within about 3 years of the
+/// epoch in a finer unit is mistaken for a coarser one, such as
+/// 1970-01-02 in milli-seconds, 86_400_000, which is seconds. Small
+/// values, such as 0 and 12_345, are therefore seconds.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{detect_epoch_unit, EpochUnit};
+///
+/// assert_eq!(detect_epoch_unit(1_700_000_000), EpochUnit::Second);
+/// assert_eq!(detect_epoch_unit(1_700_000_000_000), EpochUnit::Millisecond);
+/// assert_eq!(detect_epoch_unit(1_700_000_000_000_000), EpochUnit::Microsecond);
+/// assert_eq!(detect_epoch_unit(1_700_000_000_000_000_000), EpochUnit::Nanosecond);
+/// assert_eq!(detect_epoch_unit(-1_700_000_000_000), EpochUnit::Millisecond);
+/// assert_eq!(detect_epoch_unit(12_345), EpochUnit::Second);
+/// ```
+pub fn detect_epoch_unit(value: i64) -> EpochUnit {
+    match value.unsigned_abs() {
+        magnitude if magnitude < MIN_EPOCH_MS => EpochUnit::Second,
+        magnitude if magnitude < MIN_EPOCH_US => EpochUnit::Millisecond,
+        magnitude if magnitude < MIN_EPOCH_NS => EpochUnit::Microsecond,
+        _ => EpochUnit::Nanosecond,
+    }
+}
+
+/// Convert the epoch number `value` to time_ms inferring its unit with
+/// [`detect_epoch_unit`], see it for the thresholds and how small and
+/// negative values are treated.
+///
+/// The error is that of [`epoch_to_time_ms`], seconds below the threshold
+/// always fit so it's currently always Ok.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::epoch_any_to_time_ms;
+///
+/// // 2022-07-15T12:00:00.123Z in each unit
+/// assert_eq!(epoch_any_to_time_ms(1_657_886_400), Ok(1_657_886_400_000));
+/// assert_eq!(epoch_any_to_time_ms(1_657_886_400_123), Ok(1_657_886_400_123));
+/// assert_eq!(epoch_any_to_time_ms(1_657_886_400_123_456), Ok(1_657_886_400_123));
+/// assert_eq!(epoch_any_to_time_ms(1_657_886_400_123_456_789), Ok(1_657_886_400_123));
+/// ```
+pub fn epoch_any_to_time_ms(value: i64) -> Result<i64, TimeMsError> {
+    epoch_to_time_ms(value, detect_epoch_unit(value))
+}
+
+/// Repair a time_ms that was probably given in the wrong unit.
+///
+/// If `value` is in `plausible_range` it's returned unchanged. Otherwise
+/// if `value * 1000` is in range, `value` was seconds and is promoted to
+/// milli-seconds, or if `value / 1000` (floored) is in range, `value` was
+/// micro-seconds and is demoted. If none of these are in range None is
+/// returned.
+///
+/// This is a pragmatic data-repair helper, a value that is plausible in
+/// more than one unit is never "fixed" so choose a tight range.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::sanity_fix_time_ms;
+///
+/// // 2000-01-01 through 2100-01-01
+/// let plausible = 946_684_800_000..=4_102_444_800_000;
+///
+/// // Seconds for 2022-07-15T00:00:00Z
+/// assert_eq!(sanity_fix_time_ms(1_657_843_200, plausible.clone()), Some(1_657_843_200_000));
+/// assert_eq!(sanity_fix_time_ms(42, plausible), None);
+/// ```
+pub fn sanity_fix_time_ms(value: i64, plausible_range: RangeInclusive<i64>) -> Option<i64> {
+    if plausible_range.contains(&value) {
+        return Some(value);
+    }
+
+    if let Some(promoted) = value.checked_mul(1000) {
+        if plausible_range.contains(&promoted) {
+            return Some(promoted);
+        }
+    }
+
+    let demoted = value.div_euclid(1000);
+    if plausible_range.contains(&demoted) {
+        return Some(demoted);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use EpochUnit::*;
+
+    // 2000-01-01T00:00:00Z ..= 2100-01-01T00:00:00Z
+    const PLAUSIBLE: RangeInclusive<i64> = 946_684_800_000..=4_102_444_800_000;
+
+    #[test]
+    fn test_sanity_fix_time_ms_in_range() {
+        assert_eq!(
+            sanity_fix_time_ms(1_657_843_200_123, PLAUSIBLE),
+            Some(1_657_843_200_123)
+        );
+        assert_eq!(
+            sanity_fix_time_ms(*PLAUSIBLE.start(), PLAUSIBLE),
+            Some(*PLAUSIBLE.start())
+        );
+    }
+
+    #[test]
+    fn test_sanity_fix_time_ms_seconds_promoted() {
+        assert_eq!(
+            sanity_fix_time_ms(1_657_843_200, PLAUSIBLE),
+            Some(1_657_843_200_000)
+        );
+    }
+
+    #[test]
+    fn test_sanity_fix_time_ms_micros_demoted() {
+        assert_eq!(
+            sanity_fix_time_ms(1_657_843_200_123_456, PLAUSIBLE),
+            Some(1_657_843_200_123)
+        );
+    }
+
+    #[test]
+    fn test_sanity_fix_time_ms_no_correction() {
+        assert_eq!(sanity_fix_time_ms(0, PLAUSIBLE), None);
+        assert_eq!(sanity_fix_time_ms(-1_657_843_200, PLAUSIBLE), None);
+
+        // Nanoseconds are off by 1_000_000 which isn't corrected
+        assert_eq!(
+            sanity_fix_time_ms(1_657_843_200_123_456_789, PLAUSIBLE),
+            None
+        );
+
+        // Doesn't overflow
+        assert_eq!(sanity_fix_time_ms(i64::MAX, PLAUSIBLE), None);
+        assert_eq!(sanity_fix_time_ms(i64::MIN, PLAUSIBLE), None);
+    }
+
+    #[test]
+    fn test_epoch_to_time_ms() {
+        let cases = [
+            // value, unit, expected
+            (0, Second, 0),
+            (-1, Second, -1_000),
+            (1_657_886_400, Second, 1_657_886_400_000),
+            (i64::MAX / 1000, Second, i64::MAX / 1000 * 1000),
+            (-1, Millisecond, -1),
+            (i64::MIN, Millisecond, i64::MIN),
+            (1_499, Microsecond, 1),
+            (1_500, Microsecond, 2),
+            (-1_500, Microsecond, -1),
+            (-1_501, Microsecond, -2),
+            (499_999, Nanosecond, 0),
+            (500_000, Nanosecond, 1),
+            (-500_000, Nanosecond, 0),
+            (-500_001, Nanosecond, -1),
+            (i64::MAX, Nanosecond, 9_223_372_036_855),
+            (i64::MIN, Nanosecond, -9_223_372_036_855),
+        ];
+        for (value, unit, expected) in cases {
+            assert_eq!(
+                epoch_to_time_ms(value, unit),
+                Ok(expected),
+                "{value} {unit:?}"
+            );
+        }
+
+        assert_eq!(
+            epoch_to_time_ms(i64::MAX / 1000 + 1, Second),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(
+            epoch_to_time_ms(i64::MIN, Second),
+            Err(TimeMsError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_detect_epoch_unit_thresholds() {
+        let cases = [
+            (0, Second),
+            (12_345, Second),
+            (-12_345, Second),
+            (99_999_999_999, Second),
+            (-99_999_999_999, Second),
+            (100_000_000_000, Millisecond),
+            (-100_000_000_000, Millisecond),
+            (99_999_999_999_999, Millisecond),
+            (100_000_000_000_000, Microsecond),
+            (-100_000_000_000_000, Microsecond),
+            (99_999_999_999_999_999, Microsecond),
+            (100_000_000_000_000_000, Nanosecond),
+            (-100_000_000_000_000_000, Nanosecond),
+            (i64::MAX, Nanosecond),
+            (i64::MIN, Nanosecond),
+        ];
+        for (value, unit) in cases {
+            assert_eq!(detect_epoch_unit(value), unit, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_epoch_any_to_time_ms() {
+        let cases = [
+            // Small values are seconds
+            (0, 0),
+            (12_345, 12_345_000),
+            (-1, -1_000),
+            // 2022-07-15T12:00:00.123Z in each unit
+            (1_657_886_400, 1_657_886_400_000),
+            (1_657_886_400_123, 1_657_886_400_123),
+            (1_657_886_400_123_456, 1_657_886_400_123),
+            (1_657_886_400_123_456_789, 1_657_886_400_123),
+            // 1917-06-19T11:59:59.877Z, before the epoch, in each unit
+            (-1_657_886_401, -1_657_886_401_000),
+            (-1_657_886_400_123, -1_657_886_400_123),
+            (-1_657_886_400_123_456, -1_657_886_400_123),
+            (-1_657_886_400_123_456_789, -1_657_886_400_123),
+            // The largest seconds still fit
+            (99_999_999_999, 99_999_999_999_000),
+            (-99_999_999_999, -99_999_999_999_000),
+            (i64::MIN, -9_223_372_036_855),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(epoch_any_to_time_ms(value), Ok(expected), "{value}");
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+
+/// Errors returned by the time_ms conversions
+///
+/// Some variants only exist with the `alloc` feature, so the enum is
+/// `#[non_exhaustive]` to keep the features additive, a `match` needs a
+/// wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum TimeMsError {
+    /// The input had no values
+    EmptyInput,
+
+    /// Two inputs that must be the same length were not
+    LengthMismatch { left: usize, right: usize },
+
+    /// A weight was negative or not a number
+    InvalidWeight(f64),
+
+    /// The weights summed to zero
+    ZeroTotalWeight,
+
+    /// A value was not finite or is outside the range of the result
+    OutOfRange,
+
+    /// `value` couldn't be parsed as a time
+    #[cfg(feature = "alloc")]
+    Parse { value: String, reason: String },
+
+    /// The format of the value couldn't be determined
+    #[cfg(feature = "alloc")]
+    UnknownFormat(String),
+
+    /// `value` has a time zone offset other than UTC, `offset_seconds`
+    /// east of UTC, where only UTC is accepted
+    #[cfg(feature = "alloc")]
+    NonUtcOffset { value: String, offset_seconds: i32 },
+
+    /// The value has no time zone where one is required
+    #[cfg(feature = "alloc")]
+    MissingTimezone(String),
+}
+
+/// The name used by the string conversions, it's the same type as [`TimeMsError`]
+pub type TimeConversionError = TimeMsError;
+
+#[cfg(feature = "alloc")]
+impl TimeMsError {
+    pub(crate) fn parse(value: &str, reason: impl fmt::Display) -> Self {
+        TimeMsError::Parse {
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TimeMsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeMsError::EmptyInput => write!(f, "empty input"),
+            TimeMsError::LengthMismatch { left, right } => {
+                write!(f, "length mismatch: {left} != {right}")
+            }
+            TimeMsError::InvalidWeight(w) => write!(f, "invalid weight: {w}"),
+            TimeMsError::ZeroTotalWeight => write!(f, "weights sum to zero"),
+            TimeMsError::OutOfRange => write!(f, "value out of range"),
+            #[cfg(feature = "alloc")]
+            TimeMsError::Parse { value, reason } => {
+                write!(f, "unable to parse \"{value}\": {reason}")
+            }
+            #[cfg(feature = "alloc")]
+            TimeMsError::UnknownFormat(value) => write!(f, "unknown time format: \"{value}\""),
+            #[cfg(feature = "alloc")]
+            TimeMsError::NonUtcOffset {
+                value,
+                offset_seconds,
+            } => {
+                let sign = if *offset_seconds < 0 { '-' } else { '+' };
+                let minutes = offset_seconds.unsigned_abs() / 60;
+                write!(
+                    f,
+                    "non-UTC offset {sign}{:02}:{:02} in \"{value}\"",
+                    minutes / 60,
+                    minutes % 60
+                )
+            }
+            #[cfg(feature = "alloc")]
+            TimeMsError::MissingTimezone(value) => write!(f, "missing time zone in \"{value}\""),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimeMsError {}
@@ -0,0 +1,289 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::conversions::{fixed_offset_to_time_ms, naive_local_to_fo};
+use crate::{time_ms_to_local, TimeMsError, TzMassaging};
+
+/// The order of the day and month in a spreadsheet date, which depends
+/// on the locale of the spreadsheet
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DateOrder {
+    /// "1/15/2023", as in the US
+    MonthDayYear,
+
+    /// "15/01/2023", as in most of Europe
+    DayMonthYear,
+}
+
+/// Convert a date time string as a spreadsheet such as Excel writes it,
+/// "1/15/2023 9:30:00 AM" or "15/01/2023 09:30", to utc time_ms.
+///
+/// The date is day, month and a four digit year separated by '/' in
+/// `date_order`, the time is 12 hour with "AM" or "PM" or 24 hour,
+/// seconds are optional and day, month, hour, minute and second may be
+/// one or two digits. A date without a time is midnight.
+///
+/// The string has no time zone, it's UTC with CondAddTzUtc and local with
+/// LocalTz, HasTz is an error.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{excel_local_str_to_time_ms, DateOrder, TzMassaging};
+///
+/// let tms = excel_local_str_to_time_ms("1/15/2023 9:30:00 AM", DateOrder::MonthDayYear, TzMassaging::CondAddTzUtc);
+/// assert_eq!(tms, Ok(1_673_775_000_000));
+///
+/// let tms = excel_local_str_to_time_ms("15/01/2023 09:30", DateOrder::DayMonthYear, TzMassaging::CondAddTzUtc);
+/// assert_eq!(tms, Ok(1_673_775_000_000));
+/// ```
+pub fn excel_local_str_to_time_ms(
+    s: &str,
+    date_order: DateOrder,
+    tz_massaging: TzMassaging,
+) -> Result<i64, TimeMsError> {
+    let s = s.trim();
+    let parse_err = |reason: &str| TimeMsError::parse(s, reason);
+
+    let mut parts = s.split_whitespace();
+    let date_str = parts.next().ok_or_else(|| parse_err("empty"))?;
+    let time_str = parts.next();
+    let am_pm = parts.next();
+    if parts.next().is_some() {
+        return Err(parse_err("expected a date, a time and AM or PM"));
+    }
+
+    let date = parse_date(date_str, date_order).ok_or_else(|| parse_err("invalid date"))?;
+    let time = match time_str {
+        Some(time_str) => parse_time(time_str, am_pm).ok_or_else(|| parse_err("invalid time"))?,
+        None => NaiveTime::MIN,
+    };
+    let ndt = NaiveDateTime::new(date, time);
+
+    let dtfo = match tz_massaging {
+        TzMassaging::CondAddTzUtc => ndt.and_utc().fixed_offset(),
+        TzMassaging::LocalTz => naive_local_to_fo(s, &ndt)?,
+        TzMassaging::HasTz => return Err(parse_err("spreadsheet strings have no time zone")),
+        TzMassaging::RequireTz => return Err(TimeMsError::MissingTimezone(s.to_string())),
+    };
+
+    Ok(fixed_offset_to_time_ms(&dtfo))
+}
+
+// A one or two digit field
+fn parse_small(field: &str) -> Option<u32> {
+    if field.is_empty() || field.len() > 2 || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    field.parse().ok()
+}
+
+fn parse_date(date_str: &str, date_order: DateOrder) -> Option<NaiveDate> {
+    let mut fields = date_str.split('/');
+    let (first, second, year) = (fields.next()?, fields.next()?, fields.next()?);
+    if fields.next().is_some() || year.len() != 4 || !year.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (month, day) = match date_order {
+        DateOrder::MonthDayYear => (first, second),
+        DateOrder::DayMonthYear => (second, first),
+    };
+
+    NaiveDate::from_ymd_opt(year.parse().ok()?, parse_small(month)?, parse_small(day)?)
+}
+
+fn parse_time(time_str: &str, am_pm: Option<&str>) -> Option<NaiveTime> {
+    // "9:30AM" as well as "9:30 AM"
+    let (time_str, am_pm) = match am_pm {
+        Some(am_pm) => (time_str, Some(am_pm)),
+        None => {
+            let split = time_str.len().saturating_sub(2);
+            match time_str.get(split..) {
+                Some(suffix)
+                    if suffix.eq_ignore_ascii_case("am") || suffix.eq_ignore_ascii_case("pm") =>
+                {
+                    (&time_str[..split], Some(suffix))
+                }
+                _ => (time_str, None),
+            }
+        }
+    };
+
+    let mut fields = time_str.split(':');
+    let hour = parse_small(fields.next()?)?;
+    let minute = parse_small(fields.next()?)?;
+    let second = match fields.next() {
+        Some(second) => parse_small(second)?,
+        None => 0,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let hour = match am_pm {
+        None => hour,
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(am_pm) if am_pm.eq_ignore_ascii_case("am") => hour % 12,
+        Some(am_pm) if am_pm.eq_ignore_ascii_case("pm") => hour % 12 + 12,
+        Some(_) => return None,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// Convert time_ms to a date time string in the machine's local time zone
+/// that a spreadsheet using `date_order` parses back unchanged,
+/// "1/15/2023 9:30:00" or "15/01/2023 09:30:00".
+///
+/// Spreadsheets don't show milli-seconds so they're dropped, flooring the
+/// time.
+///
+/// # Panics
+///
+/// If time_ms is out of the range of DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{
+///     excel_local_str_to_time_ms, time_ms_to_excel_local_str, DateOrder, TzMassaging,
+/// };
+///
+/// let s = time_ms_to_excel_local_str(1_673_775_000_123, DateOrder::DayMonthYear);
+/// let tms = excel_local_str_to_time_ms(&s, DateOrder::DayMonthYear, TzMassaging::LocalTz);
+/// assert_eq!(tms, Ok(1_673_775_000_000));
+/// ```
+pub fn time_ms_to_excel_local_str(time_ms: i64, date_order: DateOrder) -> String {
+    let fmt = match date_order {
+        DateOrder::MonthDayYear => "%-m/%-d/%Y %-H:%M:%S",
+        DateOrder::DayMonthYear => "%d/%m/%Y %H:%M:%S",
+    };
+
+    time_ms_to_local(time_ms).format(fmt).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time_ms_from_utc_parts;
+
+    use DateOrder::*;
+    use TzMassaging::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> i64 {
+        time_ms_from_utc_parts(year, month, day, hour, minute, second, 0).unwrap()
+    }
+
+    #[test]
+    fn test_excel_local_str_to_time_ms() {
+        let expected = utc(2023, 1, 15, 9, 30, 0);
+        let cases = [
+            ("1/15/2023 9:30:00", MonthDayYear),
+            ("01/15/2023 09:30:00", MonthDayYear),
+            ("1/15/2023 9:30", MonthDayYear),
+            ("1/15/2023 9:30:00 AM", MonthDayYear),
+            ("1/15/2023 9:30 am", MonthDayYear),
+            ("1/15/2023 9:30AM", MonthDayYear),
+            ("15/01/2023 09:30", DayMonthYear),
+            ("15/1/2023 9:30:00", DayMonthYear),
+            (" 15/01/2023  09:30:00 ", DayMonthYear),
+        ];
+        for (s, date_order) in cases {
+            assert_eq!(
+                excel_local_str_to_time_ms(s, date_order, CondAddTzUtc),
+                Ok(expected),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_excel_local_str_to_time_ms_12_hour() {
+        let at = |s| excel_local_str_to_time_ms(s, MonthDayYear, CondAddTzUtc);
+        assert_eq!(at("1/15/2023 12:00:00 AM"), Ok(utc(2023, 1, 15, 0, 0, 0)));
+        assert_eq!(at("1/15/2023 12:30:00 PM"), Ok(utc(2023, 1, 15, 12, 30, 0)));
+        assert_eq!(at("1/15/2023 1:05:09 PM"), Ok(utc(2023, 1, 15, 13, 5, 9)));
+        assert_eq!(
+            at("1/15/2023 11:59:59 PM"),
+            Ok(utc(2023, 1, 15, 23, 59, 59))
+        );
+        assert_eq!(at("1/15/2023"), Ok(utc(2023, 1, 15, 0, 0, 0)));
+    }
+
+    #[test]
+    fn test_excel_local_str_to_time_ms_ambiguous_date() {
+        let s = "01/02/2023";
+        assert_eq!(
+            excel_local_str_to_time_ms(s, MonthDayYear, CondAddTzUtc),
+            Ok(utc(2023, 1, 2, 0, 0, 0))
+        );
+        assert_eq!(
+            excel_local_str_to_time_ms(s, DayMonthYear, CondAddTzUtc),
+            Ok(utc(2023, 2, 1, 0, 0, 0))
+        );
+
+        // Only valid in one order
+        assert!(excel_local_str_to_time_ms("15/01/2023", MonthDayYear, CondAddTzUtc).is_err());
+        assert!(excel_local_str_to_time_ms("1/15/2023", DayMonthYear, CondAddTzUtc).is_err());
+    }
+
+    #[test]
+    fn test_excel_local_str_to_time_ms_errors() {
+        for s in [
+            "",
+            "1/15/23 9:30",
+            "1/15/2023 13:30 PM",
+            "1/15/2023 0:30 AM",
+            "1/15/2023 9:30 XM",
+            "1/15/2023 24:00",
+            "1/15/2023 9:30:00.123",
+            "1/15/2023 9",
+            "001/15/2023",
+            "2023-01-15 09:30",
+            "1/15/2023 9:30 AM extra",
+        ] {
+            let e = excel_local_str_to_time_ms(s, MonthDayYear, CondAddTzUtc).unwrap_err();
+            dbg!(&e);
+            assert!(matches!(e, TimeMsError::Parse { .. }), "{s}");
+        }
+
+        let e = excel_local_str_to_time_ms("1/15/2023 9:30", MonthDayYear, HasTz).unwrap_err();
+        assert!(e.to_string().contains("no time zone"), "{e}");
+
+        let e = excel_local_str_to_time_ms("1/15/2023 9:30", MonthDayYear, RequireTz).unwrap_err();
+        assert_eq!(
+            e,
+            TimeMsError::MissingTimezone("1/15/2023 9:30".to_string())
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_excel_local_str_round_trip() {
+        for date_order in [MonthDayYear, DayMonthYear] {
+            for tms in [
+                utc(2023, 1, 15, 9, 30, 0),
+                utc(2023, 2, 1, 23, 5, 9),
+                utc(1969, 12, 31, 0, 0, 1),
+                utc(2022, 7, 5, 12, 0, 0) + 999,
+            ] {
+                let s = time_ms_to_excel_local_str(tms, date_order);
+                dbg!(&s);
+                let parsed = excel_local_str_to_time_ms(&s, date_order, LocalTz).unwrap();
+                assert_eq!(parsed, tms - tms.rem_euclid(1000), "{s}");
+                assert_eq!(time_ms_to_excel_local_str(parsed, date_order), s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_excel_local_str_format() {
+        // The offset of the local time zone is the same for both
+        let tms = utc(2023, 1, 2, 3, 4, 5);
+        let mdy = time_ms_to_excel_local_str(tms, MonthDayYear);
+        let dmy = time_ms_to_excel_local_str(tms, DayMonthYear);
+        let local = time_ms_to_local(tms);
+        assert_eq!(mdy, local.format("%-m/%-d/%Y %-H:%M:%S").to_string());
+        assert_eq!(dmy, local.format("%d/%m/%Y %H:%M:%S").to_string());
+        if local.offset().local_minus_utc() == 0 {
+            assert_eq!(mdy, "1/2/2023 3:04:05");
+            assert_eq!(dmy, "02/01/2023 03:04:05");
+        }
+    }
+}
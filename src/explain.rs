@@ -0,0 +1,202 @@
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Local, SecondsFormat, Utc, Weekday};
+
+use crate::{
+    time_ms_to_iso_week, time_ms_to_secs_nsecs, time_ms_to_utc, time_ms_to_utc_string, MS_PER_DAY,
+};
+
+// 2000-01-01T00:00:00Z ..= 2100-01-01T00:00:00Z
+const PLAUSIBLE_MIN_MS: i64 = 946_684_800_000;
+const PLAUSIBLE_MAX_MS: i64 = 4_102_444_800_000;
+
+/// Everything about a time_ms, see [`explain_time_ms`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeMsExplanation {
+    pub time_ms: i64,
+
+    /// RFC 3339 UTC string with milli-seconds
+    pub utc: String,
+
+    /// RFC 3339 local string with milli-seconds
+    pub local: String,
+
+    // UTC date components
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub weekday: Weekday,
+
+    /// UTC day of the year, 1 for January 1st
+    pub ordinal: u32,
+
+    /// UTC ISO 8601 week as `(iso_year, week)`
+    pub iso_week: (i32, u32),
+
+    /// Milli-seconds since UTC midnight
+    pub ms_of_day: i64,
+
+    /// [`time_ms_to_secs_nsecs`] of time_ms
+    pub secs: i64,
+    pub nsecs: u32,
+
+    /// True if time_ms is from 2000-01-01 through 2100-01-01 UTC
+    pub plausible: bool,
+
+    /// The RFC 3339 UTC string if time_ms were actually seconds,
+    /// micro-seconds or nano-seconds, None if out of range
+    pub as_secs: Option<String>,
+    pub as_micros: Option<String>,
+    pub as_nanos: Option<String>,
+}
+
+/// Explain `time_ms` for debugging, the UTC and local strings, date
+/// components, secs/nsecs and what the value would be in other units.
+///
+/// Panics, as [`time_ms_to_utc`] does, if time_ms is out of range for chrono.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::explain_time_ms;
+///
+/// let e = explain_time_ms(1_657_886_400_123);
+/// assert_eq!(e.utc, "2022-07-15T12:00:00.123+00:00");
+/// assert_eq!(e.iso_week, (2022, 28));
+/// assert!(e.plausible);
+/// println!("{e}");
+/// ```
+pub fn explain_time_ms(time_ms: i64) -> TimeMsExplanation {
+    let utc = time_ms_to_utc(time_ms);
+    let (secs, nsecs) = time_ms_to_secs_nsecs(time_ms);
+
+    // Interpret time_ms as another unit, converted to milli-seconds
+    let as_unit = |ms: Option<i64>| {
+        ms.and_then(DateTime::<Utc>::from_timestamp_millis)
+            .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, false))
+    };
+
+    TimeMsExplanation {
+        time_ms,
+        utc: time_ms_to_utc_string(time_ms),
+        local: utc
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Millis, false),
+        year: utc.year(),
+        month: utc.month(),
+        day: utc.day(),
+        weekday: utc.weekday(),
+        ordinal: utc.ordinal(),
+        iso_week: time_ms_to_iso_week(time_ms),
+        ms_of_day: time_ms.rem_euclid(MS_PER_DAY),
+        secs,
+        nsecs,
+        plausible: (PLAUSIBLE_MIN_MS..=PLAUSIBLE_MAX_MS).contains(&time_ms),
+        as_secs: as_unit(time_ms.checked_mul(1_000)),
+        as_micros: as_unit(Some(time_ms.div_euclid(1_000))),
+        as_nanos: as_unit(Some(time_ms.div_euclid(1_000_000))),
+    }
+}
+
+impl fmt::Display for TimeMsExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_out_of_range = |s: &Option<String>| s.clone().unwrap_or("out of range".to_string());
+
+        writeln!(f, "time_ms:     {}", self.time_ms)?;
+        writeln!(f, "utc:         {}", self.utc)?;
+        writeln!(f, "local:       {}", self.local)?;
+        writeln!(
+            f,
+            "date:        {:04}-{:02}-{:02} {}",
+            self.year, self.month, self.day, self.weekday
+        )?;
+        writeln!(f, "day of year: {}", self.ordinal)?;
+        writeln!(
+            f,
+            "iso week:    {}-W{:02}",
+            self.iso_week.0, self.iso_week.1
+        )?;
+        writeln!(f, "ms of day:   {}", self.ms_of_day)?;
+        writeln!(f, "secs nsecs:  {} {}", self.secs, self.nsecs)?;
+        writeln!(f, "plausible:   {}", self.plausible)?;
+        writeln!(f, "as secs:     {}", or_out_of_range(&self.as_secs))?;
+        writeln!(f, "as micros:   {}", or_out_of_range(&self.as_micros))?;
+        write!(f, "as nanos:    {}", or_out_of_range(&self.as_nanos))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_explain_time_ms() {
+        let e = explain_time_ms(1_657_886_400_123);
+        dbg!(&e);
+        assert_eq!(e.utc, "2022-07-15T12:00:00.123+00:00");
+        assert_eq!((e.year, e.month, e.day), (2022, 7, 15));
+        assert_eq!(e.weekday, Weekday::Fri);
+        assert_eq!(e.ordinal, 196);
+        assert_eq!(e.iso_week, (2022, 28));
+        assert_eq!(e.ms_of_day, 43_200_123);
+        assert_eq!((e.secs, e.nsecs), (1_657_886_400, 123_000_000));
+        assert!(e.plausible);
+        assert_eq!(
+            e.as_secs.as_deref(),
+            Some("+54506-05-01T00:02:03.000+00:00")
+        );
+        assert_eq!(
+            e.as_micros.as_deref(),
+            Some("1970-01-20T04:31:26.400+00:00")
+        );
+        assert_eq!(e.as_nanos.as_deref(), Some("1970-01-01T00:27:37.886+00:00"));
+
+        // The local line depends on the time zone of the machine
+        let expected = format!(
+            "time_ms:     1657886400123
+utc:         2022-07-15T12:00:00.123+00:00
+local:       {}
+date:        2022-07-15 Fri
+day of year: 196
+iso week:    2022-W28
+ms of day:   43200123
+secs nsecs:  1657886400 123000000
+plausible:   true
+as secs:     +54506-05-01T00:02:03.000+00:00
+as micros:   1970-01-20T04:31:26.400+00:00
+as nanos:    1970-01-01T00:27:37.886+00:00",
+            e.local
+        );
+        assert_eq!(e.to_string(), expected);
+
+        // As seconds this is beyond the ~262,000 years chrono supports
+        let e = explain_time_ms(10_000_000_000_000);
+        assert_eq!(e.as_secs, None);
+        assert!(e.to_string().contains("as secs:     out of range"));
+    }
+
+    #[test]
+    fn test_explain_time_ms_negative() {
+        let e = explain_time_ms(-1);
+        dbg!(&e);
+        assert_eq!(e.utc, "1969-12-31T23:59:59.999+00:00");
+        assert_eq!(e.ms_of_day, MS_PER_DAY - 1);
+        assert!(!e.plausible);
+
+        let expected = format!(
+            "time_ms:     -1
+utc:         1969-12-31T23:59:59.999+00:00
+local:       {}
+date:        1969-12-31 Wed
+day of year: 365
+iso week:    1970-W01
+ms of day:   86399999
+secs nsecs:  -1 999000000
+plausible:   false
+as secs:     1969-12-31T23:59:59.000+00:00
+as micros:   1969-12-31T23:59:59.999+00:00
+as nanos:    1969-12-31T23:59:59.999+00:00",
+            e.local
+        );
+        assert_eq!(e.to_string(), expected);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::time_ms_to_utc_z_string;
+
+/// A bounded cache of [`time_ms_to_utc_z_string`] strings, for when the
+/// same time_ms are formatted many times, such as candle boundaries in
+/// a report.
+///
+/// When the cache is full the least recently used string is evicted. The
+/// eviction scans the cache so it's meant for small capacities, tens to
+/// a few hundred entries.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::TimeMsFormatCache;
+///
+/// let mut cache = TimeMsFormatCache::new(16);
+/// assert_eq!(cache.get_utc_z(0), "1970-01-01T00:00:00.000Z");
+/// assert_eq!(cache.get_utc_z(0), "1970-01-01T00:00:00.000Z");
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimeMsFormatCache {
+    capacity: usize,
+
+    // time_ms to its string and when it was last used
+    entries: HashMap<i64, (String, u64)>,
+    uses: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl TimeMsFormatCache {
+    /// A cache holding at most `capacity` strings
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "TimeMsFormatCache::new: capacity must be > 0");
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            uses: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The [`time_ms_to_utc_z_string`] of time_ms, formatted on a miss
+    ///
+    /// # Panics
+    ///
+    /// If time_ms is out of the range of DateTime<Utc>
+    pub fn get_utc_z(&mut self, time_ms: i64) -> &str {
+        self.uses += 1;
+        if self.entries.contains_key(&time_ms) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries
+                .insert(time_ms, (time_ms_to_utc_z_string(time_ms), 0));
+        }
+
+        let (s, last_used) = self.entries.get_mut(&time_ms).expect("just inserted");
+        *last_used = self.uses;
+        s
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let lru = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(&time_ms, _)| time_ms);
+        if let Some(time_ms) = lru {
+            self.entries.remove(&time_ms);
+        }
+    }
+
+    /// The maximum number of strings cached
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of strings cached
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if no strings are cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of [`get_utc_z`](Self::get_utc_z) calls that were cached
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of [`get_utc_z`](Self::get_utc_z) calls that formatted
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+/// A [`TimeMsFormatCache`] behind a Mutex so it can be shared between
+/// threads, such as in an `Arc` or a `static`
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use time_ms_conversions::SharedTimeMsFormatCache;
+///
+/// let cache = Arc::new(SharedTimeMsFormatCache::new(16));
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let cache = Arc::clone(&cache);
+///         std::thread::spawn(move || cache.get_utc_z(1_657_886_400_123))
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), "2022-07-15T12:00:00.123Z");
+/// }
+/// assert_eq!(cache.misses(), 1);
+/// ```
+#[derive(Debug)]
+pub struct SharedTimeMsFormatCache {
+    cache: Mutex<TimeMsFormatCache>,
+}
+
+impl SharedTimeMsFormatCache {
+    /// A shared cache holding at most `capacity` strings
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(TimeMsFormatCache::new(capacity)),
+        }
+    }
+
+    // The cache is always consistent so a panic while it was locked
+    // doesn't make it unusable.
+    fn lock(&self) -> MutexGuard<'_, TimeMsFormatCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A copy of the [`time_ms_to_utc_z_string`] of time_ms, see
+    /// [`with_utc_z`](Self::with_utc_z) to avoid the copy
+    ///
+    /// # Panics
+    ///
+    /// If time_ms is out of the range of DateTime<Utc>
+    pub fn get_utc_z(&self, time_ms: i64) -> String {
+        self.with_utc_z(time_ms, str::to_string)
+    }
+
+    /// Call `f` with the [`time_ms_to_utc_z_string`] of time_ms while the
+    /// cache is locked
+    ///
+    /// # Panics
+    ///
+    /// If time_ms is out of the range of DateTime<Utc>
+    pub fn with_utc_z<R>(&self, time_ms: i64, f: impl FnOnce(&str) -> R) -> R {
+        f(self.lock().get_utc_z(time_ms))
+    }
+
+    /// See [`TimeMsFormatCache::hits`]
+    pub fn hits(&self) -> u64 {
+        self.lock().hits()
+    }
+
+    /// See [`TimeMsFormatCache::misses`]
+    pub fn misses(&self) -> u64 {
+        self.lock().misses()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_ms_format_cache_matches_direct_formatting() {
+        let mut cache = TimeMsFormatCache::new(4);
+        for tms in [0, -1, 1_657_886_400_123, 0, -1, 253_402_300_799_999] {
+            assert_eq!(cache.get_utc_z(tms), time_ms_to_utc_z_string(tms));
+        }
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn test_time_ms_format_cache_counters() {
+        let mut cache = TimeMsFormatCache::new(8);
+        assert!(cache.is_empty());
+        for _ in 0..10 {
+            for tms in [0, 1000, 2000] {
+                cache.get_utc_z(tms);
+            }
+        }
+        assert_eq!(cache.misses(), 3);
+        assert_eq!(cache.hits(), 27);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.capacity(), 8);
+    }
+
+    #[test]
+    fn test_time_ms_format_cache_eviction() {
+        let mut cache = TimeMsFormatCache::new(2);
+        cache.get_utc_z(0);
+        cache.get_utc_z(1000);
+
+        // 0 is more recently used so 1000 is evicted
+        cache.get_utc_z(0);
+        cache.get_utc_z(2000);
+        assert_eq!(cache.len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        cache.get_utc_z(0);
+        assert_eq!((cache.hits(), cache.misses()), (2, 3));
+        cache.get_utc_z(1000);
+        assert_eq!((cache.hits(), cache.misses()), (2, 4));
+
+        // 2000 was least recently used
+        cache.get_utc_z(0);
+        cache.get_utc_z(2000);
+        assert_eq!((cache.hits(), cache.misses()), (3, 5));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be > 0")]
+    fn test_time_ms_format_cache_zero_capacity() {
+        TimeMsFormatCache::new(0);
+    }
+
+    #[test]
+    fn test_shared_time_ms_format_cache() {
+        let cache = SharedTimeMsFormatCache::new(4);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for tms in [0, 1000, 2000] {
+                        assert_eq!(cache.get_utc_z(tms), time_ms_to_utc_z_string(tms));
+                    }
+                });
+            }
+        });
+        assert_eq!(cache.misses(), 3);
+        assert_eq!(cache.hits(), 9);
+        assert_eq!(cache.with_utc_z(-1, str::len), 24);
+    }
+}
@@ -0,0 +1,127 @@
+use chrono::NaiveDateTime;
+
+use crate::{time_ms_to_utc, TimeMsError};
+
+// The three formats of RFC 7231 section 7.1.1.1, all in GMT
+const IMF_FIXDATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+const RFC_850: &str = "%A, %d-%b-%y %H:%M:%S GMT";
+const ASCTIME: &str = "%a %b %e %H:%M:%S %Y";
+
+/// HTTP-date string converted to utc time_ms
+///
+/// All three RFC 7231 formats are accepted, the preferred IMF-fixdate
+/// "Sun, 06 Nov 1994 08:49:37 GMT", the obsolete RFC 850
+/// "Sunday, 06-Nov-94 08:49:37 GMT" and asctime "Sun Nov  6 08:49:37 1994".
+/// The two digit RFC 850 year is 19xx for 69 through 99 and 20xx otherwise.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::http_date_str_to_utc_time_ms;
+///
+/// let tms = http_date_str_to_utc_time_ms("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+/// assert_eq!(tms, 784_111_777_000);
+/// assert_eq!(http_date_str_to_utc_time_ms("Sunday, 06-Nov-94 08:49:37 GMT").unwrap(), tms);
+/// assert_eq!(http_date_str_to_utc_time_ms("Sun Nov  6 08:49:37 1994").unwrap(), tms);
+/// ```
+pub fn http_date_str_to_utc_time_ms(s: &str) -> Result<i64, TimeMsError> {
+    let trimmed = s.trim();
+
+    let mut last_err = None;
+    for fmt in [IMF_FIXDATE, RFC_850, ASCTIME] {
+        match NaiveDateTime::parse_from_str(trimmed, fmt) {
+            Ok(ndt) => return Ok(ndt.and_utc().timestamp_millis()),
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(TimeMsError::parse(
+        s,
+        last_err.expect("At least one format was tried"),
+    ))
+}
+
+/// Convert time_ms to an IMF-fixdate HTTP-date, "Sun, 06 Nov 1994 08:49:37 GMT"
+///
+/// The output is always GMT with a two digit day so it can be used directly
+/// as an HTTP header value. HTTP-dates have no fractional seconds so the
+/// milli-seconds are dropped, flooring the time.
+///
+/// # Examples
+/// ```
+/// use time_ms_conversions::time_ms_to_http_date_string;
+///
+/// assert_eq!(time_ms_to_http_date_string(784_111_777_999), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// ```
+pub fn time_ms_to_http_date_string(time_ms: i64) -> String {
+    time_ms_to_utc(time_ms).format(IMF_FIXDATE).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 1994-11-06T08:49:37Z
+    const EXPECTED: i64 = 784_111_777_000;
+
+    #[test]
+    fn test_http_date_imf_fixdate() {
+        let tms = http_date_str_to_utc_time_ms("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(tms, EXPECTED);
+    }
+
+    #[test]
+    fn test_http_date_rfc_850() {
+        let tms = http_date_str_to_utc_time_ms("Sunday, 06-Nov-94 08:49:37 GMT").unwrap();
+        assert_eq!(tms, EXPECTED);
+    }
+
+    #[test]
+    fn test_http_date_asctime() {
+        let tms = http_date_str_to_utc_time_ms("Sun Nov  6 08:49:37 1994").unwrap();
+        assert_eq!(tms, EXPECTED);
+        let tms = http_date_str_to_utc_time_ms("Sat Nov 26 08:49:37 1994").unwrap();
+        assert_eq!(tms, EXPECTED + 20 * 24 * 60 * 60 * 1000);
+    }
+
+    #[test]
+    fn test_http_date_errors() {
+        for s in [
+            "",
+            "1994-11-06T08:49:37Z",
+            "Sun, 06 Nov 1994 08:49:37 +0000",
+            "Mon, 06 Nov 1994 08:49:37 GMT", // Wrong day of week
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+        ] {
+            dbg!(s);
+            assert!(matches!(
+                http_date_str_to_utc_time_ms(s),
+                Err(TimeMsError::Parse { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_time_ms_to_http_date_string() {
+        assert_eq!(
+            time_ms_to_http_date_string(EXPECTED),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            time_ms_to_http_date_string(0),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+        assert_eq!(
+            time_ms_to_http_date_string(-1),
+            "Wed, 31 Dec 1969 23:59:59 GMT"
+        );
+    }
+
+    #[test]
+    fn test_http_date_round_trip() {
+        for tms in [0, -1000, EXPECTED, 1_057_049_557_000, 253_402_300_799_000] {
+            let s = time_ms_to_http_date_string(tms);
+            dbg!(&s);
+            assert_eq!(http_date_str_to_utc_time_ms(&s), Ok(tms));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::TimeMsError;
+
+/// The largest time_ms a JavaScript number, an IEEE-754 double, represents
+/// exactly along with its neighbors, `Number.MAX_SAFE_INTEGER`, 2^53 - 1.
+/// The smallest is its negation.
+pub const JS_MAX_SAFE_TIME_MS: i64 = (1 << 53) - 1;
+
+/// Convert time_ms to a JavaScript number
+///
+/// Returns `TimeMsError::OutOfRange` if time_ms is beyond
+/// +/-[`JS_MAX_SAFE_TIME_MS`] where it wouldn't survive the round trip.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{to_js_safe_ms, TimeMsError, JS_MAX_SAFE_TIME_MS};
+///
+/// assert_eq!(to_js_safe_ms(1_657_886_400_123), Ok(1_657_886_400_123.0));
+/// assert_eq!(to_js_safe_ms(JS_MAX_SAFE_TIME_MS + 1), Err(TimeMsError::OutOfRange));
+/// ```
+pub fn to_js_safe_ms(time_ms: i64) -> Result<f64, TimeMsError> {
+    if (-JS_MAX_SAFE_TIME_MS..=JS_MAX_SAFE_TIME_MS).contains(&time_ms) {
+        Ok(time_ms as f64)
+    } else {
+        Err(TimeMsError::OutOfRange)
+    }
+}
+
+/// Convert a JavaScript number to time_ms, the inverse of [`to_js_safe_ms`]
+///
+/// A fractional value, such as the sum or mean of numbers, is rounded to
+/// the nearest milli-second with halves rounded up. Returns
+/// `TimeMsError::OutOfRange` if `value` isn't finite or the rounded value
+/// is beyond +/-[`JS_MAX_SAFE_TIME_MS`] and may have lost precision.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{from_js_ms, TimeMsError};
+///
+/// assert_eq!(from_js_ms(1_657_886_400_123.0), Ok(1_657_886_400_123));
+/// assert_eq!(from_js_ms(-1.6), Ok(-2));
+/// assert_eq!(from_js_ms(f64::NAN), Err(TimeMsError::OutOfRange));
+/// ```
+pub fn from_js_ms(value: f64) -> Result<i64, TimeMsError> {
+    // Only values below 2^52 have a fraction, adding 0.5 to larger
+    // values could round them up.
+    let ms = if value.fract() == 0.0 {
+        value
+    } else {
+        (value + 0.5).floor()
+    };
+
+    if !ms.is_finite() || ms.abs() > JS_MAX_SAFE_TIME_MS as f64 {
+        return Err(TimeMsError::OutOfRange);
+    }
+
+    Ok(ms as i64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TWO_POW_53: i64 = 1 << 53;
+
+    #[test]
+    fn test_to_js_safe_ms() {
+        for tms in [0, -1, 1_657_886_400_123, TWO_POW_53 - 1, -(TWO_POW_53 - 1)] {
+            let value = to_js_safe_ms(tms).unwrap();
+            assert_eq!(value as i64, tms);
+            assert_eq!(from_js_ms(value), Ok(tms));
+        }
+
+        for tms in [
+            TWO_POW_53,
+            TWO_POW_53 + 1,
+            -TWO_POW_53,
+            -TWO_POW_53 - 1,
+            i64::MAX,
+            i64::MIN,
+        ] {
+            assert_eq!(to_js_safe_ms(tms), Err(TimeMsError::OutOfRange), "{tms}");
+        }
+    }
+
+    #[test]
+    fn test_from_js_ms_limits() {
+        assert_eq!(JS_MAX_SAFE_TIME_MS, 9_007_199_254_740_991);
+        assert_eq!(from_js_ms((TWO_POW_53 - 1) as f64), Ok(TWO_POW_53 - 1));
+        assert_eq!(from_js_ms(-(TWO_POW_53 - 1) as f64), Ok(-(TWO_POW_53 - 1)));
+
+        // 2^53 + 1 isn't representable, it's 2^53 as a double
+        assert_eq!(from_js_ms(TWO_POW_53 as f64), Err(TimeMsError::OutOfRange));
+        assert_eq!(
+            from_js_ms((TWO_POW_53 + 1) as f64),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(
+            from_js_ms(-(TWO_POW_53 + 1) as f64),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(from_js_ms(1e300), Err(TimeMsError::OutOfRange));
+    }
+
+    #[test]
+    fn test_from_js_ms_fractional() {
+        assert_eq!(from_js_ms(1000.4), Ok(1000));
+        assert_eq!(from_js_ms(1000.6), Ok(1001));
+        assert_eq!(from_js_ms(1000.5), Ok(1001));
+        assert_eq!(from_js_ms(-1000.4), Ok(-1000));
+        assert_eq!(from_js_ms(-1000.6), Ok(-1001));
+        assert_eq!(from_js_ms(-1000.5), Ok(-1000));
+        assert_eq!(from_js_ms(-0.0), Ok(0));
+    }
+
+    #[test]
+    fn test_from_js_ms_not_finite() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(from_js_ms(value), Err(TimeMsError::OutOfRange), "{value}");
+        }
+    }
+}
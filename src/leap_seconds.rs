@@ -0,0 +1,161 @@
+use crate::MS_PER_SEC;
+
+// The time_ms of the UTC midnight following each positive leap second,
+// 23:59:60 is inserted just before it. The last was at the end of 2016
+// and none have been announced since.
+const LEAP_SECONDS: [i64; 27] = [
+    78_796_800_000,    // 1972-06-30
+    94_694_400_000,    // 1972-12-31
+    126_230_400_000,   // 1973-12-31
+    157_766_400_000,   // 1974-12-31
+    189_302_400_000,   // 1975-12-31
+    220_924_800_000,   // 1976-12-31
+    252_460_800_000,   // 1977-12-31
+    283_996_800_000,   // 1978-12-31
+    315_532_800_000,   // 1979-12-31
+    362_793_600_000,   // 1981-06-30
+    394_329_600_000,   // 1982-06-30
+    425_865_600_000,   // 1983-06-30
+    489_024_000_000,   // 1985-06-30
+    567_993_600_000,   // 1987-12-31
+    631_152_000_000,   // 1989-12-31
+    662_688_000_000,   // 1990-12-31
+    709_948_800_000,   // 1992-06-30
+    741_484_800_000,   // 1993-06-30
+    773_020_800_000,   // 1994-06-30
+    820_454_400_000,   // 1995-12-31
+    867_715_200_000,   // 1997-06-30
+    915_148_800_000,   // 1998-12-31
+    1_136_073_600_000, // 2005-12-31
+    1_230_768_000_000, // 2008-12-31
+    1_341_100_800_000, // 2012-06-30
+    1_435_708_800_000, // 2015-06-30
+    1_483_228_800_000, // 2016-12-31
+];
+
+// The number of leap seconds inserted at or before time_ms
+fn leap_seconds_at(time_ms: i64) -> i64 {
+    LEAP_SECONDS.partition_point(|&leap_ms| leap_ms <= time_ms) as i64
+}
+
+/// The number of leap seconds inserted between `start_ms` and `end_ms`,
+/// negative if `end_ms < start_ms`.
+///
+/// A leap second is inserted just before the UTC midnight following it,
+/// so it's counted if that midnight is in `(start_ms, end_ms]`. time_ms
+/// has no leap seconds so a time during one, 23:59:60, is the following
+/// midnight. The embedded table ends with the leap second at the end of
+/// 2016, the last one as of this writing.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::leap_seconds_between;
+///
+/// // 2016-12-31T23:59:59Z to 2017-01-01T00:00:00Z
+/// assert_eq!(leap_seconds_between(1_483_228_799_000, 1_483_228_800_000), 1);
+/// assert_eq!(leap_seconds_between(0, 1_483_228_800_000), 27);
+/// assert_eq!(leap_seconds_between(1_483_228_800_000, 0), -27);
+/// ```
+pub fn leap_seconds_between(start_ms: i64, end_ms: i64) -> i64 {
+    leap_seconds_at(end_ms) - leap_seconds_at(start_ms)
+}
+
+/// The elapsed SI milli-seconds from `start_ms` to `end_ms`, the UTC
+/// difference plus a second for each leap second inserted between them,
+/// see [`leap_seconds_between`]. Negative if `end_ms < start_ms` and
+/// saturating at i64::MIN and i64::MAX.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::true_elapsed_ms;
+///
+/// // 2016-12-31T23:59:59Z to 2017-01-01T00:00:01Z is 3 seconds
+/// assert_eq!(true_elapsed_ms(1_483_228_799_000, 1_483_228_801_000), 3_000);
+/// assert_eq!(true_elapsed_ms(0, 1_000), 1_000);
+/// ```
+pub fn true_elapsed_ms(start_ms: i64, end_ms: i64) -> i64 {
+    // i128 as the difference can overflow an i64
+    let elapsed = end_ms as i128 - start_ms as i128
+        + leap_seconds_between(start_ms, end_ms) as i128 * MS_PER_SEC as i128;
+
+    elapsed.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2017-01-01T00:00:00Z, just after the last leap second
+    const NEW_YEAR_2017: i64 = 1_483_228_800_000;
+
+    #[test]
+    fn test_leap_seconds_table() {
+        // Sorted and each is a UTC midnight on January 1st or July 1st
+        for pair in LEAP_SECONDS.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        for leap_ms in LEAP_SECONDS {
+            assert_eq!(leap_ms % 86_400_000, 0);
+        }
+        assert_eq!(LEAP_SECONDS[LEAP_SECONDS.len() - 1], NEW_YEAR_2017);
+    }
+
+    #[test]
+    fn test_leap_seconds_between() {
+        let cases = [
+            // start_ms, end_ms, expected
+            (NEW_YEAR_2017 - 1, NEW_YEAR_2017, 1),
+            (NEW_YEAR_2017 - 1, NEW_YEAR_2017 - 1, 0),
+            (NEW_YEAR_2017, NEW_YEAR_2017 + 1, 0),
+            (NEW_YEAR_2017, NEW_YEAR_2017, 0),
+            (NEW_YEAR_2017, NEW_YEAR_2017 - 1, -1),
+            (0, NEW_YEAR_2017, 27),
+            (i64::MIN, i64::MAX, 27),
+            (i64::MAX, i64::MIN, -27),
+            (0, LEAP_SECONDS[0] - 1, 0),
+            // 1999 through 2005 had none
+            (946_684_800_000, 1_104_537_600_000, 0),
+            // Before the first and after the last
+            (-1_000_000_000_000, 0, 0),
+            (NEW_YEAR_2017, 4_102_444_800_000, 0),
+        ];
+        for (start_ms, end_ms, expected) in cases {
+            assert_eq!(
+                leap_seconds_between(start_ms, end_ms),
+                expected,
+                "{start_ms} {end_ms}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_true_elapsed_ms() {
+        // Spanning one leap second is 1000ms longer than the naive difference
+        let start_ms = NEW_YEAR_2017 - 3_600_000;
+        let end_ms = NEW_YEAR_2017 + 3_600_000;
+        assert_eq!(true_elapsed_ms(start_ms, end_ms), end_ms - start_ms + 1_000);
+        assert_eq!(true_elapsed_ms(end_ms, start_ms), start_ms - end_ms - 1_000);
+
+        // None is the naive difference
+        assert_eq!(
+            true_elapsed_ms(NEW_YEAR_2017, NEW_YEAR_2017 + 3_600_000),
+            3_600_000
+        );
+        assert_eq!(true_elapsed_ms(-1_000, 1_000), 2_000);
+
+        // 2016-12-31T23:59:59.500Z to 2017-01-01T00:00:00.500Z, the leap
+        // second is in the middle
+        assert_eq!(
+            true_elapsed_ms(NEW_YEAR_2017 - 500, NEW_YEAR_2017 + 500),
+            2_000
+        );
+    }
+
+    #[test]
+    fn test_true_elapsed_ms_limits() {
+        assert_eq!(true_elapsed_ms(i64::MIN, i64::MAX), i64::MAX);
+        assert_eq!(true_elapsed_ms(i64::MAX, i64::MIN), i64::MIN);
+        assert_eq!(true_elapsed_ms(0, i64::MAX - 27_000), i64::MAX);
+        assert_eq!(true_elapsed_ms(0, i64::MAX - 27_001), i64::MAX - 1);
+    }
+}
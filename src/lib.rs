@@ -1,10 +1,270 @@
-use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+//! Various routines to convert time_ms, milli-seconds since the Unix
+//! epoch 1970-01-01T00:00:00Z, to and from UTC.
+//!
+//! # Features
+//!
+//! - `std` (default): everything that needs `std` or `chrono`, implies
+//!   `alloc`. Without it the crate is `no_std` and doesn't depend on chrono.
+//! - `alloc`: the string builders that only need an allocator,
+//!   `delta_of_delta_encode` and `delta_of_delta_decode`,
+//!   `format_duration_ms`, `parse_duration_ms`, `time_ms_slice_to_utc_strings` and
+//!   `time_ms_slice_to_utc_strings_into`, and the string carrying
+//!   [`TimeMsError`] variants.
+//! - `time-crate`: conversions to and from the `time` crate's
+//!   `OffsetDateTime`. It doesn't imply `std`, so with
+//!   `default-features = false` chrono isn't a dependency.
+//! - `time-backend`: an alias for `time-crate`.
+//! - `serde`: the `serde` module to (de)serialize
+//!   `DateTime<Utc>` as time_ms and time_ms as RFC 3339 strings, and
+//!   `Serialize` and `Deserialize` for [`TimeShift`], implies `std`.
+//!
+//! Without `std` or `alloc` the integer arithmetic remains, such as
+//! [`time_ms_to_secs_nsecs`], [`secs_nsecs_to_time_ms`],
+//! [`time_ms_to_ticks`], [`coarsen`], [`breakdown_duration_ms`] and
+//! [`sanity_fix_time_ms`].
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod bucket;
+#[cfg(feature = "std")]
+mod business;
+#[cfg(feature = "std")]
+mod calendar;
+mod const_parse;
+#[cfg(feature = "std")]
+mod conversions;
+#[cfg(feature = "alloc")]
+mod delta;
+#[cfg(feature = "std")]
+mod detect;
+mod duration;
+mod epoch;
+mod error;
+#[cfg(feature = "std")]
+mod excel;
+#[cfg(feature = "std")]
+mod explain;
+#[cfg(feature = "std")]
+mod format_cache;
+#[cfg(feature = "std")]
+mod http_date;
+#[cfg(feature = "std")]
+mod js;
+mod leap_seconds;
+#[cfg(feature = "std")]
+mod plot;
+#[cfg(feature = "std")]
+mod range;
+#[cfg(feature = "std")]
+mod recurrence;
+#[cfg(feature = "std")]
+mod resolved;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "std")]
+mod series;
+#[cfg(feature = "std")]
+mod shift;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod system_time;
+#[cfg(feature = "time-crate")]
+mod time_crate;
+mod time_ms;
+mod time_ns;
+mod time_s;
+mod time_us;
+#[cfg(feature = "std")]
+mod trace;
+#[cfg(feature = "alloc")]
+mod utc_strings;
+mod validity;
+
+pub use bucket::{
+    align_time_ms, ceil_time_ms_to, coarsen, coarsen_slice, floor_time_ms_to,
+    is_aligned_to_interval, misalignment_ms, phase_offset_ms, ticks_to_time_ms, time_ms_to_ticks,
+    truncate_time_ms_to_day, truncate_time_ms_to_hour, truncate_time_ms_to_minute, TimeUnit,
+};
+#[cfg(feature = "alloc")]
+pub use bucket::{boundaries_crossed, busiest_bucket};
+#[cfg(feature = "std")]
+pub use business::{
+    add_business_days, business_ms_between, business_time_of_day_utc, ms_in_daily_window,
+    schedule_coverage_fraction, session_aligned_bucket_start,
+};
+#[cfg(feature = "std")]
+pub use calendar::{
+    distinct_dates_utc, parse_and_verify_weekday, time_ms_at_fraction_of_month_utc,
+    time_ms_components_utc, time_ms_days_in_month, time_ms_from_components_utc,
+    time_ms_is_leap_year, time_ms_part_of_day_local, time_ms_part_of_day_utc, time_ms_to_iso_week,
+    time_ms_to_naive_date, time_ms_to_ordinal, time_ms_weekday, truncate_time_ms_to_month,
+    year_fraction_to_time_ms, DateTimeComponents, PartOfDay,
+};
+pub use const_parse::canonical_utc_str_to_time_ms;
+#[cfg(feature = "std")]
+pub use conversions::{
+    compact_numeric_to_time_ms, dt_str_to_utc_time_ms, dt_str_to_utc_time_ms_traced,
+    dt_str_to_utc_time_ms_with_offset, dt_strs_to_utc_time_ms,
+    dt_strs_with_separator_to_utc_time_ms, fixed_offset_to_time_ms, local_formatting_is_ambiguous,
+    parse_time_ms_list, rfc2822_str_to_utc_time_ms, rfc3339_require_utc_to_time_ms,
+    time_ms_from_utc_parts, time_ms_to_fixed_offset, time_ms_to_fixed_offset_string,
+    time_ms_to_local, time_ms_to_local_string, time_ms_to_rfc2822_string,
+    time_ms_to_string_with_fmt, time_ms_to_string_with_fmt_and_offset, time_ms_to_utc,
+    time_ms_to_utc_custom_fmt, time_ms_to_utc_string, time_ms_to_utc_string_opts,
+    time_ms_to_utc_z_string, time_str_to_utc_time_ms, try_time_ms_to_utc, utc_now_to_time_ms,
+    utc_to_time_ms, utc_to_time_ms_rounded, DtSeparator, TzMassaging,
+};
+#[cfg(feature = "alloc")]
+pub use delta::{delta_of_delta_decode, delta_of_delta_encode};
+#[cfg(feature = "std")]
+pub use detect::{detect_time_format, parse_any_to_time_ms, parse_heterogeneous, TimeFormat};
+pub use duration::{breakdown_duration_ms, DurationParts};
+#[cfg(feature = "alloc")]
+pub use duration::{format_duration_ms, parse_duration_ms};
+pub use epoch::{
+    detect_epoch_unit, epoch_any_to_time_ms, epoch_to_time_ms, sanity_fix_time_ms, EpochUnit,
+};
+pub use error::{TimeConversionError, TimeMsError};
+#[cfg(feature = "std")]
+pub use excel::{excel_local_str_to_time_ms, time_ms_to_excel_local_str, DateOrder};
+#[cfg(feature = "std")]
+pub use explain::{explain_time_ms, TimeMsExplanation};
+#[cfg(feature = "std")]
+pub use format_cache::{SharedTimeMsFormatCache, TimeMsFormatCache};
+#[cfg(feature = "std")]
+pub use http_date::{http_date_str_to_utc_time_ms, time_ms_to_http_date_string};
+#[cfg(feature = "std")]
+pub use js::{from_js_ms, to_js_safe_ms, JS_MAX_SAFE_TIME_MS};
+pub use leap_seconds::{leap_seconds_between, true_elapsed_ms};
+#[cfg(feature = "std")]
+pub use plot::{choose_plot_origin, from_plot_seconds, to_plot_seconds};
+#[cfg(feature = "std")]
+pub use range::{apportion, apportion_by_month, TimeMsRange, TimeMsRangeIndex};
+#[cfg(feature = "std")]
+pub use recurrence::{
+    jittered_schedule_ms, next_annual_occurrence, next_monthly_occurrence, next_weekday_time_utc,
+    LeapDayPolicy,
+};
+#[cfg(feature = "std")]
+pub use resolved::{resolve_dt_format, ResolvedDtFormat};
+#[cfg(feature = "std")]
+pub use series::{linspace_time_ms, time_ms_range, TimeMsSteps};
+#[cfg(feature = "std")]
+pub use shift::TimeShift;
+#[cfg(feature = "std")]
+pub use stats::{
+    detect_outliers, ema_smooth_time_ms, filter_outliers, fraction_in_window,
+    interpolate_between_edges, mean_time_ms, median_time_ms, weighted_mean_time_ms, OutlierMethod,
+    TimeMsStats,
+};
+#[cfg(feature = "std")]
+pub use system_time::{system_time_to_time_ms, time_ms_to_system_time};
+#[cfg(feature = "time-crate")]
+pub use time_crate::{offset_datetime_to_time_ms, time_ms_to_offset_datetime};
+pub use time_ms::TimeMs;
+pub use time_ns::{time_ms_to_time_ns, time_ns_to_time_ms};
+#[cfg(feature = "std")]
+pub use time_ns::{time_ns_to_utc, utc_to_time_ns};
+pub use time_s::{time_ms_to_time_s, time_s_to_time_ms};
+#[cfg(feature = "std")]
+pub use time_s::{time_s_to_utc_string, utc_now_to_time_s};
+#[cfg(feature = "std")]
+pub use time_us::{
+    dt_str_to_utc_time_us, time_us_to_utc, time_us_to_utc_string, utc_now_to_time_us,
+    utc_to_time_us,
+};
+pub use time_us::{time_ms_to_time_us, time_us_to_secs_nsecs, time_us_to_time_ms};
+#[cfg(feature = "std")]
+pub use trace::{ParseBranch, ParseTrace, ParseTraceStats};
+#[cfg(feature = "alloc")]
+pub use utc_strings::{time_ms_slice_to_utc_strings, time_ms_slice_to_utc_strings_into};
+pub use validity::{is_valid_at, SkewSide, ValidityCheck};
+
+pub(crate) const MS_PER_SEC: i64 = 1_000;
+pub(crate) const MS_PER_MIN: i64 = 60 * MS_PER_SEC;
+pub(crate) const MS_PER_HOUR: i64 = 60 * MS_PER_MIN;
+pub(crate) const MS_PER_DAY: i64 = 24 * MS_PER_HOUR;
+
+/// How sub milli-second values are rounded when converting to time_ms
+///
+/// The values in each variant are what 1.5ms and -1.5ms round to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to nearest, halves toward positive infinity, 2 and -1.
+    /// This is what `utc_to_time_ms` and the parsers use.
+    HalfUp,
+
+    /// Round toward zero, 1 and -1
+    Truncate,
+
+    /// Round to nearest, halves to the even value, 2 and -2
+    HalfEven,
+
+    /// Round toward positive infinity, 2 and -1
+    Ceil,
+
+    /// Round toward negative infinity, 1 and -2
+    Floor,
+}
+
+pub(crate) fn nanos_to_time_ms_rounded(nanos: i128, mode: RoundingMode) -> i64 {
+    const NANOS_PER_MS: i128 = 1_000_000;
+
+    // div_euclid/rem_euclid so floor is the base for negative values too
+    let floor = nanos.div_euclid(NANOS_PER_MS);
+    let rem = nanos.rem_euclid(NANOS_PER_MS);
+    let ms = match mode {
+        RoundingMode::Floor => floor,
+        RoundingMode::Ceil => floor + (rem > 0) as i128,
+        RoundingMode::Truncate => {
+            if nanos < 0 {
+                floor + (rem > 0) as i128
+            } else {
+                floor
+            }
+        }
+        RoundingMode::HalfUp => floor + (rem >= NANOS_PER_MS / 2) as i128,
+        RoundingMode::HalfEven => {
+            let half = NANOS_PER_MS / 2;
+            if rem > half || (rem == half && floor % 2 != 0) {
+                floor + 1
+            } else {
+                floor
+            }
+        }
+    };
 
-fn fo_to_time_ms(date_time: &DateTime<FixedOffset>) -> i64 {
-    (date_time.timestamp_nanos() + 500_000) / 1_000_000
+    ms as i64
 }
 
-fn time_ms_to_secs_nsecs(time_ms: i64) -> (i64, u32) {
+// SplitMix64, a small well mixed generator so a seed gives the same
+// result on every platform and release, also used by the tests
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Convert time_ms to seconds and nano-seconds
+///
+/// For negative times the seconds are negative but the nano-seconds are
+/// positive, so -1ms is `(-1, 999_000_000)`. This is pure integer
+/// arithmetic and is available without the `std` feature.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_secs_nsecs;
+///
+/// assert_eq!(time_ms_to_secs_nsecs(1001), (1, 1_000_000));
+/// assert_eq!(time_ms_to_secs_nsecs(-1), (-1, 999_000_000));
+/// ```
+pub fn time_ms_to_secs_nsecs(time_ms: i64) -> (i64, u32) {
     // println!("time_ms_to_secs_nsecs: + time_ms={}", time_ms);
     let mut secs = time_ms / 1000;
     let ms: u32 = if time_ms < 0 {
@@ -13,8 +273,8 @@ fn time_ms_to_secs_nsecs(time_ms: i64) -> (i64, u32) {
         // milli-seconds are positive. Thus -1ms is represented
         // in time as -1sec + 0.999ms. Sooooooo
 
-        // First negate then modulo 1000 to get millis as a u32
-        let mut millis = (-time_ms % 1_000) as u32;
+        // First modulo 1000 then negate, so i64::MIN can't overflow, to get millis as a u32
+        let mut millis = (-(time_ms % 1_000)) as u32;
 
         // This is very "likely" and it would be nice to be able
         // to tell the compiler with `if likely(millis > 0) {...}
@@ -48,222 +308,110 @@ fn time_ms_to_secs_nsecs(time_ms: i64) -> (i64, u32) {
     (secs, nsecs)
 }
 
-pub fn time_ms_to_utc_string(time_ms: i64) -> String {
-    time_ms_to_utc(time_ms).to_rfc3339_opts(SecondsFormat::Millis, false)
-}
-
-pub fn time_ms_to_utc_z_string(time_ms: i64) -> String {
-    time_ms_to_utc(time_ms).to_rfc3339_opts(SecondsFormat::Millis, true)
-}
-/// Get Utc::now() and convert to time_ms
+/// Convert seconds and nano-seconds, such as from `clock_gettime`, to time_ms
+///
+/// This is the inverse of the decomposition used by `time_ms_to_utc`, where
+/// negative times have negative seconds but positive nano-seconds, so
+/// `(-1, 999_000_000)` is -1ms. The nano-seconds are rounded to the nearest
+/// milli-second with halves rounded up and the result saturates at
+/// i64::MIN and i64::MAX.
 ///
 /// # Example
 /// ```
-/// use chrono::{DateTime, Utc};
-/// use time_ms_conversions::utc_now_to_time_ms;
-///
-/// let before: i64 = Utc::now().timestamp_nanos() / 1_000_000;
+/// use time_ms_conversions::secs_nsecs_to_time_ms;
 ///
-/// assert!(utc_now_to_time_ms() >= before);
+/// const TIME_MS: i64 = secs_nsecs_to_time_ms(-1, 999_000_000);
+/// assert_eq!(TIME_MS, -1);
+/// assert_eq!(secs_nsecs_to_time_ms(1, 2_500_000), 1003);
 /// ```
-pub fn utc_now_to_time_ms() -> i64 {
-    (Utc::now().timestamp_nanos() + 500_000) / 1_000_000
+pub const fn secs_nsecs_to_time_ms(secs: i64, nsecs: u32) -> i64 {
+    // i128 because secs * 1000 can be out of range while the sum isn't
+    let ms = secs as i128 * 1000 + (nsecs as i128 + 500_000) / 1_000_000;
+    if ms > i64::MAX as i128 {
+        i64::MAX
+    } else if ms < i64::MIN as i128 {
+        i64::MIN
+    } else {
+        ms as i64
+    }
 }
 
-/// Convert time_ms to DateTime<Utc>
+/// The whole epoch seconds of time_ms floored toward negative infinity,
+/// so -1ms is -1 seconds, the second it's in. This is consistent with
+/// `time_ms_to_secs_nsecs` and is the one to use for keys.
 ///
 /// # Example
 /// ```
-/// use chrono::{DateTime, Utc};
-/// use time_ms_conversions::{utc_to_time_ms, time_ms_to_utc};
+/// use time_ms_conversions::time_ms_to_epoch_seconds_floor;
 ///
-/// let epoch: DateTime<Utc> = time_ms_to_utc(0);
-/// assert_eq!(utc_to_time_ms(&epoch), 0);
+/// assert_eq!(time_ms_to_epoch_seconds_floor(1999), 1);
+/// assert_eq!(time_ms_to_epoch_seconds_floor(-1), -1);
 /// ```
-pub fn time_ms_to_utc(time_ms: i64) -> DateTime<Utc> {
-    let (secs, nsecs) = time_ms_to_secs_nsecs(time_ms);
-    let naive_datetime = NaiveDateTime::from_timestamp(secs, nsecs);
-    DateTime::from_utc(naive_datetime, Utc)
+pub const fn time_ms_to_epoch_seconds_floor(time_ms: i64) -> i64 {
+    time_ms.div_euclid(MS_PER_SEC)
 }
 
-/// Convert a DateTime<Utc> to time_ms
+/// The whole epoch seconds of time_ms truncated toward zero, so -1ms is
+/// 0 seconds. Unlike [`time_ms_to_epoch_seconds_floor`] the second
+/// before and after the epoch both map to 0.
 ///
-/// # Examples
+/// # Example
 /// ```
-/// use chrono::{DateTime, Utc};
-/// use time_ms_conversions::time_ms_to_utc;
+/// use time_ms_conversions::time_ms_to_epoch_seconds_trunc;
 ///
-/// let dt: DateTime<Utc> = time_ms_to_utc(0);
-/// assert_eq!(dt.to_string(), "1970-01-01 00:00:00 UTC");
+/// assert_eq!(time_ms_to_epoch_seconds_trunc(1999), 1);
+/// assert_eq!(time_ms_to_epoch_seconds_trunc(-1), 0);
 /// ```
-pub fn utc_to_time_ms(date_time: &DateTime<Utc>) -> i64 {
-    (date_time.timestamp_nanos() + 500_000) / 1_000_000
+pub const fn time_ms_to_epoch_seconds_trunc(time_ms: i64) -> i64 {
+    time_ms / MS_PER_SEC
 }
 
-pub enum TzMassaging {
-    CondAddTzUtc,
-    HasTz,
-    LocalTz,
-}
-
-/// DateTime string converted to utc time_ms with either T or Space seperator
+/// Convert epoch seconds to time_ms, saturating at i64::MIN and i64::MAX.
+/// This is the inverse of [`time_ms_to_epoch_seconds_floor`] for time_ms
+/// on a whole second.
 ///
-/// # Examples
+/// # Example
 /// ```
-/// use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
-/// use time_ms_conversions::{dt_str_to_utc_time_ms, TzMassaging};
-///
-/// // Might not have time zone and if not assume it's UTC
-/// let str_time_no_ms = "1970-01-01 00:00:00";
-/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-///     .expect("Bad time format");
-/// assert_eq!(ts, 0);
-///
-/// // If it does have UTC that's fine too
-/// let str_time_no_ms = "1970-01-01 00:00:00+00:00";
-/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-///     .expect("Bad time format");
-/// assert_eq!(ts, 0);
-///
-/// // If timezone is 'Z' for UTC that's fine
-/// let str_time_no_ms = "1970-01-01 00:00:00Z";
-/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-///     .expect("Bad time format");
-/// assert_eq!(ts, 0);
+/// use time_ms_conversions::{epoch_seconds_to_time_ms, time_ms_to_epoch_seconds_floor};
 ///
-/// // If timezone is 'Z' for UTC that's fine
-/// let str_time_no_ms = "1970-01-01T00:00:00Z";
-/// let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-///     .expect("Bad time format");
-/// assert_eq!(ts, 0);
-///
-/// // And CondAddTzUtz handles other time zones and converts them to Utc
-/// let str_time_pst = "1969-12-31T16:00:00-0800";
-/// let ts_utc = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::CondAddTzUtc)
-///     .expect("Bad time format with milliseconds");
-/// assert_eq!(ts_utc, 0);
-///
-/// // Assume there is a timezone, this is more efficient then CondAddTzUtc
-/// let str_time_tz = "1970-01-01 00:00:00+0000";
-/// let ts = dt_str_to_utc_time_ms(str_time_tz, TzMassaging::HasTz).expect("Bad time format");
-/// assert_eq!(ts, 0);
-///
-/// // UTC time_ms 0 as a Date Time string without time zone
-/// let utc_time_ms_0_str = "1970-01-01T00:00:00";
-///
-/// // Get a NaiveDateTime
-/// let ndt: NaiveDateTime = utc_time_ms_0_str.parse().unwrap();
+/// assert_eq!(epoch_seconds_to_time_ms(-1), -1000);
+/// assert_eq!(epoch_seconds_to_time_ms(time_ms_to_epoch_seconds_floor(-1500)), -2000);
+/// ```
+pub const fn epoch_seconds_to_time_ms(secs: i64) -> i64 {
+    secs.saturating_mul(MS_PER_SEC)
+}
+
+/// time_ms plus `delta_ms`, saturating at i64::MIN and i64::MAX rather
+/// than wrapping or panicking, for long horizon simulations.
 ///
-/// // Convert to the local time of where ever this is running
-/// let ldt = Local.from_local_datetime(&ndt).unwrap();
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_add_saturating;
 ///
-/// // Get the offset from local time to utc, a negative value for west of UTC
-/// // This will be -08:00 for PST
-/// let ldt_offset = ldt.offset();
-/// dbg!(ldt_offset);
+/// assert_eq!(time_ms_add_saturating(1_000, 234), 1_234);
+/// assert_eq!(time_ms_add_saturating(i64::MAX, 1), i64::MAX);
+/// ```
+pub const fn time_ms_add_saturating(time_ms: i64, delta_ms: i64) -> i64 {
+    time_ms.saturating_add(delta_ms)
+}
+
+/// time_ms minus `delta_ms`, saturating at i64::MIN and i64::MAX rather
+/// than wrapping or panicking.
 ///
-/// // Get the value to add to UTC to convert to local time
-/// // This will be -28800000 = (-8 * 60 * 60 * 1000) for PST
-/// let milli_seconds_to_add_to_convert_to_utc = ldt_offset.local_minus_utc() as i64 * 1000;
-/// dbg!(milli_seconds_to_add_to_convert_to_utc);
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_sub_saturating;
 ///
-/// // Adding milli_seconds_to_add_to_convert_to_utc to tms will equal 0 for all timezones
-/// // For PST tms is 28800000 and adding -28800000 is 0
-/// let tms = dt_str_to_utc_time_ms(utc_time_ms_0_str, TzMassaging::LocalTz)
-///     .expect("Bad time format");
-/// assert_eq!(tms + milli_seconds_to_add_to_convert_to_utc, 0);
+/// assert_eq!(time_ms_sub_saturating(1_234, 234), 1_000);
+/// assert_eq!(time_ms_sub_saturating(i64::MIN, 1), i64::MIN);
 /// ```
-pub fn dt_str_to_utc_time_ms(
-    dt_str: &str,
-    tz_massaging: TzMassaging,
-) -> Result<i64, Box<dyn std::error::Error>> {
-    pub fn dt_str_with_fmt_str_to_utc_time_ms(
-        dt_str: &str,
-        fmt_str: &str,
-        tz_massaging: TzMassaging,
-    ) -> Result<i64, Box<dyn std::error::Error>> {
-        let dt_str = dt_str.trim();
-        match tz_massaging {
-            TzMassaging::HasTz => {
-                let fs = format!("{fmt_str}%#z");
-                let dtfo = DateTime::parse_from_str(dt_str, &fs)?;
-                Ok(fo_to_time_ms(&dtfo))
-            }
-            TzMassaging::CondAddTzUtc => {
-                // Check if we have a Z|z timezone
-                let last_char = dt_str.chars().last();
-                if last_char == Some('Z') || last_char == Some('z') {
-                    let fs = format!("{fmt_str}%#z");
-                    let dtfo = DateTime::parse_from_str(dt_str, &fs)?;
-                    Ok(fo_to_time_ms(&dtfo))
-                } else {
-                    // No, see if there is a '+' then there "must be" a time zone
-                    let has_pos_tz = dt_str.matches('+').count() > 0;
-
-                    // If there is a '-' after the "year" then there must be a time zone
-                    let mut rmtchr = dt_str.rmatch_indices('-');
-                    let first_rmatch = rmtchr.next();
-                    let has_neg_tz = if let Some((idx, _s)) = first_rmatch {
-                        // If there is a '-' after index 7 then assume there is a negative time zone
-                        //     2020-01-01T...
-                        //     01234567
-                        idx > 7
-                    } else {
-                        // No numeric timezone
-                        false
-                    };
-
-                    let s = if !has_pos_tz && !has_neg_tz {
-                        // Add numeric timezone for UTC
-                        format!("{dt_str}+0000")
-                    } else {
-                        // Else there is one so just convert dt_str to String
-                        dt_str.to_string()
-                    };
-
-                    let fs = format!("{fmt_str}%#z");
-                    let dtfo = DateTime::parse_from_str(&s, &fs)?;
-                    Ok(fo_to_time_ms(&dtfo))
-                }
-            }
-            TzMassaging::LocalTz => {
-                // Convert datetime string to DateTime<Local>
-                // from: https://stackoverflow.com/questions/65820170/parsing-a-datetime-string-to-local-time-in-rust-chrono?rq=1
-                let ndt = NaiveDateTime::parse_from_str(dt_str, fmt_str)?;
-                let ldt = match Local.from_local_datetime(&ndt) {
-                    chrono::LocalResult::None => {
-                        return Err("No result".into());
-                    }
-                    chrono::LocalResult::Single(dt) => dt,
-                    chrono::LocalResult::Ambiguous(_, _) => {
-                        return Err("Ambigious result".into());
-                    }
-                };
-
-                // Convert from DateTime<Local> to DateTime<Utc> with timezone information
-                // from: https://stackoverflow.com/questions/56887881/how-do-i-convert-a-chrono-datetimelocal-instance-to-datetimeutc
-                let dt_utc = ldt.with_timezone(&Utc);
-
-                Ok(utc_to_time_ms(&dt_utc))
-            }
-        }
-    }
-
-    let tms = if dt_str.matches('T').count() == 1 {
-        dt_str_with_fmt_str_to_utc_time_ms(dt_str, "%Y-%m-%dT%H:%M:%S%.f", tz_massaging)?
-    } else {
-        dt_str_with_fmt_str_to_utc_time_ms(dt_str, "%Y-%m-%d %H:%M:%S%.f", tz_massaging)?
-    };
-
-    Ok(tms)
+pub const fn time_ms_sub_saturating(time_ms: i64, delta_ms: i64) -> i64 {
+    time_ms.saturating_sub(delta_ms)
 }
 
 #[cfg(test)]
 mod test {
-    use chrono::SecondsFormat;
-
     use super::*;
-    use std::time::Instant;
 
     #[test]
     fn test_time_ms_to_secs_nsecs() {
@@ -283,263 +431,147 @@ mod test {
     }
 
     #[test]
-    fn test_utc_now_to_time_ms() {
-        let start = Instant::now();
-
-        // Because we use integer arithmetic we must
-        // see 2 milli-second time ticks to see a minimum
-        // duration of > 1ms.
-        let tms1 = utc_now_to_time_ms();
-        let mut tms2 = tms1;
-        while tms2 < (tms1 + 2) {
-            tms2 = utc_now_to_time_ms();
-        }
-        let done = Instant::now();
-        let duration = done.duration_since(start);
-
-        println!(
-            "tms1: {} tms2: {} done: {:?} - start {:?} = {}ns or {}ms",
-            tms1,
-            tms2,
-            done,
-            start,
-            duration.as_nanos(),
-            duration.as_millis()
-        );
-
-        assert!(tms2 >= (tms1 + 2));
-        assert!(duration.as_millis() >= 1);
-
-        // The duration.as_millis should be < 2ms. But with Tarpaulin
-        // I've seen durations over 4ms so we skip this test.
-        // assert!(duration.as_millis() < 2);
+    fn test_secs_nsecs_to_time_ms() {
+        assert_eq!(secs_nsecs_to_time_ms(-3, 999_000_000), -2001);
+        assert_eq!(secs_nsecs_to_time_ms(-1, 999_000_000), -1);
+        assert_eq!(secs_nsecs_to_time_ms(-1, 0), -1000);
+        assert_eq!(secs_nsecs_to_time_ms(0, 0), 0);
+        assert_eq!(secs_nsecs_to_time_ms(0, 499_999), 0);
+        assert_eq!(secs_nsecs_to_time_ms(0, 500_000), 1);
+        assert_eq!(secs_nsecs_to_time_ms(-1, 999_500_000), 0);
+        assert_eq!(secs_nsecs_to_time_ms(0, 999_999_999), 1000);
+        assert_eq!(secs_nsecs_to_time_ms(i64::MAX, 0), i64::MAX);
+        assert_eq!(secs_nsecs_to_time_ms(i64::MIN, 0), i64::MIN);
     }
 
     #[test]
-    fn test_dt_str_with_tee_to_utc_time_ms() {
-        let str_time_no_ms = "1970-01-01T00:00:00";
-        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_with_ms = "1970-01-01T00:00:00.123";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-
-        let str_time_with_ms = "1970-01-01T00:00:00.123Z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds with Z");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-
-        let str_time_with_ms = "1970-01-01T00:00:00.123z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds with z");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-    }
-
-    #[test]
-    fn test_dt_str_with_space_to_utc_time_ms() {
-        let str_time_no_ms = "1970-01-01 00:00:00";
-        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_with_ms = "1970-01-01 00:00:00z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with seconds with z");
-        dbg!(tms);
-        assert_eq!(tms, 0);
-
-        let str_time_with_ms = "1970-01-01 00:00:00.123Z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-
-        let str_time_with_ms = "1970-01-01T00:00:00.123z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds and z");
-        dbg!(tms);
-        assert_eq!(tms, 123);
+    fn test_secs_nsecs_to_time_ms_inverse_of_time_ms_to_secs_nsecs() {
+        // Same values as test_time_ms_to_secs_nsecs in both directions
+        let pairs = [
+            (-2001, (-3i64, 999_000_000u32)),
+            (-2000, (-2i64, 0u32)),
+            (-1999, (-2i64, 1_000_000u32)),
+            (-1001, (-2i64, 999_000_000u32)),
+            (-1000, (-1i64, 0u32)),
+            (-999, (-1i64, 1_000_000u32)),
+            (-1, (-1i64, 999_000_000u32)),
+            (0, (0i64, 0u32)),
+            (1, (0i64, 1_000_000u32)),
+            (999, (0i64, 999_000_000u32)),
+            (1000, (1i64, 0u32)),
+        ];
+        for (tms, (secs, nsecs)) in pairs {
+            assert_eq!(time_ms_to_secs_nsecs(tms), (secs, nsecs));
+            assert_eq!(secs_nsecs_to_time_ms(secs, nsecs), tms);
+            assert_eq!(
+                time_ms_to_secs_nsecs(secs_nsecs_to_time_ms(secs, nsecs)),
+                (secs, nsecs)
+            );
+        }
     }
 
     #[test]
-    fn test_dt_str_with_leading_trailing_spaces_to_utc_time_ms() {
-        let str_time_no_ms = " 1970-01-01 00:00:00";
-        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_with_ms = "1970-01-01 00:00:00.123 ";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-        let str_time_no_ms = " 1970-01-01T00:00:00z  ";
-        let ts = dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_with_ms = "  1970-01-01T00:00:00.123Z  ";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::CondAddTzUtc)
-            .expect("Bad time format with milliseconds");
-        dbg!(tms);
-        assert_eq!(tms, 123);
-    }
+    fn test_secs_nsecs_to_time_ms_round_trip() {
+        // Every value around the epoch and then a pseudo-random sample
+        for tms in -100_000..=100_000 {
+            let (secs, nsecs) = time_ms_to_secs_nsecs(tms);
+            assert_eq!(secs_nsecs_to_time_ms(secs, nsecs), tms);
+        }
 
-    #[test]
-    fn test_dt_str_addtzutc_with_utc() {
-        let str_time_tz = "1970-01-01 00:00:00+00";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-        let str_time_tz = "1970-01-01T00:00:00.1+00";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 100);
-
-        let str_time_tz = "1970-01-01T00:00:00.123+0000";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 123);
-
-        let str_time_tz = "1970-01-01 00:00:00+00:00";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_tz = "1970-01-01 00:00:00.456+00:00";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 456);
-    }
+        let mut x: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..100_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let tms = x as i64;
+            let (secs, nsecs) = time_ms_to_secs_nsecs(tms);
+            assert_eq!(secs_nsecs_to_time_ms(secs, nsecs), tms);
+        }
 
-    #[test]
-    fn test_dt_str_with_tz_to_utc_time_ms() {
-        let str_time_no_ms = "1970-01-01T00:00:00+0000";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::HasTz).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_no_ms = "1970-01-01T00:00:00Z";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_no_ms, TzMassaging::HasTz).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_with_ms = "1970-01-01T00:00:00.123z";
-        let tms = dt_str_to_utc_time_ms(str_time_with_ms, TzMassaging::HasTz)
-            .expect("Bad time format with milliseconds");
-        dbg!(tms);
-        assert_eq!(tms, 123);
+        for tms in [i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX] {
+            let (secs, nsecs) = time_ms_to_secs_nsecs(tms);
+            assert_eq!(secs_nsecs_to_time_ms(secs, nsecs), tms);
+        }
     }
 
     #[test]
-    fn test_dt_str_both_hastz() {
-        let str_time_tz = "1970-01-01T00:00:00+0000";
-        let ts = dt_str_to_utc_time_ms(str_time_tz, TzMassaging::HasTz).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_pst = "1969-12-31T16:00:00-0800";
-        let ts_pst = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::HasTz)
-            .expect("Bad time format with milliseconds");
-        dbg!(ts_pst);
-        assert_eq!(ts, ts_pst);
+    fn test_time_ms_to_epoch_seconds_floor_and_trunc() {
+        // (time_ms, floor, trunc)
+        for (tms, floor, trunc) in [
+            (0, 0, 0),
+            (999, 0, 0),
+            (1000, 1, 1),
+            (1500, 1, 1),
+            (1_657_877_459_123, 1_657_877_459, 1_657_877_459),
+            (-1, -1, 0),
+            (-999, -1, 0),
+            (-1000, -1, -1),
+            (-1001, -2, -1),
+            (-1500, -2, -1),
+            (i64::MIN, i64::MIN / 1000 - 1, i64::MIN / 1000),
+        ] {
+            assert_eq!(time_ms_to_epoch_seconds_floor(tms), floor, "tms={tms}");
+            assert_eq!(time_ms_to_epoch_seconds_trunc(tms), trunc, "tms={tms}");
+            assert_eq!(
+                time_ms_to_epoch_seconds_floor(tms),
+                time_ms_to_secs_nsecs(tms).0
+            );
+        }
     }
 
     #[test]
-    fn test_dt_str_addtzutc_hastz() {
-        let str_time_tz = "1970-01-01T00:00:00";
-        let ts =
-            dt_str_to_utc_time_ms(str_time_tz, TzMassaging::CondAddTzUtc).expect("Bad time format");
-        dbg!(ts);
-        assert_eq!(ts, 0);
-
-        let str_time_pst = "1969-12-31T16:00:00-0800";
-        let ts_pst = dt_str_to_utc_time_ms(str_time_pst, TzMassaging::HasTz)
-            .expect("Bad time format with milliseconds");
-        dbg!(ts_pst);
-        assert_eq!(ts, ts_pst);
+    fn test_time_ms_add_saturating() {
+        assert_eq!(time_ms_add_saturating(0, 0), 0);
+        assert_eq!(
+            time_ms_add_saturating(1_657_886_400_000, 123),
+            1_657_886_400_123
+        );
+        assert_eq!(time_ms_add_saturating(1000, -1500), -500);
+        assert_eq!(time_ms_add_saturating(i64::MAX, 1), i64::MAX);
+        assert_eq!(time_ms_add_saturating(i64::MAX - 1, 1), i64::MAX);
+        assert_eq!(time_ms_add_saturating(i64::MIN, -1), i64::MIN);
+        assert_eq!(time_ms_add_saturating(i64::MIN, i64::MAX), -1);
     }
 
     #[test]
-    fn test_dt_str_to_utc_time_ms_using_localtz() {
-        // UTC time_ms 0 as a Date Time string without time zone
-        let utc_time_ms_0_str = "1970-01-01T00:00:00";
-
-        // Get a NaiveDateTime
-        let ndt: NaiveDateTime = utc_time_ms_0_str.parse().unwrap();
-        dbg!(ndt);
-
-        // Convert to local time so at PST this is 1970-10-01T00:00:00-08:00
-        let ldt = Local.from_local_datetime(&ndt).unwrap();
-        dbg!(ldt);
-
-        // Get the offset from local time to utc, a negative value for west of UTC
-        // This will be -08:00 for PST
-        let ldt_offset = ldt.offset();
-        dbg!(ldt_offset);
-
-        // Get the value to add to UTC to convert to local time
-        // This will be -28800000 = (-8 * 60 * 60 * 1000) for PST
-        let milli_seconds_to_add_to_convert_to_utc = ldt_offset.local_minus_utc() as i64 * 1000;
-        dbg!(milli_seconds_to_add_to_convert_to_utc);
-
-        // Adding milli_seconds_to_add_to_convert_to_utc to tms will equal 0 for all timezones
-        // For PST tms is 28800000 and adding -28800000 is 0
-        let tms = dt_str_to_utc_time_ms(utc_time_ms_0_str, TzMassaging::LocalTz)
-            .expect("Bad time format");
-        dbg!(tms);
-        assert_eq!(tms + milli_seconds_to_add_to_convert_to_utc, 0);
+    fn test_time_ms_sub_saturating() {
+        assert_eq!(
+            time_ms_sub_saturating(1_657_886_400_123, 123),
+            1_657_886_400_000
+        );
+        assert_eq!(time_ms_sub_saturating(-500, -1500), 1000);
+        assert_eq!(time_ms_sub_saturating(i64::MIN, 1), i64::MIN);
+        assert_eq!(time_ms_sub_saturating(i64::MAX, -1), i64::MAX);
+        assert_eq!(time_ms_sub_saturating(0, i64::MIN), i64::MAX);
+        assert_eq!(time_ms_sub_saturating(-1, i64::MIN), i64::MAX);
     }
 
     #[test]
-    fn test_time_ms_to_utc() {
-        let dt = time_ms_to_utc(0i64);
+    fn test_epoch_seconds_to_time_ms() {
+        assert_eq!(epoch_seconds_to_time_ms(0), 0);
+        assert_eq!(epoch_seconds_to_time_ms(1_657_877_459), 1_657_877_459_000);
+        assert_eq!(epoch_seconds_to_time_ms(-1), -1000);
+        assert_eq!(epoch_seconds_to_time_ms(i64::MAX), i64::MAX);
+        assert_eq!(epoch_seconds_to_time_ms(i64::MIN), i64::MIN);
+
+        // Inverse of floor on whole seconds, and floors otherwise
+        for tms in [-2000, -1000, 0, 1000, 1_657_877_459_000] {
+            assert_eq!(
+                epoch_seconds_to_time_ms(time_ms_to_epoch_seconds_floor(tms)),
+                tms
+            );
+        }
         assert_eq!(
-            dt.to_rfc3339_opts(SecondsFormat::Millis, true),
-            "1970-01-01T00:00:00.000Z"
+            epoch_seconds_to_time_ms(time_ms_to_epoch_seconds_floor(-1500)),
+            -2000
         );
         assert_eq!(
-            dt.to_rfc3339_opts(SecondsFormat::Millis, false),
-            "1970-01-01T00:00:00.000+00:00"
+            epoch_seconds_to_time_ms(time_ms_to_epoch_seconds_trunc(-1500)),
+            -1000
+        );
+        assert_eq!(
+            epoch_seconds_to_time_ms(time_ms_to_epoch_seconds_floor(1500)),
+            1000
         );
-    }
-
-    #[test]
-    fn test_time_ms_to_utc_string() {
-        let dt = time_ms_to_utc_string(0i64);
-        assert_eq!(dt, "1970-01-01T00:00:00.000+00:00");
-    }
-
-    #[test]
-    fn test_time_ms_to_utc_z_string() {
-        let dt = time_ms_to_utc_z_string(0i64);
-        assert_eq!(dt, "1970-01-01T00:00:00.000Z");
-    }
-
-    #[test]
-    fn test_date_time_parse_from_rfc3339() {
-        let s = format!("1970-01-01T00:00:00.000{}", "Z");
-        let dt = match DateTime::parse_from_rfc3339(&s) {
-            Ok(v) => v,
-            Err(e) => panic!("shit {e}"),
-        };
-        println!("test_date_time_parse_from_rfc3339: {dt}");
     }
 }
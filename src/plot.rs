@@ -0,0 +1,135 @@
+use crate::{TimeMsError, TimeMsRange, MS_PER_DAY, MS_PER_SEC};
+
+/// Seconds from `origin_ms` to time_ms as an f64, for plotting
+///
+/// Plotting with seconds relative to a nearby origin, see
+/// [`choose_plot_origin`], keeps the values small so differences between
+/// nearby times are much more precise than with seconds since the epoch.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::to_plot_seconds;
+///
+/// assert_eq!(to_plot_seconds(1_500, 1_000), 0.5);
+/// assert_eq!(to_plot_seconds(0, 1_000), -1.0);
+/// ```
+pub fn to_plot_seconds(time_ms: i64, origin_ms: i64) -> f64 {
+    (time_ms as i128 - origin_ms as i128) as f64 / MS_PER_SEC as f64
+}
+
+/// The time_ms `secs` seconds after `origin_ms`, the inverse of [`to_plot_seconds`]
+///
+/// Sub milli-seconds are rounded half up. Returns `TimeMsError::OutOfRange`
+/// if `secs` isn't finite or the result doesn't fit in an i64.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{from_plot_seconds, TimeMsError};
+///
+/// assert_eq!(from_plot_seconds(0.5, 1_000), Ok(1_500));
+/// assert_eq!(from_plot_seconds(-0.0015, 0), Ok(-1));
+/// assert_eq!(from_plot_seconds(f64::NAN, 0), Err(TimeMsError::OutOfRange));
+/// ```
+pub fn from_plot_seconds(secs: f64, origin_ms: i64) -> Result<i64, TimeMsError> {
+    let offset_ms = (secs * MS_PER_SEC as f64 + 0.5).floor();
+
+    // i64::MAX as f64 rounds up to 2^63 so it's excluded
+    if !offset_ms.is_finite() || offset_ms < i64::MIN as f64 || offset_ms >= i64::MAX as f64 {
+        return Err(TimeMsError::OutOfRange);
+    }
+
+    origin_ms
+        .checked_add(offset_ms as i64)
+        .ok_or(TimeMsError::OutOfRange)
+}
+
+/// A plot origin for `range`, the start of the UTC day of `range.start_ms`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{choose_plot_origin, TimeMsRange};
+///
+/// // 2022-07-15T12:00:00.123Z through 2025-07-15
+/// let range = TimeMsRange::new(1_657_886_400_123, 1_752_537_600_000);
+/// assert_eq!(choose_plot_origin(&range), 1_657_843_200_000);
+/// ```
+pub fn choose_plot_origin(range: &TimeMsRange) -> i64 {
+    range.start_ms.div_euclid(MS_PER_DAY) * MS_PER_DAY
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2020-01-01T00:00:00Z through 2025-01-01T00:00:00Z
+    const RANGE: TimeMsRange = TimeMsRange {
+        start_ms: 1_577_836_800_000,
+        end_ms: 1_735_689_600_000,
+    };
+
+    #[test]
+    fn test_plot_seconds_round_trip() {
+        let origin = choose_plot_origin(&RANGE);
+        assert_eq!(origin, RANGE.start_ms);
+
+        let mut tms = RANGE.start_ms;
+        while tms < RANGE.end_ms {
+            let secs = to_plot_seconds(tms, origin);
+            assert_eq!(from_plot_seconds(secs, origin), Ok(tms));
+            tms += 12_345_678_901;
+        }
+        for tms in [RANGE.start_ms - 1, RANGE.end_ms, RANGE.end_ms + 1] {
+            let secs = to_plot_seconds(tms, origin);
+            dbg!(tms, secs);
+            assert_eq!(from_plot_seconds(secs, origin), Ok(tms));
+        }
+    }
+
+    #[test]
+    fn test_plot_seconds_precision() {
+        // Three years into the range, the plotted difference between
+        // adjacent milli-seconds should be 0.001
+        let tms = RANGE.start_ms + 3 * 365 * MS_PER_DAY + 123;
+
+        let from_epoch = to_plot_seconds(tms + 1, 0) - to_plot_seconds(tms, 0);
+        let origin = choose_plot_origin(&RANGE);
+        let from_origin = to_plot_seconds(tms + 1, origin) - to_plot_seconds(tms, origin);
+        dbg!(from_epoch, from_origin);
+
+        // About 1e-7 from the epoch versus 1e-9 from the origin
+        assert!((from_epoch - 0.001).abs() > 1e-8);
+        assert!((from_origin - 0.001).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_from_plot_seconds_rounding() {
+        assert_eq!(from_plot_seconds(0.0004, 0), Ok(0));
+        assert_eq!(from_plot_seconds(0.0005, 0), Ok(1));
+        assert_eq!(from_plot_seconds(-0.0005, 0), Ok(0));
+        assert_eq!(from_plot_seconds(-0.0006, 0), Ok(-1));
+        assert_eq!(from_plot_seconds(-1.0, -1_000), Ok(-2_000));
+    }
+
+    #[test]
+    fn test_from_plot_seconds_out_of_range() {
+        for secs in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e17, -1e17] {
+            dbg!(secs);
+            assert_eq!(from_plot_seconds(secs, 0), Err(TimeMsError::OutOfRange));
+        }
+        assert_eq!(
+            from_plot_seconds(0.001, i64::MAX),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(
+            from_plot_seconds(-0.001, i64::MIN),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(from_plot_seconds(-0.001, i64::MAX), Ok(i64::MAX - 1));
+    }
+
+    #[test]
+    fn test_choose_plot_origin_negative() {
+        let range = TimeMsRange::new(-1, 1_000);
+        assert_eq!(choose_plot_origin(&range), -MS_PER_DAY);
+    }
+}
@@ -0,0 +1,511 @@
+use crate::calendar::next_month_start;
+use crate::{align_time_ms, truncate_time_ms_to_month};
+
+/// A half-open range of time_ms values, `[start_ms, end_ms)`
+///
+/// A range whose `end_ms` is not greater than `start_ms` is empty,
+/// it contains no time_ms and overlaps nothing.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::TimeMsRange;
+///
+/// let r = TimeMsRange::new(1_000, 2_000);
+/// assert!(r.contains(1_000));
+/// assert!(!r.contains(2_000));
+/// assert_eq!(r.duration_ms(), 1_000);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimeMsRange {
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+impl TimeMsRange {
+    pub fn new(start_ms: i64, end_ms: i64) -> Self {
+        Self { start_ms, end_ms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end_ms <= self.start_ms
+    }
+
+    /// Duration of the range in milli-seconds, 0 if empty and
+    /// saturating at i64::MAX
+    pub fn duration_ms(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            self.end_ms.saturating_sub(self.start_ms)
+        }
+    }
+
+    pub fn contains(&self, time_ms: i64) -> bool {
+        self.start_ms <= time_ms && time_ms < self.end_ms
+    }
+
+    /// True if both ranges are non-empty and share at least one time_ms
+    pub fn overlaps(&self, other: &TimeMsRange) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.start_ms < other.end_ms
+            && other.start_ms < self.end_ms
+    }
+}
+
+// Split `range` at the bucket starts, the first is first_start_ms and
+// each following one is next_start(bucket_start), None if there is no
+// following bucket in range
+fn apportion_with(
+    range: &TimeMsRange,
+    first_start_ms: i64,
+    mut next_start: impl FnMut(i64) -> Option<i64>,
+) -> Vec<(i64, i64)> {
+    let mut buckets = Vec::new();
+    if range.is_empty() {
+        return buckets;
+    }
+
+    let mut bucket_start = first_start_ms;
+    loop {
+        let next = next_start(bucket_start);
+        let covered_end = next.map_or(range.end_ms, |next| next.min(range.end_ms));
+        let covered_start = bucket_start.max(range.start_ms);
+        buckets.push((bucket_start, covered_end.saturating_sub(covered_start)));
+
+        match next {
+            Some(next) if next < range.end_ms => bucket_start = next,
+            _ => return buckets,
+        }
+    }
+}
+
+/// Split `range` across the `bucket_ms` sized buckets it touches, such as
+/// for billing usage by the hour, returning `(bucket_start, covered_ms)`
+/// for each in ascending order.
+///
+/// Buckets are aligned as [`align_time_ms`]. Each touched bucket has a
+/// positive `covered_ms` and they sum exactly to `range.duration_ms()`,
+/// a range ending on a bucket boundary doesn't touch the bucket starting
+/// there. Empty if `range` is empty.
+///
+/// # Panics
+///
+/// If `bucket_ms <= 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{apportion, TimeMsRange};
+///
+/// let buckets = apportion(&TimeMsRange::new(500, 2_250), 1_000);
+/// assert_eq!(buckets, [(0, 500), (1_000, 1_000), (2_000, 250)]);
+/// ```
+pub fn apportion(range: &TimeMsRange, bucket_ms: i64) -> Vec<(i64, i64)> {
+    assert!(bucket_ms > 0, "apportion: bucket_ms must be > 0");
+
+    apportion_with(range, align_time_ms(range.start_ms, bucket_ms), |start| {
+        start.checked_add(bucket_ms)
+    })
+}
+
+/// [`apportion`] with the UTC calendar months as the buckets, see
+/// [`truncate_time_ms_to_month`]
+///
+/// # Panics
+///
+/// If `range` isn't empty and its start is out of the range of
+/// DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{apportion_by_month, time_ms_from_utc_parts, TimeMsRange};
+///
+/// const DAY: i64 = 24 * 60 * 60 * 1000;
+/// let utc = |y, m, d| time_ms_from_utc_parts(y, m, d, 0, 0, 0, 0).unwrap();
+///
+/// let range = TimeMsRange::new(utc(2023, 1, 31), utc(2023, 3, 2));
+/// assert_eq!(
+///     apportion_by_month(&range),
+///     [(utc(2023, 1, 1), DAY), (utc(2023, 2, 1), 28 * DAY), (utc(2023, 3, 1), DAY)]
+/// );
+/// ```
+pub fn apportion_by_month(range: &TimeMsRange) -> Vec<(i64, i64)> {
+    if range.is_empty() {
+        return Vec::new();
+    }
+
+    apportion_with(
+        range,
+        truncate_time_ms_to_month(range.start_ms),
+        next_month_start,
+    )
+}
+
+/// Index over many [`TimeMsRange`] values for fast containment
+/// and overlap queries.
+///
+/// The ranges are sorted by `start_ms` and a max-`end_ms` segment tree
+/// is built over them, an implicit interval tree. A query binary searches
+/// for the ranges that start early enough and then only descends into
+/// subtrees whose maximum `end_ms` is late enough. Each range returned is
+/// reached by a path of O(log n) nodes, so a query costs O((m + 1) log n)
+/// where m is the number of ranges returned.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{TimeMsRange, TimeMsRangeIndex};
+///
+/// let idx = TimeMsRangeIndex::build(vec![
+///     TimeMsRange::new(0, 100),
+///     TimeMsRange::new(50, 150),
+///     TimeMsRange::new(200, 300),
+/// ]);
+/// assert_eq!(idx.containing(75).count(), 2);
+/// assert!(!idx.any_contains(175));
+/// assert_eq!(idx.overlapping(&TimeMsRange::new(140, 210)).count(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimeMsRangeIndex {
+    ranges: Vec<TimeMsRange>,
+
+    // Segment tree of the maximum end_ms, leaves start at `leaves`
+    max_end: Vec<i64>,
+    leaves: usize,
+}
+
+impl TimeMsRangeIndex {
+    pub fn build(mut ranges: Vec<TimeMsRange>) -> Self {
+        ranges.sort_by_key(|r| (r.start_ms, r.end_ms));
+
+        let leaves = ranges.len().next_power_of_two();
+        let mut max_end = vec![i64::MIN; 2 * leaves];
+        for (i, r) in ranges.iter().enumerate() {
+            max_end[leaves + i] = r.end_ms;
+        }
+        for node in (1..leaves).rev() {
+            max_end[node] = max_end[2 * node].max(max_end[2 * node + 1]);
+        }
+
+        Self {
+            ranges,
+            max_end,
+            leaves,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The indexed ranges sorted by `start_ms`
+    pub fn ranges(&self) -> &[TimeMsRange] {
+        &self.ranges
+    }
+
+    /// Ranges containing `time_ms` in order of `start_ms`
+    pub fn containing(&self, time_ms: i64) -> impl Iterator<Item = &TimeMsRange> {
+        let starts_before = self.ranges.partition_point(|r| r.start_ms <= time_ms);
+        self.query(starts_before, time_ms)
+            .filter(move |r| r.contains(time_ms))
+    }
+
+    /// Ranges overlapping `query` in order of `start_ms`
+    pub fn overlapping(&self, query: &TimeMsRange) -> impl Iterator<Item = &TimeMsRange> {
+        let query = *query;
+        let starts_before = if query.is_empty() {
+            0
+        } else {
+            self.ranges.partition_point(|r| r.start_ms < query.end_ms)
+        };
+        self.query(starts_before, query.start_ms)
+            .filter(move |r| r.overlaps(&query))
+    }
+
+    pub fn any_contains(&self, time_ms: i64) -> bool {
+        self.containing(time_ms).next().is_some()
+    }
+
+    /// Candidate ranges among the first `prefix_len` whose end_ms is > `after_ms`
+    fn query(&self, prefix_len: usize, after_ms: i64) -> Candidates<'_> {
+        let mut stack = Vec::new();
+        if prefix_len > 0 {
+            stack.push((1, 0, self.leaves));
+        }
+        Candidates {
+            index: self,
+            prefix_len,
+            after_ms,
+            stack,
+        }
+    }
+}
+
+struct Candidates<'a> {
+    index: &'a TimeMsRangeIndex,
+    prefix_len: usize,
+    after_ms: i64,
+
+    // (node, first leaf, one past last leaf)
+    stack: Vec<(usize, usize, usize)>,
+}
+
+impl<'a> Iterator for Candidates<'a> {
+    type Item = &'a TimeMsRange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, lo, hi)) = self.stack.pop() {
+            if lo >= self.prefix_len || self.index.max_end[node] <= self.after_ms {
+                continue;
+            }
+            if hi - lo == 1 {
+                return Some(&self.index.ranges[lo]);
+            }
+
+            // Push right first so ranges are returned in start_ms order
+            let mid = (lo + hi) / 2;
+            self.stack.push((2 * node + 1, mid, hi));
+            self.stack.push((2 * node, lo, mid));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Small deterministic xorshift generator so the tests are repeatable
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn range(&mut self, lo: i64, hi: i64) -> i64 {
+            lo + (self.next() % (hi - lo) as u64) as i64
+        }
+    }
+
+    fn random_ranges(rng: &mut XorShift, n: usize) -> Vec<TimeMsRange> {
+        (0..n)
+            .map(|_| {
+                let start = rng.range(-10_000, 10_000);
+                let len = rng.range(-10, 2_000);
+                TimeMsRange::new(start, start + len)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_time_ms_range() {
+        let r = TimeMsRange::new(-10, 10);
+        assert!(!r.is_empty());
+        assert_eq!(r.duration_ms(), 20);
+        assert!(r.contains(-10));
+        assert!(r.contains(9));
+        assert!(!r.contains(10));
+
+        let empty = TimeMsRange::new(10, 10);
+        assert!(empty.is_empty());
+        assert_eq!(empty.duration_ms(), 0);
+        assert!(!empty.contains(10));
+        assert!(!empty.overlaps(&r));
+        assert!(!TimeMsRange::new(0, 5).overlaps(&empty));
+
+        assert!(r.overlaps(&TimeMsRange::new(9, 20)));
+        assert!(!r.overlaps(&TimeMsRange::new(10, 20)));
+        assert!(!r.overlaps(&TimeMsRange::new(-20, -10)));
+    }
+
+    #[test]
+    fn test_apportion() {
+        // Inside one bucket
+        assert_eq!(
+            apportion(&TimeMsRange::new(1_100, 1_900), 1_000),
+            [(1_000, 800)]
+        );
+
+        // Exactly spanning several
+        assert_eq!(
+            apportion(&TimeMsRange::new(1_000, 4_000), 1_000),
+            [(1_000, 1_000), (2_000, 1_000), (3_000, 1_000)]
+        );
+
+        // Starting or ending exactly on a boundary
+        assert_eq!(
+            apportion(&TimeMsRange::new(1_000, 2_500), 1_000),
+            [(1_000, 1_000), (2_000, 500)]
+        );
+        assert_eq!(
+            apportion(&TimeMsRange::new(500, 2_000), 1_000),
+            [(0, 500), (1_000, 1_000)]
+        );
+        assert_eq!(
+            apportion(&TimeMsRange::new(-1, 1), 1_000),
+            [(-1_000, 1), (0, 1)]
+        );
+        assert_eq!(apportion(&TimeMsRange::new(999, 1_000), 1_000), [(0, 1)]);
+
+        // Empty
+        assert!(apportion(&TimeMsRange::new(1_000, 1_000), 1_000).is_empty());
+        assert!(apportion(&TimeMsRange::new(2_000, 1_000), 1_000).is_empty());
+
+        // The last bucket doesn't overflow
+        assert_eq!(
+            apportion(&TimeMsRange::new(i64::MAX - 1, i64::MAX), 1_000),
+            [(i64::MAX - 807, 1)]
+        );
+    }
+
+    #[test]
+    fn test_apportion_sum_invariant() {
+        let mut rng = XorShift(0x0ddb_1a5e_5ca1_ab1e);
+        for _ in 0..10_000 {
+            let bucket_ms = rng.range(1, 5_000);
+            let start = rng.range(-20_000, 20_000);
+            let range = TimeMsRange::new(start, start + rng.range(-10, 30_000));
+            let buckets = apportion(&range, bucket_ms);
+
+            let total: i64 = buckets.iter().map(|&(_, covered)| covered).sum();
+            assert_eq!(total, range.duration_ms(), "{range:?} {bucket_ms}");
+            for (i, &(bucket_start, covered)) in buckets.iter().enumerate() {
+                assert_eq!(bucket_start.rem_euclid(bucket_ms), 0);
+                assert!((1..=bucket_ms).contains(&covered), "{range:?} {bucket_ms}");
+                if i > 0 {
+                    assert_eq!(bucket_start, buckets[i - 1].0 + bucket_ms);
+                }
+            }
+            if let (Some(first), Some(last)) = (buckets.first(), buckets.last()) {
+                assert!(first.0 <= range.start_ms && range.start_ms < first.0 + bucket_ms);
+                assert!(last.0 < range.end_ms && range.end_ms <= last.0 + bucket_ms);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "apportion: bucket_ms must be > 0")]
+    fn test_apportion_zero_bucket() {
+        apportion(&TimeMsRange::new(0, 1_000), 0);
+    }
+
+    #[test]
+    fn test_apportion_by_month() {
+        const DAY: i64 = 24 * 60 * 60 * 1000;
+        let utc = |y, m, d| crate::time_ms_from_utc_parts(y, m, d, 0, 0, 0, 0).unwrap();
+
+        // Inside one month
+        let range = TimeMsRange::new(utc(2024, 2, 10), utc(2024, 2, 12));
+        assert_eq!(apportion_by_month(&range), [(utc(2024, 2, 1), 2 * DAY)]);
+
+        // Exactly spanning months, 2024 is a leap year
+        let range = TimeMsRange::new(utc(2024, 1, 1), utc(2024, 4, 1));
+        assert_eq!(
+            apportion_by_month(&range),
+            [
+                (utc(2024, 1, 1), 31 * DAY),
+                (utc(2024, 2, 1), 29 * DAY),
+                (utc(2024, 3, 1), 31 * DAY),
+            ]
+        );
+
+        // Across the end of a year and the epoch
+        let range = TimeMsRange::new(utc(1969, 12, 31), 1);
+        assert_eq!(
+            apportion_by_month(&range),
+            [(utc(1969, 12, 1), DAY), (0, 1)]
+        );
+
+        assert!(apportion_by_month(&TimeMsRange::new(0, 0)).is_empty());
+        assert!(apportion_by_month(&TimeMsRange::new(i64::MAX, i64::MIN)).is_empty());
+    }
+
+    #[test]
+    fn test_apportion_by_month_sum_invariant() {
+        const YEAR: i64 = 365 * 24 * 60 * 60 * 1000;
+        let mut rng = XorShift(0x5eed_0fa1_10ca_7e01);
+        for _ in 0..1_000 {
+            let start = rng.range(-50 * YEAR, 50 * YEAR);
+            let range = TimeMsRange::new(start, start + rng.range(-10, 3 * YEAR));
+            let buckets = apportion_by_month(&range);
+
+            let total: i64 = buckets.iter().map(|&(_, covered)| covered).sum();
+            assert_eq!(total, range.duration_ms(), "{range:?}");
+            for (i, &(month_start, covered)) in buckets.iter().enumerate() {
+                assert_eq!(truncate_time_ms_to_month(month_start), month_start);
+                assert!(covered > 0, "{range:?}");
+                if i > 0 {
+                    assert_eq!(next_month_start(buckets[i - 1].0), Some(month_start));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_ms_range_index_empty() {
+        let idx = TimeMsRangeIndex::build(vec![]);
+        assert!(idx.is_empty());
+        assert_eq!(idx.containing(0).count(), 0);
+        assert!(!idx.any_contains(0));
+        assert_eq!(idx.overlapping(&TimeMsRange::new(0, 10)).count(), 0);
+    }
+
+    #[test]
+    fn test_time_ms_range_index_extremes() {
+        let idx = TimeMsRangeIndex::build(vec![
+            TimeMsRange::new(i64::MIN, i64::MAX),
+            TimeMsRange::new(i64::MAX - 1, i64::MAX),
+        ]);
+        assert!(idx.any_contains(i64::MIN));
+        assert_eq!(idx.containing(i64::MAX - 1).count(), 2);
+        assert!(!idx.any_contains(i64::MAX));
+    }
+
+    #[test]
+    fn test_time_ms_range_index_vs_brute_force() {
+        let mut rng = XorShift(0x1234_5678_9abc_def0);
+        for n in [1, 2, 3, 7, 64, 100, 1_000] {
+            let ranges = random_ranges(&mut rng, n);
+            let idx = TimeMsRangeIndex::build(ranges.clone());
+            assert_eq!(idx.len(), n);
+
+            for _ in 0..200 {
+                let t = rng.range(-12_000, 12_000);
+                let mut expected: Vec<&TimeMsRange> =
+                    ranges.iter().filter(|r| r.contains(t)).collect();
+                let mut actual: Vec<&TimeMsRange> = idx.containing(t).collect();
+                expected.sort_by_key(|r| (r.start_ms, r.end_ms));
+                actual.sort_by_key(|r| (r.start_ms, r.end_ms));
+                assert_eq!(actual, expected);
+                assert_eq!(idx.any_contains(t), !expected.is_empty());
+
+                let qs = rng.range(-12_000, 12_000);
+                let query = TimeMsRange::new(qs, qs + rng.range(-10, 500));
+                let mut expected: Vec<&TimeMsRange> =
+                    ranges.iter().filter(|r| r.overlaps(&query)).collect();
+                let mut actual: Vec<&TimeMsRange> = idx.overlapping(&query).collect();
+                expected.sort_by_key(|r| (r.start_ms, r.end_ms));
+                actual.sort_by_key(|r| (r.start_ms, r.end_ms));
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_ms_range_index_ordered_by_start() {
+        let idx = TimeMsRangeIndex::build(vec![
+            TimeMsRange::new(30, 100),
+            TimeMsRange::new(10, 100),
+            TimeMsRange::new(20, 100),
+        ]);
+        let starts: Vec<i64> = idx.containing(50).map(|r| r.start_ms).collect();
+        assert_eq!(starts, vec![10, 20, 30]);
+    }
+}
@@ -0,0 +1,426 @@
+use chrono::{Datelike, Days, NaiveTime, Weekday};
+
+use crate::{split_mix64, time_ms_from_utc_parts, time_ms_to_utc, utc_to_time_ms, MS_PER_DAY};
+
+/// What [`next_annual_occurrence`] does with Feb 29 in a non-leap year
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeapDayPolicy {
+    /// Occur on Feb 28 instead
+    ClampToFeb28,
+
+    /// Don't occur, skip to the next leap year
+    SkipToLeapYear,
+}
+
+// time_ms of year-month-day at hms UTC, None if the date doesn't exist
+fn occurrence(year: i32, month: u32, day: u32, hms: (u32, u32, u32)) -> Option<i64> {
+    let (hour, minute, second) = hms;
+    time_ms_from_utc_parts(year, month, day, hour, minute, second, 0)
+}
+
+fn assert_valid_hms(hms: (u32, u32, u32)) {
+    let (hour, minute, second) = hms;
+    assert!(
+        hour < 24 && minute < 60 && second < 60,
+        "invalid hms {hms:?}"
+    );
+}
+
+/// The next UTC occurrence strictly after `now_ms` of `day_of_month` at
+/// `hms`, (hour, minute, second), each month.
+///
+/// In months shorter than `day_of_month` the occurrence is clamped to the
+/// last day of the month, so the 31st occurs on Feb 28 or 29, Apr 30 and
+/// so on, and is back on the 31st in months that have one.
+///
+/// # Panics
+///
+/// If `day_of_month` isn't 1 through 31 or `hms` isn't a valid time
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{next_monthly_occurrence, time_ms_from_utc_parts};
+///
+/// let now = time_ms_from_utc_parts(2023, 1, 31, 12, 0, 0, 0).unwrap();
+/// let next = time_ms_from_utc_parts(2023, 2, 28, 0, 0, 0, 0).unwrap();
+/// assert_eq!(next_monthly_occurrence(now, 31, (0, 0, 0)), next);
+/// ```
+pub fn next_monthly_occurrence(now_ms: i64, day_of_month: u32, hms: (u32, u32, u32)) -> i64 {
+    assert!(
+        (1..=31).contains(&day_of_month),
+        "next_monthly_occurrence: day_of_month must be 1 through 31"
+    );
+    assert_valid_hms(hms);
+
+    let now = time_ms_to_utc(now_ms);
+    let (mut year, mut month) = (now.year(), now.month());
+    loop {
+        // Clamp to the last day of a short month
+        let tms = (1..=day_of_month)
+            .rev()
+            .find_map(|day| occurrence(year, month, day, hms))
+            .expect("Every month has a 28th");
+        if tms > now_ms {
+            return tms;
+        }
+
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+}
+
+/// The next UTC occurrence strictly after `now_ms` of `month` and `day`
+/// at `hms`, (hour, minute, second), each year.
+///
+/// Feb 29 is handled in non-leap years according to `policy`.
+///
+/// # Panics
+///
+/// If `month` and `day` isn't a date in a leap year or `hms` isn't a valid time
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{next_annual_occurrence, time_ms_from_utc_parts, LeapDayPolicy};
+///
+/// let now = time_ms_from_utc_parts(2023, 1, 1, 0, 0, 0, 0).unwrap();
+/// let skip = time_ms_from_utc_parts(2024, 2, 29, 0, 0, 0, 0).unwrap();
+/// let clamp = time_ms_from_utc_parts(2023, 2, 28, 0, 0, 0, 0).unwrap();
+/// assert_eq!(next_annual_occurrence(now, 2, 29, (0, 0, 0), LeapDayPolicy::SkipToLeapYear), skip);
+/// assert_eq!(next_annual_occurrence(now, 2, 29, (0, 0, 0), LeapDayPolicy::ClampToFeb28), clamp);
+/// ```
+pub fn next_annual_occurrence(
+    now_ms: i64,
+    month: u32,
+    day: u32,
+    hms: (u32, u32, u32),
+    policy: LeapDayPolicy,
+) -> i64 {
+    // 2000 is a leap year so any valid month and day exists in it
+    assert!(
+        occurrence(2000, month, day, (0, 0, 0)).is_some(),
+        "next_annual_occurrence: invalid month {month} and day {day}"
+    );
+    assert_valid_hms(hms);
+
+    let mut year = time_ms_to_utc(now_ms).year();
+    loop {
+        let tms = match occurrence(year, month, day, hms) {
+            Some(tms) => Some(tms),
+            None => match policy {
+                LeapDayPolicy::ClampToFeb28 => occurrence(year, 2, 28, hms),
+                LeapDayPolicy::SkipToLeapYear => None,
+            },
+        };
+        if let Some(tms) = tms.filter(|&tms| tms > now_ms) {
+            return tms;
+        }
+        year += 1;
+    }
+}
+
+/// The next UTC occurrence strictly after `now_ms` of `weekday` at `time`,
+/// such as "next Monday 9am". If today is `weekday` and `time` hasn't
+/// passed it's today, otherwise it's a week from today.
+///
+/// # Example
+/// ```
+/// use chrono::{NaiveTime, Weekday};
+/// use time_ms_conversions::{next_weekday_time_utc, time_ms_from_utc_parts};
+///
+/// // Friday 2022-07-15
+/// let now = time_ms_from_utc_parts(2022, 7, 15, 12, 0, 0, 0).unwrap();
+/// let nine_am = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+/// let monday = time_ms_from_utc_parts(2022, 7, 18, 9, 0, 0, 0).unwrap();
+/// assert_eq!(next_weekday_time_utc(now, Weekday::Mon, nine_am), monday);
+/// ```
+pub fn next_weekday_time_utc(now_ms: i64, weekday: Weekday, time: NaiveTime) -> i64 {
+    let today = time_ms_to_utc(now_ms).date_naive();
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let date = today + Days::new(days_ahead as u64);
+    let tms = utc_to_time_ms(&date.and_time(time).and_utc());
+    if tms > now_ms {
+        tms
+    } else {
+        tms + 7 * MS_PER_DAY
+    }
+}
+
+/// `base_ms` delayed by a pseudo random amount in `[0, max_jitter_ms]`
+/// chosen from `seed`, so jobs scheduled for the same time are spread
+/// out but each job, with its own seed, is always at the same time.
+/// The result saturates at i64::MAX.
+///
+/// # Panics
+///
+/// If `max_jitter_ms < 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::jittered_schedule_ms;
+///
+/// let base = 1_657_886_400_000;
+/// let at = jittered_schedule_ms(base, 60_000, 42);
+/// assert!((base..=base + 60_000).contains(&at));
+/// assert_eq!(jittered_schedule_ms(base, 60_000, 42), at);
+/// assert_eq!(jittered_schedule_ms(base, 0, 42), base);
+/// ```
+pub fn jittered_schedule_ms(base_ms: i64, max_jitter_ms: i64, seed: u64) -> i64 {
+    assert!(
+        max_jitter_ms >= 0,
+        "jittered_schedule_ms: max_jitter_ms must be >= 0"
+    );
+    let mut state = seed;
+    let jitter_ms = split_mix64(&mut state) % (max_jitter_ms as u64 + 1);
+
+    base_ms.saturating_add(jitter_ms as i64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> i64 {
+        time_ms_from_utc_parts(year, month, day, hour, minute, second, 0).unwrap()
+    }
+
+    const MIDNIGHT: (u32, u32, u32) = (0, 0, 0);
+
+    #[test]
+    fn test_next_monthly_occurrence_31st_across_february() {
+        let mut now = utc(2023, 1, 1, 0, 0, 0);
+        let mut occurrences = vec![];
+        for _ in 0..5 {
+            now = next_monthly_occurrence(now, 31, (9, 30, 0));
+            occurrences.push(now);
+        }
+        assert_eq!(
+            occurrences,
+            vec![
+                utc(2023, 1, 31, 9, 30, 0),
+                utc(2023, 2, 28, 9, 30, 0),
+                utc(2023, 3, 31, 9, 30, 0),
+                utc(2023, 4, 30, 9, 30, 0),
+                utc(2023, 5, 31, 9, 30, 0),
+            ]
+        );
+
+        // Leap year February
+        let now = utc(2024, 2, 1, 0, 0, 0);
+        assert_eq!(
+            next_monthly_occurrence(now, 31, MIDNIGHT),
+            utc(2024, 2, 29, 0, 0, 0)
+        );
+        assert_eq!(
+            next_monthly_occurrence(now, 30, MIDNIGHT),
+            utc(2024, 2, 29, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_monthly_occurrence_now_at_occurrence() {
+        let at = utc(2023, 3, 15, 12, 0, 0);
+        assert_eq!(
+            next_monthly_occurrence(at, 15, (12, 0, 0)),
+            utc(2023, 4, 15, 12, 0, 0)
+        );
+        assert_eq!(next_monthly_occurrence(at - 1, 15, (12, 0, 0)), at);
+    }
+
+    #[test]
+    fn test_next_monthly_occurrence_year_rollover() {
+        let now = utc(2023, 12, 31, 23, 59, 59);
+        assert_eq!(
+            next_monthly_occurrence(now, 1, MIDNIGHT),
+            utc(2024, 1, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            next_monthly_occurrence(now, 31, MIDNIGHT),
+            utc(2024, 1, 31, 0, 0, 0)
+        );
+
+        // Before the epoch
+        let now = utc(1969, 12, 15, 0, 0, 0);
+        assert_eq!(next_monthly_occurrence(now, 1, MIDNIGHT), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "day_of_month must be 1 through 31")]
+    fn test_next_monthly_occurrence_bad_day() {
+        next_monthly_occurrence(0, 32, MIDNIGHT);
+    }
+
+    #[test]
+    fn test_next_annual_occurrence_feb_29() {
+        use LeapDayPolicy::*;
+
+        let now = utc(2023, 3, 1, 0, 0, 0);
+        assert_eq!(
+            next_annual_occurrence(now, 2, 29, MIDNIGHT, SkipToLeapYear),
+            utc(2024, 2, 29, 0, 0, 0)
+        );
+        assert_eq!(
+            next_annual_occurrence(now, 2, 29, MIDNIGHT, ClampToFeb28),
+            utc(2024, 2, 29, 0, 0, 0)
+        );
+
+        let now = utc(2024, 3, 1, 0, 0, 0);
+        assert_eq!(
+            next_annual_occurrence(now, 2, 29, MIDNIGHT, SkipToLeapYear),
+            utc(2028, 2, 29, 0, 0, 0)
+        );
+        assert_eq!(
+            next_annual_occurrence(now, 2, 29, MIDNIGHT, ClampToFeb28),
+            utc(2025, 2, 28, 0, 0, 0)
+        );
+
+        // 2100 isn't a leap year
+        let now = utc(2096, 3, 1, 0, 0, 0);
+        assert_eq!(
+            next_annual_occurrence(now, 2, 29, MIDNIGHT, SkipToLeapYear),
+            utc(2104, 2, 29, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_annual_occurrence_now_at_occurrence() {
+        let at = utc(2023, 7, 4, 12, 0, 0);
+        let policy = LeapDayPolicy::SkipToLeapYear;
+        assert_eq!(
+            next_annual_occurrence(at, 7, 4, (12, 0, 0), policy),
+            utc(2024, 7, 4, 12, 0, 0)
+        );
+        assert_eq!(next_annual_occurrence(at - 1, 7, 4, (12, 0, 0), policy), at);
+
+        // Clamped Feb 28 at now
+        let at = utc(2023, 2, 28, 0, 0, 0);
+        assert_eq!(
+            next_annual_occurrence(at, 2, 29, MIDNIGHT, LeapDayPolicy::ClampToFeb28),
+            utc(2024, 2, 29, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_annual_occurrence_year_rollover() {
+        let now = utc(2023, 12, 31, 12, 0, 0);
+        let policy = LeapDayPolicy::ClampToFeb28;
+        assert_eq!(
+            next_annual_occurrence(now, 1, 1, MIDNIGHT, policy),
+            utc(2024, 1, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            next_annual_occurrence(now, 12, 31, (11, 0, 0), policy),
+            utc(2024, 12, 31, 11, 0, 0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid month 2 and day 30")]
+    fn test_next_annual_occurrence_bad_date() {
+        next_annual_occurrence(0, 2, 30, MIDNIGHT, LeapDayPolicy::ClampToFeb28);
+    }
+
+    #[test]
+    fn test_next_weekday_time_utc() {
+        let nine_am = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        // Wednesday 2022-07-13
+        let now = utc(2022, 7, 13, 12, 0, 0);
+        assert_eq!(time_ms_to_utc(now).weekday(), Weekday::Wed);
+
+        // Later this week
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Fri, nine_am),
+            utc(2022, 7, 15, 9, 0, 0)
+        );
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Sun, nine_am),
+            utc(2022, 7, 17, 9, 0, 0)
+        );
+
+        // Earlier in the week rolls forward to next week
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Mon, nine_am),
+            utc(2022, 7, 18, 9, 0, 0)
+        );
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Tue, nine_am),
+            utc(2022, 7, 19, 9, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_weekday_time_utc_today() {
+        // Wednesday 2022-07-13 at noon
+        let now = utc(2022, 7, 13, 12, 0, 0);
+        let at = |h, m, s| NaiveTime::from_hms_opt(h, m, s).unwrap();
+
+        // Later today
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Wed, at(17, 30, 0)),
+            utc(2022, 7, 13, 17, 30, 0)
+        );
+
+        // Passed today, and exactly now, is next week
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Wed, at(9, 0, 0)),
+            utc(2022, 7, 20, 9, 0, 0)
+        );
+        assert_eq!(
+            next_weekday_time_utc(now, Weekday::Wed, at(12, 0, 0)),
+            utc(2022, 7, 20, 12, 0, 0)
+        );
+
+        // Sub second times and before the epoch
+        let t = NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap();
+        assert_eq!(
+            next_weekday_time_utc(-1, Weekday::Wed, t),
+            -1 + 7 * MS_PER_DAY
+        );
+        assert_eq!(next_weekday_time_utc(-2, Weekday::Wed, t), -1);
+    }
+
+    #[test]
+    fn test_jittered_schedule_ms_stable() {
+        let base = utc(2022, 7, 15, 0, 0, 0);
+        for seed in 0..100 {
+            assert_eq!(
+                jittered_schedule_ms(base, 60_000, seed),
+                jittered_schedule_ms(base, 60_000, seed)
+            );
+        }
+
+        // The jitter depends only on the seed, not the base
+        let jitter = jittered_schedule_ms(base, 60_000, 7) - base;
+        assert_eq!(jittered_schedule_ms(-1, 60_000, 7), -1 + jitter);
+    }
+
+    #[test]
+    fn test_jittered_schedule_ms_window() {
+        let base = utc(2022, 7, 15, 0, 0, 0);
+        let mut offsets: Vec<i64> = (0..1000)
+            .map(|seed| jittered_schedule_ms(base, 1000, seed) - base)
+            .collect();
+        assert!(offsets.iter().all(|o| (0..=1000).contains(o)));
+
+        // Spread over the window, not bunched
+        offsets.sort_unstable();
+        offsets.dedup();
+        assert!(offsets.len() > 500, "{}", offsets.len());
+        assert!(offsets[0] < 50 && offsets[offsets.len() - 1] > 950);
+
+        assert_eq!(jittered_schedule_ms(base, 0, 123), base);
+        assert_eq!(jittered_schedule_ms(i64::MAX - 1, 1000, 123), i64::MAX);
+        let tms = jittered_schedule_ms(0, i64::MAX, 123);
+        assert!(tms >= 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_jitter_ms must be >= 0")]
+    fn test_jittered_schedule_ms_negative_jitter() {
+        jittered_schedule_ms(0, -1, 0);
+    }
+}
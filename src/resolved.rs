@@ -0,0 +1,449 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::conversions::{
+    detect_separator, fixed_offset_to_time_ms, has_basic_date, has_tz_offset, naive_local_to_fo,
+    normalize_decimal_comma, parse_dt_str,
+};
+use crate::{DtSeparator, ParseBranch, ParseTrace, TimeMsError, TzMassaging};
+
+/// The shape of a DateTime string as detected by [`dt_str_to_utc_time_ms`]:
+/// the separator, whether there is a time zone offset, the number of
+/// fractional second digits and the chrono format used. See
+/// [`resolve_dt_format`].
+///
+/// [`dt_str_to_utc_time_ms`]: crate::dt_str_to_utc_time_ms
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedDtFormat {
+    tz_massaging: TzMassaging,
+    branch: ParseBranch,
+    fraction_digits: usize,
+    decimal_comma: bool,
+
+    // The chrono format the rows are parsed with, without the offset if
+    // the rows have none
+    format: String,
+
+    // chrono only parses exactly 3, 6 or 9 fractional digits, other
+    // lengths are parsed with "%.f" and the digits counted
+    count_fraction_digits: bool,
+}
+
+/// Detect the shape of the DateTime string `sample`, validating it with
+/// [`dt_str_to_utc_time_ms`], so the rows of a homogeneous file can be
+/// converted with [`ResolvedDtFormat::convert`] without detecting the
+/// shape of each one.
+///
+/// [`dt_str_to_utc_time_ms`]: crate::dt_str_to_utc_time_ms
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{resolve_dt_format, DtSeparator, TzMassaging};
+///
+/// let resolved = resolve_dt_format("1970-01-01 00:00:00.000", TzMassaging::CondAddTzUtc).unwrap();
+/// assert_eq!(resolved.separator(), Some(DtSeparator::Space));
+/// assert!(!resolved.has_offset());
+/// assert_eq!(resolved.fraction_digits(), 3);
+/// assert_eq!(resolved.format(), "%Y-%m-%d %H:%M:%S.%3f");
+///
+/// assert_eq!(resolved.convert("1970-01-01 00:00:01.500"), Ok(1500));
+///
+/// // The offset deviates from the sample
+/// let err = resolved.convert("1970-01-01 00:00:01.500Z").unwrap_err();
+/// assert!(err.to_string().contains("expected no time zone offset"));
+/// ```
+pub fn resolve_dt_format(
+    sample: &str,
+    tz_massaging: TzMassaging,
+) -> Result<ResolvedDtFormat, TimeMsError> {
+    let mut trace = ParseTrace::default();
+    parse_dt_str(sample, tz_massaging, &mut trace)?;
+
+    let normalized = normalize_decimal_comma(sample.trim());
+    let decimal_comma = matches!(normalized, Cow::Owned(_));
+    let fraction_digits = fraction_digits(&normalized);
+    let fraction = match fraction_digits {
+        0 => String::new(),
+        3 | 6 | 9 if decimal_comma => format!(",%{fraction_digits}f"),
+        3 | 6 | 9 => format!(".%{fraction_digits}f"),
+        _ => "%.f".to_string(),
+    };
+
+    // Rows without an offset are parsed as naive and are then UTC or local
+    let format = trace
+        .branch
+        .format
+        .expect("a parsed sample always has a format");
+    let format = if trace.branch.has_offset {
+        format
+    } else {
+        format.trim_end_matches("%#z")
+    };
+
+    Ok(ResolvedDtFormat {
+        tz_massaging,
+        branch: trace.branch,
+        fraction_digits,
+        decimal_comma,
+        format: format.replace("%.f", &fraction),
+        count_fraction_digits: !matches!(fraction_digits, 0 | 3 | 6 | 9),
+    })
+}
+
+// The number of digits after the '.' of the fractional seconds
+fn fraction_digits(dt_str: &str) -> usize {
+    match dt_str.find('.') {
+        Some(idx) => dt_str[idx + 1..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count(),
+        None => 0,
+    }
+}
+
+impl ResolvedDtFormat {
+    /// The separator between the date and time, None for a date without
+    /// a time
+    pub fn separator(&self) -> Option<DtSeparator> {
+        self.branch.separator
+    }
+
+    /// True if the rows must have a time zone offset, false if they must
+    /// not have one
+    pub fn has_offset(&self) -> bool {
+        self.branch.has_offset
+    }
+
+    /// The number of fractional second digits the rows must have
+    pub fn fraction_digits(&self) -> usize {
+        self.fraction_digits
+    }
+
+    /// The chrono format string the rows are parsed with
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// Convert `dt_str` to utc time_ms with the resolved format
+    ///
+    /// No detection is done, the row is parsed with [`Self::format`]. If
+    /// that fails because `dt_str` deviates from the shape of the sample
+    /// the error names the deviation, such as
+    /// "expected 3 fractional second digits, found 0".
+    pub fn convert(&self, dt_str: &str) -> Result<i64, TimeMsError> {
+        let date_str = dt_str.trim();
+        let parse_err = |e: String| {
+            self.deviation(date_str)
+                .unwrap_or_else(|| TimeMsError::parse(date_str, e))
+        };
+
+        let dt_str = if self.count_fraction_digits {
+            let dt_str = normalize_decimal_comma(date_str);
+            if fraction_digits(&dt_str) != self.fraction_digits {
+                return Err(parse_err("unexpected fractional second digits".into()));
+            }
+            dt_str
+        } else {
+            Cow::Borrowed(date_str)
+        };
+
+        let dtfo = if self.branch.has_offset {
+            DateTime::parse_from_str(&dt_str, &self.format).map_err(|e| parse_err(e.to_string()))?
+        } else {
+            let ndt = if self.branch.separator.is_some() {
+                NaiveDateTime::parse_from_str(&dt_str, &self.format)
+            } else {
+                NaiveDate::parse_from_str(&dt_str, &self.format)
+                    .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight is always valid"))
+            }
+            .map_err(|e| parse_err(e.to_string()))?;
+            match self.tz_massaging {
+                TzMassaging::LocalTz => naive_local_to_fo(date_str, &ndt)?,
+                _ => ndt.and_utc().fixed_offset(),
+            }
+        };
+
+        Ok(fixed_offset_to_time_ms(&dtfo))
+    }
+
+    // How `date_str` deviates from the resolved shape, None if it conforms
+    // and only its values are invalid. Only used once parsing has failed.
+    fn deviation(&self, date_str: &str) -> Option<TimeMsError> {
+        let deviation = |reason: String| {
+            Some(TimeMsError::parse(
+                date_str,
+                format!("deviates from the resolved format, {reason}"),
+            ))
+        };
+
+        let Some(resolved_separator) = self.branch.separator else {
+            if date_str.contains(['T', 't', ' ', ':']) {
+                return deviation("expected a date without a time".into());
+            }
+            return None;
+        };
+
+        let Ok(separator) = detect_separator(date_str) else {
+            return deviation(format!(
+                "expected a {} separator",
+                resolved_separator.name()
+            ));
+        };
+        if separator != resolved_separator {
+            return deviation(format!(
+                "expected a {} separator, found {}",
+                resolved_separator.name(),
+                separator.name()
+            ));
+        }
+
+        let resolved_basic = self.format.starts_with("%Y%m%d");
+        if has_basic_date(date_str) != resolved_basic {
+            let (expected, found) = if resolved_basic {
+                ("basic", "extended")
+            } else {
+                ("extended", "basic")
+            };
+            return deviation(format!(
+                "expected the ISO 8601 {expected} format, found the {found} format"
+            ));
+        }
+
+        let dt_str = normalize_decimal_comma(date_str);
+        if self.tz_massaging == TzMassaging::CondAddTzUtc {
+            match (self.branch.has_offset, has_tz_offset(&dt_str)) {
+                (true, false) => {
+                    return deviation("expected a time zone offset, found none".into())
+                }
+                (false, true) => {
+                    return deviation("expected no time zone offset, found one".into())
+                }
+                _ => {}
+            }
+        }
+        if self.tz_massaging == TzMassaging::RequireTz && !has_tz_offset(&dt_str) {
+            return Some(TimeMsError::MissingTimezone(date_str.to_string()));
+        }
+
+        let digits = fraction_digits(&dt_str);
+        if digits != self.fraction_digits {
+            return deviation(format!(
+                "expected {} fractional second digits, found {digits}",
+                self.fraction_digits
+            ));
+        }
+
+        let decimal_comma = matches!(dt_str, Cow::Owned(_));
+        if digits > 0 && !self.count_fraction_digits && decimal_comma != self.decimal_comma {
+            let mark = |comma| if comma { "comma" } else { "point" };
+            return deviation(format!(
+                "expected a decimal {}, found a decimal {}",
+                mark(self.decimal_comma),
+                mark(decimal_comma)
+            ));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dt_str_to_utc_time_ms;
+
+    #[test]
+    fn test_resolve_dt_format() {
+        use TzMassaging::*;
+
+        let cases = [
+            (
+                "1970-01-01T00:00:00",
+                CondAddTzUtc,
+                Some(DtSeparator::T),
+                false,
+                0,
+            ),
+            (
+                "1970-01-01 00:00:00.123Z",
+                CondAddTzUtc,
+                Some(DtSeparator::Space),
+                true,
+                3,
+            ),
+            (
+                "1970-01-01T00:00:00,5+01:00",
+                HasTz,
+                Some(DtSeparator::T),
+                true,
+                1,
+            ),
+            (
+                "19700101T000000.000001Z",
+                CondAddTzUtc,
+                Some(DtSeparator::T),
+                true,
+                6,
+            ),
+            (
+                "1970-01-01T00:00:00",
+                LocalTz,
+                Some(DtSeparator::T),
+                false,
+                0,
+            ),
+            ("1970-01-01", CondAddTzUtc, None, false, 0),
+        ];
+        for (sample, tz_massaging, separator, has_offset, fraction_digits) in cases {
+            let resolved = resolve_dt_format(sample, tz_massaging).unwrap();
+            dbg!(&resolved);
+            assert_eq!(resolved.separator(), separator, "{sample}");
+            assert_eq!(resolved.has_offset(), has_offset, "{sample}");
+            assert_eq!(resolved.fraction_digits(), fraction_digits, "{sample}");
+            assert_eq!(
+                resolved.convert(sample),
+                dt_str_to_utc_time_ms(sample, tz_massaging)
+            );
+        }
+
+        assert!(resolve_dt_format("bad", CondAddTzUtc).is_err());
+        assert!(resolve_dt_format("1970-01-01T00:00:00", HasTz).is_err());
+    }
+
+    #[test]
+    fn test_resolved_dt_format_matches_one_shot_parser() {
+        use TzMassaging::*;
+
+        let files: [(TzMassaging, &[&str]); 6] = [
+            (
+                CondAddTzUtc,
+                &[
+                    "2022-07-15T12:00:00.123",
+                    "1969-12-31T23:59:59.999",
+                    "2022-7-5T01:02:03.000",
+                ],
+            ),
+            (
+                CondAddTzUtc,
+                &[
+                    "2022-07-15 12:00:00-0700",
+                    "2022-07-15 12:00:00Z",
+                    "1970-01-01 00:00:00+01:00",
+                ],
+            ),
+            (
+                HasTz,
+                &["2022-07-15T12:00:00,123Z", "2022-07-15T12:00:00,456+0530"],
+            ),
+            (CondAddTzUtc, &["20220715T120000", "19691231T235959"]),
+            (LocalTz, &["2022-01-15T12:00:00", "2022-01-16T00:00:00"]),
+            (HasTz, &["2022-07-15", "1969-12-31"]),
+        ];
+        for (tz_massaging, rows) in files {
+            let resolved = resolve_dt_format(rows[0], tz_massaging).unwrap();
+            for row in rows {
+                let tms = resolved.convert(row);
+                assert!(tms.is_ok(), "{row}: {tms:?}");
+                assert_eq!(tms, dt_str_to_utc_time_ms(row, tz_massaging), "{row}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolved_dt_format_deviations() {
+        use TzMassaging::*;
+
+        let cases = [
+            (
+                "2022-07-15T12:00:00.123",
+                CondAddTzUtc,
+                "2022-07-15 12:00:00.123",
+                "expected a 'T' separator, found ' '",
+            ),
+            (
+                "2022-07-15T12:00:00.123",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00",
+                "expected 3 fractional second digits, found 0",
+            ),
+            (
+                "2022-07-15T12:00:00.123",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00.123Z",
+                "expected no time zone offset, found one",
+            ),
+            (
+                "2022-07-15T12:00:00.123-07:00",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00.123",
+                "expected a time zone offset, found none",
+            ),
+            (
+                "20220715T120000Z",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00Z",
+                "expected the ISO 8601 basic format, found the extended format",
+            ),
+            (
+                "2022-07-15",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00",
+                "expected a date without a time",
+            ),
+            (
+                "2022-07-15 12:00:00",
+                CondAddTzUtc,
+                "2022-07-15_12:00:00",
+                "expected a ' ' separator",
+            ),
+            (
+                "2022-07-15T12:00:00,123Z",
+                HasTz,
+                "2022-07-15T12:00:00.123Z",
+                "expected a decimal comma, found a decimal point",
+            ),
+            (
+                "2022-07-15T12:00:00.12",
+                CondAddTzUtc,
+                "2022-07-15T12:00:00.123",
+                "expected 2 fractional second digits, found 3",
+            ),
+        ];
+        for (sample, tz_massaging, row_2, deviation) in cases {
+            let resolved = resolve_dt_format(sample, tz_massaging).unwrap();
+            assert!(resolved.convert(sample).is_ok(), "{sample}");
+
+            let err = resolved.convert(row_2).unwrap_err();
+            dbg!(&err);
+            let msg = err.to_string();
+            assert!(msg.contains(row_2), "{msg}");
+            assert!(msg.contains(deviation), "{msg}");
+        }
+
+        // RequireTz rejects rows without a time zone
+        let resolved = resolve_dt_format("2022-07-15T12:00:00Z", RequireTz).unwrap();
+        assert!(resolved.has_offset());
+        assert_eq!(
+            resolved.convert("2022-07-15T12:00:01Z"),
+            Ok(1_657_886_401_000)
+        );
+        assert_eq!(
+            resolved.convert("2022-07-15T12:00:01"),
+            Err(TimeMsError::MissingTimezone(
+                "2022-07-15T12:00:01".to_string()
+            ))
+        );
+        assert_eq!(
+            resolve_dt_format("2022-07-15T12:00:00", RequireTz),
+            Err(TimeMsError::MissingTimezone(
+                "2022-07-15T12:00:00".to_string()
+            ))
+        );
+
+        // Conforming shape but an invalid value is a parse error
+        let resolved = resolve_dt_format("2022-07-15T12:00:00", CondAddTzUtc).unwrap();
+        let err = resolved.convert("2022-07-15T25:00:00").unwrap_err();
+        assert!(!err.to_string().contains("deviates"), "{err}");
+    }
+}
@@ -0,0 +1,225 @@
+//! Serde helpers for `#[serde(with = "...")]`
+
+/// (De)serialize a `DateTime<Utc>` as time_ms
+///
+/// Deserializing a time_ms out of the range of `DateTime<Utc>` is an
+/// error, not a panic.
+///
+/// # Example
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use serde::{Deserialize, Serialize};
+/// use time_ms_conversions::time_ms_to_utc;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Event {
+///     #[serde(with = "time_ms_conversions::serde::dt_as_time_ms")]
+///     at: DateTime<Utc>,
+/// }
+///
+/// let json = serde_json::to_string(&Event { at: time_ms_to_utc(1_657_886_400_123) }).unwrap();
+/// assert_eq!(json, r#"{"at":1657886400123}"#);
+///
+/// let event: Event = serde_json::from_str(&json).unwrap();
+/// assert_eq!(event.at, time_ms_to_utc(1_657_886_400_123));
+/// ```
+pub mod dt_as_time_ms {
+    use chrono::{DateTime, Utc};
+    use serde::de::{self, Unexpected};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::{try_time_ms_to_utc, utc_to_time_ms};
+
+    const EXPECTED: &str = "a time_ms in the range of DateTime<Utc>";
+
+    pub fn serialize<S: Serializer>(dt: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(utc_to_time_ms(dt))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        to_utc(i64::deserialize(deserializer)?)
+    }
+
+    fn to_utc<E: de::Error>(time_ms: i64) -> Result<DateTime<Utc>, E> {
+        try_time_ms_to_utc(time_ms)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(time_ms), &EXPECTED))
+    }
+
+    /// (De)serialize an `Option<DateTime<Utc>>` as an optional time_ms
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::{DateTime, Utc};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Event {
+    ///     #[serde(with = "time_ms_conversions::serde::dt_as_time_ms::option")]
+    ///     ended: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// let event: Event = serde_json::from_str(r#"{"ended":null}"#).unwrap();
+    /// assert_eq!(event.ended, None);
+    /// assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"ended":null}"#);
+    /// ```
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use crate::utc_to_time_ms;
+
+        pub fn serialize<S: Serializer>(
+            dt: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match dt {
+                Some(dt) => serializer.serialize_some(&utc_to_time_ms(dt)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            Option::<i64>::deserialize(deserializer)?
+                .map(super::to_utc)
+                .transpose()
+        }
+    }
+}
+
+/// (De)serialize an i64 time_ms as an RFC 3339 string in UTC with
+/// milli-seconds, "2022-07-15T12:00:00.123Z", as [`crate::time_ms_to_utc_z_string`]
+/// formats it
+///
+/// Deserializing requires a time zone, a string without one is an error
+/// reporting the string.
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Event {
+///     #[serde(with = "time_ms_conversions::serde::time_ms_as_rfc3339")]
+///     at: i64,
+/// }
+///
+/// let json = serde_json::to_string(&Event { at: 1_657_886_400_123 }).unwrap();
+/// assert_eq!(json, r#"{"at":"2022-07-15T12:00:00.123Z"}"#);
+///
+/// let event: Event = serde_json::from_str(r#"{"at":"2022-07-15T05:00:00.123-07:00"}"#).unwrap();
+/// assert_eq!(event.at, 1_657_886_400_123);
+/// ```
+pub mod time_ms_as_rfc3339 {
+    use chrono::SecondsFormat;
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    use crate::{dt_str_to_utc_time_ms, try_time_ms_to_utc, TzMassaging};
+
+    pub fn serialize<S: Serializer>(time_ms: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        let dt = try_time_ms_to_utc(*time_ms).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        let dt_str = String::deserialize(deserializer)?;
+        dt_str_to_utc_time_ms(&dt_str, TzMassaging::HasTz).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::serde::{Deserialize, Serialize};
+    use chrono::{DateTime, Utc};
+
+    use crate::time_ms_to_utc;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "crate::serde::dt_as_time_ms")]
+        at: DateTime<Utc>,
+
+        #[serde(with = "crate::serde::dt_as_time_ms::option")]
+        ended: Option<DateTime<Utc>>,
+    }
+
+    #[test]
+    fn test_dt_as_time_ms_round_trip() {
+        for (tms, json) in [
+            (1_657_886_400_123, r#"{"at":1657886400123,"ended":null}"#),
+            (0, r#"{"at":0,"ended":null}"#),
+            (-1, r#"{"at":-1,"ended":null}"#),
+        ] {
+            let event = Event {
+                at: time_ms_to_utc(tms),
+                ended: None,
+            };
+            assert_eq!(serde_json::to_string(&event).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Event>(json).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn test_dt_as_time_ms_option() {
+        let json = r#"{"at":-1500,"ended":-1}"#;
+        let event: Event = serde_json::from_str(json).unwrap();
+        assert_eq!(event.at, time_ms_to_utc(-1500));
+        assert_eq!(event.ended, Some(time_ms_to_utc(-1)));
+        assert_eq!(serde_json::to_string(&event).unwrap(), json);
+    }
+
+    #[test]
+    fn test_dt_as_time_ms_out_of_range() {
+        for json in [
+            r#"{"at":9223372036854775807,"ended":null}"#,
+            r#"{"at":0,"ended":-9223372036854775808}"#,
+        ] {
+            let err = serde_json::from_str::<Event>(json).unwrap_err();
+            dbg!(&err);
+            assert!(err.to_string().contains("DateTime<Utc>"));
+        }
+
+        // Not a time_ms
+        assert!(serde_json::from_str::<Event>(r#"{"at":"0","ended":null}"#).is_err());
+        assert!(serde_json::from_str::<Event>(r#"{"at":1.5,"ended":null}"#).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        #[serde(with = "crate::serde::time_ms_as_rfc3339")]
+        at: i64,
+    }
+
+    #[test]
+    fn test_time_ms_as_rfc3339_round_trip() {
+        for (tms, json) in [
+            (1_657_886_400_123, r#"{"at":"2022-07-15T12:00:00.123Z"}"#),
+            (0, r#"{"at":"1970-01-01T00:00:00.000Z"}"#),
+            (-1, r#"{"at":"1969-12-31T23:59:59.999Z"}"#),
+        ] {
+            let row = Row { at: tms };
+            assert_eq!(serde_json::to_string(&row).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Row>(json).unwrap(), row);
+        }
+
+        // Any offset is accepted
+        let row: Row = serde_json::from_str(r#"{"at":"1969-12-31 16:00:00-0800"}"#).unwrap();
+        assert_eq!(row.at, 0);
+    }
+
+    #[test]
+    fn test_time_ms_as_rfc3339_errors() {
+        let err = serde_json::from_str::<Row>(r#"{"at":"2022-07-15T12:00:00.123"}"#).unwrap_err();
+        dbg!(&err);
+        assert!(err.to_string().contains("\"2022-07-15T12:00:00.123\""));
+
+        assert!(serde_json::from_str::<Row>(r#"{"at":"not a time"}"#).is_err());
+        assert!(serde_json::from_str::<Row>(r#"{"at":0}"#).is_err());
+
+        // Out of the range of DateTime<Utc>
+        assert!(serde_json::to_string(&Row { at: i64::MAX }).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+/// Return `n` evenly spaced time_ms values from `start_ms` to `end_ms` inclusive.
+///
+/// Intermediate values are rounded to the nearest milli-second, with
+/// halves rounded up, and the endpoints are always exact. When `n` is 1
+/// only `start_ms` is returned and when `n` is 0 the result is empty.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::linspace_time_ms;
+///
+/// assert_eq!(linspace_time_ms(0, 1000, 3), vec![0, 500, 1000]);
+/// assert_eq!(linspace_time_ms(0, 1000, 4), vec![0, 333, 667, 1000]);
+/// ```
+pub fn linspace_time_ms(start_ms: i64, end_ms: i64, n: usize) -> Vec<i64> {
+    match n {
+        0 => vec![],
+        1 => vec![start_ms],
+        _ => {
+            // i128 so the span and products can't overflow
+            let span = end_ms as i128 - start_ms as i128;
+            let den = (n - 1) as i128;
+            (0..n)
+                .map(|i| {
+                    let num = span * i as i128;
+                    let offset = (2 * num + den).div_euclid(2 * den);
+                    (start_ms as i128 + offset) as i64
+                })
+                .collect()
+        }
+    }
+}
+
+/// Lazily step from `start_ms` toward `end_ms` by `step_ms`, half-open
+/// `[start_ms, end_ms)`.
+///
+/// A negative `step_ms` steps down through `(end_ms, start_ms]`. The
+/// iterator is empty if `step_ms` is 0, `start_ms == end_ms` or it steps
+/// away from `end_ms`, and never overflows at i64::MIN and i64::MAX.
+///
+/// The number of steps is computed up front so [`TimeMsSteps`] is an
+/// `ExactSizeIterator` and collecting it allocates once.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_range;
+///
+/// assert_eq!(time_ms_range(0, 3000, 1000).collect::<Vec<_>>(), vec![0, 1000, 2000]);
+/// assert_eq!(time_ms_range(3000, 0, -1000).collect::<Vec<_>>(), vec![3000, 2000, 1000]);
+/// assert_eq!(time_ms_range(0, 3000, 0).count(), 0);
+/// assert_eq!(time_ms_range(0, 3001, 1000).len(), 4);
+/// ```
+pub fn time_ms_range(start_ms: i64, end_ms: i64, step_ms: i64) -> TimeMsSteps {
+    // i128 so the span can't overflow
+    let span = end_ms as i128 - start_ms as i128;
+    let step = step_ms as i128;
+    let remaining = if step == 0 || span.signum() != step.signum() {
+        0
+    } else {
+        // Rounded up so a partial last step is included
+        ((span.abs() + step.abs() - 1) / step.abs()) as u64
+    };
+
+    TimeMsSteps {
+        next_ms: start_ms,
+        step_ms,
+        remaining,
+    }
+}
+
+/// The iterator returned by [`time_ms_range`]
+///
+/// Named `TimeMsSteps` as [`TimeMsRange`](crate::TimeMsRange) is the
+/// half-open interval type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeMsSteps {
+    next_ms: i64,
+    step_ms: i64,
+    remaining: u64,
+}
+
+impl Iterator for TimeMsSteps {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let time_ms = self.next_ms;
+        self.remaining -= 1;
+
+        // Only the step after the last value could overflow
+        if self.remaining > 0 {
+            self.next_ms += self.step_ms;
+        }
+
+        Some(time_ms)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(len) => (len, Some(len)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// The length is exact unless it exceeds usize::MAX, possible only on
+/// targets with a usize smaller than 64 bits
+impl ExactSizeIterator for TimeMsSteps {}
+
+impl std::iter::FusedIterator for TimeMsSteps {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_linspace_time_ms() {
+        assert_eq!(linspace_time_ms(0, 1000, 3), vec![0, 500, 1000]);
+        assert_eq!(linspace_time_ms(0, 1000, 1), vec![0]);
+        assert_eq!(linspace_time_ms(0, 1000, 0), Vec::<i64>::new());
+        assert_eq!(linspace_time_ms(0, 1000, 2), vec![0, 1000]);
+        assert_eq!(linspace_time_ms(0, 1000, 4), vec![0, 333, 667, 1000]);
+        assert_eq!(linspace_time_ms(0, 1, 3), vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn test_linspace_time_ms_descending_and_negative() {
+        assert_eq!(linspace_time_ms(1000, 0, 3), vec![1000, 500, 0]);
+        assert_eq!(
+            linspace_time_ms(-1000, 1000, 5),
+            vec![-1000, -500, 0, 500, 1000]
+        );
+        assert_eq!(linspace_time_ms(-3, 0, 3), vec![-3, -1, 0]);
+    }
+
+    #[test]
+    fn test_linspace_time_ms_extremes() {
+        let v = linspace_time_ms(i64::MIN, i64::MAX, 3);
+        assert_eq!(v, vec![i64::MIN, 0, i64::MAX]);
+    }
+
+    #[test]
+    fn test_time_ms_range_ascending() {
+        let v: Vec<i64> = time_ms_range(0, 5000, 1000).collect();
+        assert_eq!(v, vec![0, 1000, 2000, 3000, 4000]);
+
+        // end isn't a multiple of step
+        let v: Vec<i64> = time_ms_range(-1500, 1000, 1000).collect();
+        assert_eq!(v, vec![-1500, -500, 500]);
+
+        let v: Vec<i64> = time_ms_range(i64::MAX - 2, i64::MAX, 1).collect();
+        assert_eq!(v, vec![i64::MAX - 2, i64::MAX - 1]);
+
+        // Stops rather than overflowing
+        let v: Vec<i64> = time_ms_range(i64::MAX - 5, i64::MAX, 3).collect();
+        assert_eq!(v, vec![i64::MAX - 5, i64::MAX - 2]);
+    }
+
+    #[test]
+    fn test_time_ms_range_len() {
+        assert_eq!(time_ms_range(0, 5000, 1000).len(), 5);
+        assert_eq!(time_ms_range(0, 5001, 1000).len(), 6);
+        assert_eq!(time_ms_range(5000, 0, -1000).len(), 5);
+        assert_eq!(time_ms_range(0, 0, 1000).len(), 0);
+        assert_eq!(time_ms_range(0, 1000, 0).len(), 0);
+        assert_eq!(time_ms_range(0, 1000, -1).len(), 0);
+        assert_eq!(time_ms_range(1000, 0, 1).len(), 0);
+        assert_eq!(time_ms_range(i64::MIN, i64::MAX, 1).len() as u64, u64::MAX);
+        assert_eq!(time_ms_range(i64::MIN, i64::MAX, i64::MAX).len(), 3);
+
+        let mut steps = time_ms_range(0, 3000, 1000);
+        assert_eq!(steps.len(), 3);
+        steps.next();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps.size_hint(), (2, Some(2)));
+        steps.by_ref().for_each(drop);
+        assert_eq!(steps.len(), 0);
+        assert_eq!(steps.next(), None);
+
+        let v: Vec<i64> = time_ms_range(0, 10_000, 7).collect();
+        assert_eq!(v.len(), 1429);
+        assert_eq!(v.capacity(), 1429);
+    }
+
+    #[test]
+    fn test_time_ms_range_descending() {
+        let v: Vec<i64> = time_ms_range(5000, 0, -1000).collect();
+        assert_eq!(v, vec![5000, 4000, 3000, 2000, 1000]);
+
+        let v: Vec<i64> = time_ms_range(1000, -1500, -1000).collect();
+        assert_eq!(v, vec![1000, 0, -1000]);
+
+        let v: Vec<i64> = time_ms_range(i64::MIN + 5, i64::MIN, -3).collect();
+        assert_eq!(v, vec![i64::MIN + 5, i64::MIN + 2]);
+    }
+
+    #[test]
+    fn test_time_ms_range_empty() {
+        assert_eq!(time_ms_range(0, 5000, 0).count(), 0);
+        assert_eq!(time_ms_range(5000, 5000, 1000).count(), 0);
+        assert_eq!(time_ms_range(5000, 5000, -1000).count(), 0);
+
+        // Stepping away from end
+        assert_eq!(time_ms_range(0, 5000, -1000).count(), 0);
+        assert_eq!(time_ms_range(5000, 0, 1000).count(), 0);
+    }
+
+    #[test]
+    fn test_time_ms_range_is_lazy() {
+        // Would never finish if it wasn't lazy
+        let v: Vec<i64> = time_ms_range(0, i64::MAX, 1).skip(2).take(3).collect();
+        assert_eq!(v, vec![2, 3, 4]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{split_mix64, try_time_ms_to_utc, TimeMsRange, MS_PER_DAY};
+
+// The random offsets are 10 to 100 years of whole days
+const MIN_SHIFT_DAYS: u64 = 10 * 365;
+const MAX_SHIFT_DAYS: u64 = 100 * 365;
+
+/// A constant offset added to every time_ms of a dataset, so it can be
+/// shared without revealing the real event times and reversed later with
+/// [`TimeShift::invert`].
+///
+/// Intervals between events are exactly preserved. The shift wraps
+/// rather than overflowing so applying then inverting is always the
+/// identity, see [`TimeShift::random_for_range`] for a shift that
+/// doesn't wrap the dataset.
+///
+/// With the `serde` feature a `TimeShift` can be (de)serialized so it can
+/// be stored, securely, alongside the shifted dataset.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{TimeMsRange, TimeShift};
+///
+/// let mut times = [1_657_886_400_123, 1_657_886_401_000, -1];
+/// let shift = TimeShift::random_for_range(&TimeMsRange::new(-1, 1_657_886_401_001), 42);
+/// shift.apply_slice(&mut times);
+/// assert_eq!(times[1] - times[0], 877);
+///
+/// shift.invert().apply_slice(&mut times);
+/// assert_eq!(times, [1_657_886_400_123, 1_657_886_401_000, -1]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct TimeShift {
+    offset_ms: i64,
+}
+
+impl TimeShift {
+    /// A shift of `offset_ms`
+    pub fn new(offset_ms: i64) -> Self {
+        Self { offset_ms }
+    }
+
+    /// A pseudo random shift of 10 to 100 years, forward or backward,
+    /// chosen from `rng_seed` so the same seed and range always give the
+    /// same shift.
+    ///
+    /// The shift is a whole number of days so the time of day of each
+    /// event, and any daily pattern, is preserved. It's chosen so the
+    /// shifted `range` remains in the range of `DateTime<Utc>` and can
+    /// still be formatted.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is so large that no 10 to 100 year shift of it stays
+    /// in the range of `DateTime<Utc>`, about +/-262,000 years
+    pub fn random_for_range(range: &TimeMsRange, rng_seed: u64) -> Self {
+        let mut state = rng_seed;
+        let r = split_mix64(&mut state);
+        let days = MIN_SHIFT_DAYS + r % (MAX_SHIFT_DAYS - MIN_SHIFT_DAYS + 1);
+        let offset_ms = days as i64 * MS_PER_DAY;
+
+        let fits = |offset_ms: i64| {
+            [range.start_ms, range.end_ms].iter().all(|&time_ms| {
+                time_ms
+                    .checked_add(offset_ms)
+                    .is_some_and(|shifted| try_time_ms_to_utc(shifted).is_ok())
+            })
+        };
+
+        // Prefer the direction from the seed but use the other if the
+        // shifted range wouldn't fit
+        let forward_first = split_mix64(&mut state) & 1 == 0;
+        let candidates = if forward_first {
+            [offset_ms, -offset_ms]
+        } else {
+            [-offset_ms, offset_ms]
+        };
+        let offset_ms = candidates
+            .into_iter()
+            .find(|&o| fits(o))
+            .unwrap_or_else(|| {
+                panic!("TimeShift::random_for_range: {range:?} is too large to shift")
+            });
+
+        Self { offset_ms }
+    }
+
+    /// The milli-seconds added by [`TimeShift::apply`]
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms
+    }
+
+    /// Shift `time_ms`, wrapping at i64::MIN and i64::MAX
+    pub fn apply(&self, time_ms: i64) -> i64 {
+        time_ms.wrapping_add(self.offset_ms)
+    }
+
+    /// [`TimeShift::apply`] to each of `times` in place
+    pub fn apply_slice(&self, times: &mut [i64]) {
+        for time_ms in times.iter_mut() {
+            *time_ms = self.apply(*time_ms);
+        }
+    }
+
+    /// The shift that reverses this one
+    pub fn invert(&self) -> Self {
+        Self {
+            offset_ms: self.offset_ms.wrapping_neg(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const YEAR: i64 = 365 * MS_PER_DAY;
+
+    #[test]
+    fn test_time_shift_random_for_range() {
+        let range = TimeMsRange::new(1_600_000_000_000, 1_700_000_000_000);
+        for seed in 0..100 {
+            let shift = TimeShift::random_for_range(&range, seed);
+            assert_eq!(shift, TimeShift::random_for_range(&range, seed));
+
+            let offset = shift.offset_ms();
+            assert_eq!(offset % MS_PER_DAY, 0, "{seed}");
+            assert!((10 * YEAR..=100 * YEAR).contains(&offset.abs()), "{seed}");
+        }
+
+        // Both directions are chosen
+        let offsets: Vec<i64> = (0..100)
+            .map(|seed| TimeShift::random_for_range(&range, seed).offset_ms())
+            .collect();
+        assert!(offsets.iter().any(|&o| o > 0));
+        assert!(offsets.iter().any(|&o| o < 0));
+        assert_ne!(offsets[0], offsets[1]);
+    }
+
+    #[test]
+    fn test_time_shift_random_for_range_stays_in_range() {
+        // 2 years from the end of DateTime<Utc> so only backward fits
+        let max_ms = crate::utc_to_time_ms(&chrono::DateTime::<chrono::Utc>::MAX_UTC);
+        let range = TimeMsRange::new(max_ms - 2 * YEAR, max_ms);
+        for seed in 0..100 {
+            let shift = TimeShift::random_for_range(&range, seed);
+            assert!(shift.offset_ms() < 0, "{seed}");
+            assert!(try_time_ms_to_utc(shift.apply(range.end_ms)).is_ok());
+        }
+
+        let min_ms = crate::utc_to_time_ms(&chrono::DateTime::<chrono::Utc>::MIN_UTC);
+        let range = TimeMsRange::new(min_ms, min_ms + 2 * YEAR);
+        for seed in 0..100 {
+            assert!(TimeShift::random_for_range(&range, seed).offset_ms() > 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "too large to shift")]
+    fn test_time_shift_random_for_range_too_large() {
+        TimeShift::random_for_range(&TimeMsRange::new(i64::MIN, i64::MAX), 0);
+    }
+
+    #[test]
+    fn test_time_shift_preserves_intervals() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut random_in =
+            |lo: i64, hi: i64| lo + (split_mix64(&mut state) % (hi - lo) as u64) as i64;
+        for seed in 0..200 {
+            let n = random_in(1, 50) as usize;
+            let original: Vec<i64> = (0..n)
+                .map(|_| random_in(-5_000_000_000_000, 5_000_000_000_000))
+                .collect();
+            let range = TimeMsRange::new(
+                *original.iter().min().unwrap(),
+                *original.iter().max().unwrap() + 1,
+            );
+            let shift = TimeShift::random_for_range(&range, seed);
+
+            let mut shifted = original.clone();
+            shift.apply_slice(&mut shifted);
+            for i in 0..n {
+                for j in 0..n {
+                    assert_eq!(shifted[i] - shifted[j], original[i] - original[j], "{seed}");
+                }
+            }
+
+            shift.invert().apply_slice(&mut shifted);
+            assert_eq!(shifted, original, "{seed}");
+        }
+    }
+
+    #[test]
+    fn test_time_shift_invert_is_identity() {
+        for offset_ms in [0, 1, -1, YEAR, -YEAR, i64::MAX, i64::MIN] {
+            let shift = TimeShift::new(offset_ms);
+            for time_ms in [0, -1, 1, -1_657_886_400_123, i64::MIN, i64::MAX] {
+                assert_eq!(shift.invert().apply(shift.apply(time_ms)), time_ms);
+                assert_eq!(shift.apply(shift.invert().apply(time_ms)), time_ms);
+            }
+        }
+        assert_eq!(TimeShift::new(-5).invert(), TimeShift::new(5));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_time_shift_serde() {
+        let shift = TimeShift::new(-315_360_000_000);
+        let json = serde_json::to_string(&shift).unwrap();
+        assert_eq!(json, r#"{"offset_ms":-315360000000}"#);
+        assert_eq!(serde_json::from_str::<TimeShift>(&json).unwrap(), shift);
+    }
+}
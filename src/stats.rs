@@ -0,0 +1,841 @@
+use std::fmt;
+
+use crate::{try_time_ms_to_utc, TimeMsError};
+
+// Divide rounding to nearest with halves toward positive infinity, den > 0
+fn div_round_half_up(num: i128, den: i128) -> i128 {
+    (2 * num + den).div_euclid(2 * den)
+}
+
+/// Mean of `times`, None if empty.
+///
+/// The sum is computed in i128 so it can't overflow and the result is
+/// rounded to the nearest milli-second with halves rounded up.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::mean_time_ms;
+///
+/// assert_eq!(mean_time_ms(&[1000, 2000, 4000]), Some(2333));
+/// assert_eq!(mean_time_ms(&[i64::MAX, i64::MAX]), Some(i64::MAX));
+/// assert_eq!(mean_time_ms(&[]), None);
+/// ```
+pub fn mean_time_ms(times: &[i64]) -> Option<i64> {
+    if times.is_empty() {
+        return None;
+    }
+    let sum: i128 = times.iter().map(|&t| t as i128).sum();
+
+    Some(div_round_half_up(sum, times.len() as i128) as i64)
+}
+
+/// Median of `times`, None if empty.
+///
+/// `times` doesn't need to be sorted and isn't modified, a sorted copy
+/// is made. For an even count the median is the mean of the two middle
+/// values rounded to the nearest milli-second with halves rounded up.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::median_time_ms;
+///
+/// let times = [3000, 1000, 2000];
+/// assert_eq!(median_time_ms(&times), Some(2000));
+/// assert_eq!(times, [3000, 1000, 2000]);
+/// assert_eq!(median_time_ms(&[0, 1001]), Some(501));
+/// ```
+pub fn median_time_ms(times: &[i64]) -> Option<i64> {
+    if times.is_empty() {
+        return None;
+    }
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        return Some(sorted[mid]);
+    }
+
+    Some(div_round_half_up(sorted[mid - 1] as i128 + sorted[mid] as i128, 2) as i64)
+}
+
+/// The time_ms at quantile `q` of the range spanned by the sorted bucket
+/// `edges`, linearly interpolated from the first edge, at `q == 0.0`, to
+/// the last, at `q == 1.0`, and rounded to the nearest milli-second with
+/// halves rounded up. None if there are fewer than two edges.
+///
+/// `q` is clamped to `[0.0, 1.0]` so the result is always within the
+/// edges.
+///
+/// # Panics
+///
+/// If `q` is NaN or `edges` isn't sorted in ascending order
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::interpolate_between_edges;
+///
+/// let edges = [0, 1000, 3000];
+/// assert_eq!(interpolate_between_edges(&edges, 0.5), Some(1500));
+/// assert_eq!(interpolate_between_edges(&edges, 1.0), Some(3000));
+/// assert_eq!(interpolate_between_edges(&[1000], 0.5), None);
+/// ```
+pub fn interpolate_between_edges(edges: &[i64], q: f64) -> Option<i64> {
+    assert!(!q.is_nan(), "interpolate_between_edges: q must not be NaN");
+    assert!(
+        edges.windows(2).all(|pair| pair[0] <= pair[1]),
+        "interpolate_between_edges: edges must be sorted"
+    );
+    let (&first, &last) = (edges.first()?, edges.last()?);
+    if edges.len() < 2 {
+        return None;
+    }
+
+    // i128 so the span of i64::MIN..i64::MAX doesn't overflow
+    let span = last as i128 - first as i128;
+    let offset = (q.clamp(0.0, 1.0) * span as f64 + 0.5).floor() as i128;
+    Some((first as i128 + offset.min(span)) as i64)
+}
+
+/// Fraction of `times` within the half-open `window`, `start..end`,
+/// 0.0 if `times` is empty.
+///
+/// # Panics
+///
+/// If the window's start > end
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::fraction_in_window;
+///
+/// assert_eq!(fraction_in_window(&[1000, 2000, 3000, 4000], (2000, 4000)), 0.5);
+/// assert_eq!(fraction_in_window(&[], (0, 1000)), 0.0);
+/// ```
+pub fn fraction_in_window(times: &[i64], window: (i64, i64)) -> f64 {
+    let (start, end) = window;
+    assert!(
+        start <= end,
+        "fraction_in_window: window start must be <= end"
+    );
+    if times.is_empty() {
+        return 0.0;
+    }
+    let inside = times.iter().filter(|&&t| start <= t && t < end).count();
+
+    inside as f64 / times.len() as f64
+}
+
+/// Weighted mean of `times`.
+///
+/// Each weight must be non-negative and they must not all be zero.
+/// The offsets from the first time are weighted so values near i64::MAX
+/// or i64::MIN don't overflow and the result is rounded to the nearest
+/// milli-second.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{weighted_mean_time_ms, TimeMsError};
+///
+/// assert_eq!(weighted_mean_time_ms(&[1000, 2000], &[3.0, 1.0]), Ok(1250));
+/// assert_eq!(
+///     weighted_mean_time_ms(&[1000, 2000], &[1.0]),
+///     Err(TimeMsError::LengthMismatch { left: 2, right: 1 })
+/// );
+/// ```
+pub fn weighted_mean_time_ms(times: &[i64], weights: &[f64]) -> Result<i64, TimeMsError> {
+    if times.len() != weights.len() {
+        return Err(TimeMsError::LengthMismatch {
+            left: times.len(),
+            right: weights.len(),
+        });
+    }
+    if times.is_empty() {
+        return Err(TimeMsError::EmptyInput);
+    }
+    if let Some(&w) = weights
+        .iter()
+        .find(|w| !w.is_finite() || w.is_sign_negative() && **w != 0.0)
+    {
+        return Err(TimeMsError::InvalidWeight(w));
+    }
+    let total_weight: f64 = weights.iter().sum();
+    if total_weight == 0.0 {
+        return Err(TimeMsError::ZeroTotalWeight);
+    }
+
+    let base = times[0] as i128;
+    let weighted_offsets: f64 = times
+        .iter()
+        .zip(weights)
+        .map(|(&t, &w)| (t as i128 - base) as f64 * w)
+        .sum();
+    let offset = (weighted_offsets / total_weight).round() as i128;
+
+    // The mean is between the min and max times so this is in range
+    Ok((base + offset).clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+}
+
+/// Smooth `times` with an exponential moving average, for example to
+/// reduce the jitter of noisy sample times.
+///
+/// Each result is `alpha * times[i] + (1 - alpha) * previous result`,
+/// rounded to the nearest milli-second, with the first result being
+/// `times[0]`. An `alpha` of 1.0 is no smoothing and smaller values
+/// smooth more, lagging behind changes. The averaging is done relative to
+/// `times[0]` with i128 offsets so values anywhere in the i64 range can't
+/// overflow or lose precision.
+///
+/// # Panics
+///
+/// If `alpha` isn't in `(0, 1]`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::ema_smooth_time_ms;
+///
+/// assert_eq!(ema_smooth_time_ms(&[0, 1000, 2000], 1.0), vec![0, 1000, 2000]);
+/// assert_eq!(ema_smooth_time_ms(&[0, 1000, 2000], 0.5), vec![0, 500, 1250]);
+/// ```
+pub fn ema_smooth_time_ms(times: &[i64], alpha: f64) -> Vec<i64> {
+    assert!(
+        alpha > 0.0 && alpha <= 1.0,
+        "ema_smooth_time_ms: alpha must be in (0, 1]"
+    );
+    let Some(&first) = times.first() else {
+        return vec![];
+    };
+
+    let base = first as i128;
+    let mut ema = 0.0;
+    times
+        .iter()
+        .map(|&t| {
+            ema += alpha * ((t as i128 - base) as f64 - ema);
+            (base + ema.round() as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        })
+        .collect()
+}
+
+/// How [`detect_outliers`] decides a time_ms is an outlier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutlierMethod {
+    /// An outlier is more than `k` median absolute deviations (MAD) from
+    /// the median. The raw MAD is used, it's not scaled by 1.4826. When
+    /// more than half the values are identical the MAD is 0 and every value
+    /// not equal to the median is an outlier, so all identical values
+    /// have no outliers.
+    MadThreshold(f64),
+
+    /// An outlier is more than `tolerance_ms` from `center`
+    FixedWindow { center: i64, tolerance_ms: i64 },
+}
+
+// Median of i128 values, the mean of the middle two for an even count
+fn median_i128(values: &mut [i128]) -> i128 {
+    let len = values.len();
+    let (lower, &mut upper, _) = values.select_nth_unstable(len / 2);
+    if len % 2 == 1 {
+        return upper;
+    }
+    let lower = *lower.iter().max().expect("even count has a lower half");
+
+    div_round_half_up(lower + upper, 2)
+}
+
+/// Flag the outliers in `times`, `true` at the index of each outlier.
+///
+/// The median and deviations are computed with i128 so values anywhere in
+/// the i64 range can't overflow.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{detect_outliers, OutlierMethod};
+///
+/// let times = [1000, 1001, 1002, 1003, 99_999];
+/// assert_eq!(
+///     detect_outliers(&times, OutlierMethod::MadThreshold(3.0)),
+///     vec![false, false, false, false, true]
+/// );
+/// ```
+pub fn detect_outliers(times: &[i64], method: OutlierMethod) -> Vec<bool> {
+    if times.is_empty() {
+        return vec![];
+    }
+
+    match method {
+        OutlierMethod::MadThreshold(k) => {
+            let mut scratch: Vec<i128> = times.iter().map(|&t| t as i128).collect();
+            let median = median_i128(&mut scratch);
+
+            let deviation = |t: i64| (t as i128 - median).abs();
+            let mut deviations: Vec<i128> = times.iter().map(|&t| deviation(t)).collect();
+            let mad = median_i128(&mut deviations);
+
+            let limit = k * mad as f64;
+            times.iter().map(|&t| deviation(t) as f64 > limit).collect()
+        }
+        OutlierMethod::FixedWindow {
+            center,
+            tolerance_ms,
+        } => times
+            .iter()
+            .map(|&t| (t as i128 - center as i128).abs() > tolerance_ms as i128)
+            .collect(),
+    }
+}
+
+/// The values of `times` that [`detect_outliers`] doesn't flag, in order
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{filter_outliers, OutlierMethod};
+///
+/// let method = OutlierMethod::FixedWindow { center: 1000, tolerance_ms: 10 };
+/// assert_eq!(filter_outliers(&[995, 1020, 1010], method), vec![995, 1010]);
+/// ```
+pub fn filter_outliers(times: &[i64], method: OutlierMethod) -> Vec<i64> {
+    times
+        .iter()
+        .zip(detect_outliers(times, method))
+        .filter(|(_, outlier)| !outlier)
+        .map(|(&t, _)| t)
+        .collect()
+}
+
+/// Running statistics of a stream of time_ms, such as a column of a
+/// large file, without keeping the values
+///
+/// Stats of shards can be combined with [`TimeMsStats::merge`].
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::TimeMsStats;
+///
+/// let mut stats = TimeMsStats::default();
+/// for tms in [2000, 1000, 3000] {
+///     stats.observe(tms);
+/// }
+/// assert_eq!((stats.min(), stats.max()), (Some(1000), Some(3000)));
+/// assert_eq!((stats.first(), stats.last()), (Some(2000), Some(3000)));
+/// assert_eq!(stats.span_ms(), Some(2000));
+/// assert_eq!(
+///     stats.to_string(),
+///     "count=3 min=1970-01-01T00:00:01.000Z max=1970-01-01T00:00:03.000Z"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeMsStats {
+    count: u64,
+
+    // Only meaningful if count > 0
+    min: i64,
+    max: i64,
+    first: i64,
+    last: i64,
+}
+
+impl TimeMsStats {
+    /// Add `time_ms` to the statistics
+    pub fn observe(&mut self, time_ms: i64) {
+        if self.count == 0 {
+            self.min = time_ms;
+            self.max = time_ms;
+            self.first = time_ms;
+        } else {
+            self.min = self.min.min(time_ms);
+            self.max = self.max.max(time_ms);
+        }
+        self.last = time_ms;
+        self.count += 1;
+    }
+
+    /// Combine the statistics of `other`, treated as observed after those
+    /// of `self`, as if all of the values were observed by `self`.
+    ///
+    /// Merging is associative so shards can be merged in any grouping, the
+    /// first and last are only correct if the shards are merged in order.
+    pub fn merge(&mut self, other: &TimeMsStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.last = other.last;
+        self.count += other.count;
+    }
+
+    /// The number of values observed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn if_observed(&self, value: i64) -> Option<i64> {
+        (self.count > 0).then_some(value)
+    }
+
+    /// The smallest value, None if nothing was observed
+    pub fn min(&self) -> Option<i64> {
+        self.if_observed(self.min)
+    }
+
+    /// The largest value, None if nothing was observed
+    pub fn max(&self) -> Option<i64> {
+        self.if_observed(self.max)
+    }
+
+    /// The first value observed
+    pub fn first(&self) -> Option<i64> {
+        self.if_observed(self.first)
+    }
+
+    /// The last value observed
+    pub fn last(&self) -> Option<i64> {
+        self.if_observed(self.last)
+    }
+
+    /// `max - min`, saturating at i64::MAX, None if nothing was observed
+    pub fn span_ms(&self) -> Option<i64> {
+        self.if_observed(self.max.saturating_sub(self.min))
+    }
+}
+
+impl fmt::Display for TimeMsStats {
+    /// "count=3 min=1970-01-01T00:00:01.000Z max=1970-01-01T00:00:03.000Z",
+    /// a min or max out of the range of DateTime<Utc> is shown as a number
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count={}", self.count)?;
+        if self.count == 0 {
+            return Ok(());
+        }
+        let rfc3339 = |time_ms: i64| match try_time_ms_to_utc(time_ms) {
+            Ok(dt) => dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            Err(_) => time_ms.to_string(),
+        };
+        write!(f, " min={} max={}", rfc3339(self.min), rfc3339(self.max))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mean_time_ms() {
+        assert_eq!(mean_time_ms(&[]), None);
+        assert_eq!(mean_time_ms(&[42]), Some(42));
+        assert_eq!(mean_time_ms(&[0, 1]), Some(1));
+        assert_eq!(mean_time_ms(&[-1, 0]), Some(0));
+        assert_eq!(mean_time_ms(&[-2, -1]), Some(-1));
+        assert_eq!(mean_time_ms(&[1000, 2000, 4000]), Some(2333));
+    }
+
+    #[test]
+    fn test_mean_time_ms_no_overflow() {
+        assert_eq!(mean_time_ms(&[i64::MAX; 3]), Some(i64::MAX));
+        assert_eq!(mean_time_ms(&[i64::MIN; 3]), Some(i64::MIN));
+        assert_eq!(mean_time_ms(&[i64::MAX, i64::MAX - 2]), Some(i64::MAX - 1));
+        assert_eq!(mean_time_ms(&[i64::MIN, i64::MAX]), Some(0));
+    }
+
+    #[test]
+    fn test_median_time_ms() {
+        assert_eq!(median_time_ms(&[]), None);
+        assert_eq!(median_time_ms(&[5]), Some(5));
+        assert_eq!(median_time_ms(&[3000, 1000, 2000]), Some(2000));
+        assert_eq!(median_time_ms(&[4, 1, 3, 2]), Some(3));
+        assert_eq!(median_time_ms(&[0, 1000]), Some(500));
+        assert_eq!(median_time_ms(&[-3, -2]), Some(-2));
+        assert_eq!(median_time_ms(&[1, 1, 1, 1000]), Some(1));
+    }
+
+    #[test]
+    fn test_median_time_ms_input_unchanged() {
+        let times = vec![5000, -1000, 3000, 0, 1000, 2000];
+        assert_eq!(median_time_ms(&times), Some(1500));
+        assert_eq!(times, [5000, -1000, 3000, 0, 1000, 2000]);
+    }
+
+    #[test]
+    fn test_median_time_ms_no_overflow() {
+        assert_eq!(median_time_ms(&[i64::MAX, i64::MAX]), Some(i64::MAX));
+        assert_eq!(median_time_ms(&[i64::MIN, i64::MIN]), Some(i64::MIN));
+        assert_eq!(median_time_ms(&[i64::MAX, i64::MIN]), Some(0));
+    }
+
+    #[test]
+    fn test_interpolate_between_edges() {
+        let edges = [0, 1000, 3000];
+        assert_eq!(interpolate_between_edges(&edges, 0.0), Some(0));
+        assert_eq!(interpolate_between_edges(&edges, 1.0), Some(3000));
+        assert_eq!(interpolate_between_edges(&edges, 0.5), Some(1500));
+        assert_eq!(interpolate_between_edges(&edges, 0.25), Some(750));
+        assert_eq!(interpolate_between_edges(&edges, 0.9), Some(2700));
+
+        // Rounded half up and clamped
+        assert_eq!(interpolate_between_edges(&[0, 3], 0.5), Some(2));
+        assert_eq!(interpolate_between_edges(&[-3, 0], 0.5), Some(-1));
+        assert_eq!(interpolate_between_edges(&edges, -1.0), Some(0));
+        assert_eq!(interpolate_between_edges(&edges, 2.0), Some(3000));
+
+        // Equal edges
+        assert_eq!(interpolate_between_edges(&[5, 5], 0.7), Some(5));
+    }
+
+    #[test]
+    fn test_interpolate_between_edges_too_few() {
+        assert_eq!(interpolate_between_edges(&[], 0.5), None);
+        assert_eq!(interpolate_between_edges(&[1000], 0.5), None);
+    }
+
+    #[test]
+    fn test_interpolate_between_edges_limits() {
+        let edges = [i64::MIN, i64::MAX];
+        assert_eq!(interpolate_between_edges(&edges, 0.0), Some(i64::MIN));
+        assert_eq!(interpolate_between_edges(&edges, 1.0), Some(i64::MAX));
+        assert_eq!(interpolate_between_edges(&edges, 0.5), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "interpolate_between_edges: edges must be sorted")]
+    fn test_interpolate_between_edges_unsorted() {
+        interpolate_between_edges(&[0, 3000, 1000], 0.5);
+    }
+
+    #[test]
+    fn test_fraction_in_window() {
+        let times = [1000, 2000, 3000, 4000];
+        let cases = [
+            // window, expected
+            ((0, 5000), 1.0),
+            ((1000, 4001), 1.0),
+            ((5000, 6000), 0.0),
+            ((0, 1000), 0.0),
+            ((2000, 4000), 0.5),
+            ((1000, 1001), 0.25),
+            ((2000, 2000), 0.0),
+            ((i64::MIN, i64::MAX), 1.0),
+        ];
+        for (window, expected) in cases {
+            assert_eq!(fraction_in_window(&times, window), expected, "{window:?}");
+        }
+
+        // The end is excluded, so i64::MAX is never within a window
+        assert_eq!(fraction_in_window(&[i64::MAX], (i64::MIN, i64::MAX)), 0.0);
+    }
+
+    #[test]
+    fn test_fraction_in_window_empty() {
+        assert_eq!(fraction_in_window(&[], (0, 1000)), 0.0);
+        assert_eq!(fraction_in_window(&[], (0, 0)), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction_in_window: window start must be <= end")]
+    fn test_fraction_in_window_reversed() {
+        fraction_in_window(&[1000], (2000, 1000));
+    }
+
+    #[test]
+    fn test_weighted_mean_time_ms() {
+        assert_eq!(weighted_mean_time_ms(&[1000], &[0.5]), Ok(1000));
+        assert_eq!(weighted_mean_time_ms(&[1000, 2000], &[1.0, 1.0]), Ok(1500));
+        assert_eq!(weighted_mean_time_ms(&[1000, 2000], &[3.0, 1.0]), Ok(1250));
+        assert_eq!(weighted_mean_time_ms(&[1000, 2000], &[0.0, 1.0]), Ok(2000));
+        assert_eq!(
+            weighted_mean_time_ms(&[-1000, -2000], &[1.0, 1.0]),
+            Ok(-1500)
+        );
+    }
+
+    #[test]
+    fn test_weighted_mean_time_ms_no_overflow() {
+        assert_eq!(
+            weighted_mean_time_ms(&[i64::MAX, i64::MAX - 2], &[1.0, 1.0]),
+            Ok(i64::MAX - 1)
+        );
+        assert_eq!(
+            weighted_mean_time_ms(&[i64::MIN, i64::MIN + 2], &[1.0, 1.0]),
+            Ok(i64::MIN + 1)
+        );
+        assert_eq!(
+            weighted_mean_time_ms(&[i64::MAX, i64::MAX], &[1e300, 1e300]),
+            Ok(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_weighted_mean_time_ms_errors() {
+        assert_eq!(
+            weighted_mean_time_ms(&[], &[]),
+            Err(TimeMsError::EmptyInput)
+        );
+        assert_eq!(
+            weighted_mean_time_ms(&[1, 2], &[1.0]),
+            Err(TimeMsError::LengthMismatch { left: 2, right: 1 })
+        );
+        assert_eq!(
+            weighted_mean_time_ms(&[1, 2], &[1.0, -1.0]),
+            Err(TimeMsError::InvalidWeight(-1.0))
+        );
+        assert_eq!(
+            weighted_mean_time_ms(&[1, 2], &[0.0, 0.0]),
+            Err(TimeMsError::ZeroTotalWeight)
+        );
+        assert!(matches!(
+            weighted_mean_time_ms(&[1, 2], &[1.0, f64::NAN]),
+            Err(TimeMsError::InvalidWeight(w)) if w.is_nan()
+        ));
+        assert_eq!(
+            weighted_mean_time_ms(&[1, 2], &[1.0, f64::INFINITY]),
+            Err(TimeMsError::InvalidWeight(f64::INFINITY))
+        );
+    }
+
+    #[test]
+    fn test_detect_outliers_mad() {
+        // A cluster around 1_657_843_200_000 and two wild values
+        let base = 1_657_843_200_000;
+        let times = [
+            base,
+            base + 12,
+            0,
+            base - 7,
+            base + 3,
+            base + 86_400_000,
+            base - 2,
+        ];
+        let method = OutlierMethod::MadThreshold(3.0);
+        assert_eq!(
+            detect_outliers(&times, method),
+            vec![false, false, true, false, false, true, false]
+        );
+        assert_eq!(
+            filter_outliers(&times, method),
+            vec![base, base + 12, base - 7, base + 3, base - 2]
+        );
+    }
+
+    #[test]
+    fn test_detect_outliers_all_equal() {
+        let times = [1000; 5];
+        assert_eq!(
+            detect_outliers(&times, OutlierMethod::MadThreshold(3.0)),
+            vec![false; 5]
+        );
+        assert_eq!(
+            filter_outliers(&times, OutlierMethod::MadThreshold(3.0)),
+            times.to_vec()
+        );
+
+        // MAD is 0 when most values are equal so any other value is an outlier
+        assert_eq!(
+            detect_outliers(&[1000, 1000, 1000, 1001], OutlierMethod::MadThreshold(3.0)),
+            vec![false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_detect_outliers_no_overflow() {
+        let times = [i64::MIN, i64::MAX, i64::MAX, i64::MAX];
+        assert_eq!(
+            detect_outliers(&times, OutlierMethod::MadThreshold(3.0)),
+            vec![true, false, false, false]
+        );
+        let method = OutlierMethod::FixedWindow {
+            center: i64::MAX,
+            tolerance_ms: 0,
+        };
+        assert_eq!(
+            detect_outliers(&times, method),
+            vec![true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_detect_outliers_fixed_window() {
+        let method = OutlierMethod::FixedWindow {
+            center: 0,
+            tolerance_ms: 100,
+        };
+        assert_eq!(
+            detect_outliers(&[-101, -100, 0, 100, 101], method),
+            vec![true, false, false, false, true]
+        );
+        assert_eq!(
+            filter_outliers(&[-101, -100, 0, 100, 101], method),
+            vec![-100, 0, 100]
+        );
+    }
+
+    #[test]
+    fn test_detect_outliers_empty() {
+        assert!(detect_outliers(&[], OutlierMethod::MadThreshold(3.0)).is_empty());
+        assert!(filter_outliers(&[], OutlierMethod::MadThreshold(3.0)).is_empty());
+    }
+
+    #[test]
+    fn test_ema_smooth_time_ms_no_smoothing() {
+        let times = [
+            1_657_886_400_123,
+            1_657_886_401_120,
+            1_657_886_402_131,
+            -5,
+            i64::MIN,
+        ];
+        assert_eq!(ema_smooth_time_ms(&times, 1.0), times.to_vec());
+        assert!(ema_smooth_time_ms(&[], 0.5).is_empty());
+        assert_eq!(ema_smooth_time_ms(&[i64::MAX], 0.5), vec![i64::MAX]);
+    }
+
+    #[test]
+    fn test_ema_smooth_time_ms_lags() {
+        // A step from 0 to 1000 is approached but never reached
+        let times = [0, 1000, 1000, 1000, 1000];
+        let smoothed = ema_smooth_time_ms(&times, 0.5);
+        assert_eq!(smoothed, vec![0, 500, 750, 875, 938]);
+        for (s, t) in smoothed.iter().zip(&times) {
+            assert!(s <= t);
+        }
+
+        // Jittery samples a second apart are smoothed toward the trend
+        let base = 1_657_886_400_000;
+        let times = [base, base + 1_040, base + 1_960, base + 3_030, base + 3_970];
+        let smoothed = ema_smooth_time_ms(&times, 0.2);
+        dbg!(&smoothed);
+        assert_eq!(smoothed[0], base);
+        assert!(smoothed.windows(2).all(|w| w[0] < w[1]));
+        assert!(smoothed.iter().zip(&times).skip(1).all(|(s, t)| s < t));
+
+        // Extreme values don't overflow
+        assert_eq!(
+            ema_smooth_time_ms(&[i64::MIN, i64::MAX], 0.5),
+            vec![i64::MIN, 0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha must be in (0, 1]")]
+    fn test_ema_smooth_time_ms_zero_alpha() {
+        ema_smooth_time_ms(&[0, 1000], 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha must be in (0, 1]")]
+    fn test_ema_smooth_time_ms_nan_alpha() {
+        ema_smooth_time_ms(&[0, 1000], f64::NAN);
+    }
+
+    fn observe_all(times: &[i64]) -> TimeMsStats {
+        let mut stats = TimeMsStats::default();
+        for &tms in times {
+            stats.observe(tms);
+        }
+        stats
+    }
+
+    #[test]
+    fn test_time_ms_stats_empty_and_single() {
+        let stats = TimeMsStats::default();
+        assert_eq!(stats.count(), 0);
+        assert_eq!((stats.min(), stats.max()), (None, None));
+        assert_eq!((stats.first(), stats.last()), (None, None));
+        assert_eq!(stats.span_ms(), None);
+        assert_eq!(stats.to_string(), "count=0");
+
+        let stats = observe_all(&[-1]);
+        assert_eq!(stats.count(), 1);
+        assert_eq!((stats.min(), stats.max()), (Some(-1), Some(-1)));
+        assert_eq!((stats.first(), stats.last()), (Some(-1), Some(-1)));
+        assert_eq!(stats.span_ms(), Some(0));
+        assert_eq!(
+            stats.to_string(),
+            "count=1 min=1969-12-31T23:59:59.999Z max=1969-12-31T23:59:59.999Z"
+        );
+
+        let stats = observe_all(&[i64::MIN, i64::MAX]);
+        assert_eq!(stats.span_ms(), Some(i64::MAX));
+        assert_eq!(
+            stats.to_string(),
+            format!("count=2 min={} max={}", i64::MIN, i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_time_ms_stats_merge_empty() {
+        let stats = observe_all(&[3000, 1000, 2000]);
+
+        let mut merged = stats;
+        merged.merge(&TimeMsStats::default());
+        assert_eq!(merged, stats);
+
+        let mut merged = TimeMsStats::default();
+        merged.merge(&stats);
+        assert_eq!(merged, stats);
+
+        let mut merged = TimeMsStats::default();
+        merged.merge(&TimeMsStats::default());
+        assert_eq!(merged, TimeMsStats::default());
+    }
+
+    #[test]
+    fn test_time_ms_stats_merge_shards() {
+        let times: Vec<i64> = (0..1000i64)
+            .map(|i| (i * 7919).rem_euclid(10_007) * 1000 - 5_000_000)
+            .collect();
+        let sequential = observe_all(&times);
+
+        let shards: Vec<TimeMsStats> = std::thread::scope(|s| {
+            let handles: Vec<_> = times
+                .chunks(137)
+                .map(|chunk| s.spawn(|| observe_all(chunk)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // In order, left to right
+        let mut merged = TimeMsStats::default();
+        for shard in &shards {
+            merged.merge(shard);
+        }
+        assert_eq!(merged, sequential);
+
+        // In order, right to left
+        let mut merged = TimeMsStats::default();
+        for shard in shards.iter().rev() {
+            let mut earlier = *shard;
+            earlier.merge(&merged);
+            merged = earlier;
+        }
+        assert_eq!(merged, sequential);
+
+        // Pairwise as a tree
+        let mut level = shards.clone();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let mut stats = pair[0];
+                    if let Some(other) = pair.get(1) {
+                        stats.merge(other);
+                    }
+                    stats
+                })
+                .collect();
+        }
+        assert_eq!(level[0], sequential);
+
+        // Out of order the order independent statistics still match
+        let mut merged = TimeMsStats::default();
+        for i in [3, 0, 7, 1, 5, 2, 6, 4] {
+            merged.merge(&shards[i]);
+        }
+        assert_eq!(merged.count(), sequential.count());
+        assert_eq!(merged.min(), sequential.min());
+        assert_eq!(merged.max(), sequential.max());
+        assert_eq!(merged.span_ms(), sequential.span_ms());
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{nanos_to_time_ms_rounded, RoundingMode, TimeMsError};
+
+/// Convert a SystemTime to time_ms
+///
+/// Times before the Unix epoch are negative and sub milli-seconds are
+/// rounded half up just as [`utc_to_time_ms`](crate::utc_to_time_ms) does.
+/// Returns `TimeMsError::OutOfRange` if the time is beyond the range of time_ms.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use time_ms_conversions::system_time_to_time_ms;
+///
+/// assert_eq!(system_time_to_time_ms(UNIX_EPOCH + Duration::from_millis(1500)), Ok(1500));
+/// assert_eq!(system_time_to_time_ms(UNIX_EPOCH - Duration::from_millis(1500)), Ok(-1500));
+/// ```
+pub fn system_time_to_time_ms(st: SystemTime) -> Result<i64, TimeMsError> {
+    let nanos = match st.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    };
+
+    // The largest magnitudes that still round into the i64 range
+    let max_nanos = i64::MAX as i128 * 1_000_000 + 499_999;
+    let min_nanos = i64::MIN as i128 * 1_000_000 - 500_000;
+    if !(min_nanos..=max_nanos).contains(&nanos) {
+        return Err(TimeMsError::OutOfRange);
+    }
+
+    Ok(nanos_to_time_ms_rounded(nanos, RoundingMode::HalfUp))
+}
+
+/// Convert time_ms to a SystemTime
+///
+/// Returns `TimeMsError::OutOfRange` if the platform's SystemTime can't
+/// represent time_ms.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use time_ms_conversions::time_ms_to_system_time;
+///
+/// assert_eq!(time_ms_to_system_time(-1), Ok(UNIX_EPOCH - Duration::from_millis(1)));
+/// ```
+pub fn time_ms_to_system_time(time_ms: i64) -> Result<SystemTime, TimeMsError> {
+    let offset = Duration::from_millis(time_ms.unsigned_abs());
+    let st = if time_ms < 0 {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    };
+
+    st.ok_or(TimeMsError::OutOfRange)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_system_time_to_time_ms_around_epoch() {
+        assert_eq!(system_time_to_time_ms(UNIX_EPOCH), Ok(0));
+        for ms in [1, -1, 999, -999, 1_000, -1_000] {
+            let st = time_ms_to_system_time(ms).unwrap();
+            dbg!(ms, st);
+            assert_eq!(system_time_to_time_ms(st), Ok(ms));
+        }
+
+        // Sub milli-seconds round half up
+        let nanos = Duration::from_nanos;
+        assert_eq!(system_time_to_time_ms(UNIX_EPOCH + nanos(499_999)), Ok(0));
+        assert_eq!(system_time_to_time_ms(UNIX_EPOCH + nanos(500_000)), Ok(1));
+        assert_eq!(system_time_to_time_ms(UNIX_EPOCH - nanos(500_000)), Ok(0));
+        assert_eq!(system_time_to_time_ms(UNIX_EPOCH - nanos(500_001)), Ok(-1));
+        assert_eq!(
+            system_time_to_time_ms(UNIX_EPOCH - nanos(1_500_000)),
+            Ok(-1)
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_system_time() {
+        assert_eq!(time_ms_to_system_time(0), Ok(UNIX_EPOCH));
+        assert_eq!(
+            time_ms_to_system_time(1_657_886_400_123),
+            Ok(UNIX_EPOCH + Duration::from_millis(1_657_886_400_123))
+        );
+        assert_eq!(
+            time_ms_to_system_time(-1_500),
+            Ok(UNIX_EPOCH - Duration::from_millis(1_500))
+        );
+    }
+
+    #[test]
+    fn test_system_time_pre_1970_round_trip() {
+        // 1900-01-01T00:00:00.001Z
+        let ms = -2_208_988_799_999;
+        let st = UNIX_EPOCH - Duration::from_millis(2_208_988_799_999);
+        assert_eq!(system_time_to_time_ms(st), Ok(ms));
+        assert_eq!(time_ms_to_system_time(ms), Ok(st));
+    }
+
+    #[test]
+    fn test_system_time_far_future_round_trip() {
+        // 9999-12-31T23:59:59.999Z
+        let ms = 253_402_300_799_999;
+        let st = time_ms_to_system_time(ms).unwrap();
+        assert_eq!(
+            st.duration_since(UNIX_EPOCH).unwrap().as_millis(),
+            ms as u128
+        );
+        assert_eq!(system_time_to_time_ms(st), Ok(ms));
+
+        // The extremes of time_ms
+        for ms in [i64::MAX, i64::MIN] {
+            let st = time_ms_to_system_time(ms).unwrap();
+            assert_eq!(system_time_to_time_ms(st), Ok(ms));
+        }
+    }
+
+    #[test]
+    fn test_system_time_to_time_ms_out_of_range() {
+        let st = UNIX_EPOCH + Duration::from_secs(i64::MAX as u64);
+        assert_eq!(system_time_to_time_ms(st), Err(TimeMsError::OutOfRange));
+
+        let st = time_ms_to_system_time(i64::MAX).unwrap() + Duration::from_nanos(500_000);
+        assert_eq!(system_time_to_time_ms(st), Err(TimeMsError::OutOfRange));
+        let st = time_ms_to_system_time(i64::MIN).unwrap() - Duration::from_nanos(500_001);
+        assert_eq!(system_time_to_time_ms(st), Err(TimeMsError::OutOfRange));
+    }
+}
@@ -0,0 +1,173 @@
+use time::OffsetDateTime;
+
+use crate::{nanos_to_time_ms_rounded, time_ms_to_secs_nsecs, RoundingMode, TimeMsError};
+
+/// Convert time_ms to a UTC `time::OffsetDateTime`
+///
+/// Returns `TimeMsError::OutOfRange` if time_ms is outside the years the
+/// time crate supports, -9999 through 9999 by default.
+///
+/// # Example
+/// ```
+/// use time::OffsetDateTime;
+/// use time_ms_conversions::time_ms_to_offset_datetime;
+///
+/// assert_eq!(time_ms_to_offset_datetime(0), Ok(OffsetDateTime::UNIX_EPOCH));
+/// ```
+pub fn time_ms_to_offset_datetime(time_ms: i64) -> Result<OffsetDateTime, TimeMsError> {
+    let (secs, nsecs) = time_ms_to_secs_nsecs(time_ms);
+    OffsetDateTime::from_unix_timestamp(secs)
+        .and_then(|dt| dt.replace_nanosecond(nsecs))
+        .map_err(|_| TimeMsError::OutOfRange)
+}
+
+/// Convert a `time::OffsetDateTime` to time_ms
+///
+/// Sub milli-seconds are rounded half up just as
+/// [`utc_to_time_ms`](crate::utc_to_time_ms) does.
+///
+/// # Example
+/// ```
+/// use time::{Duration, OffsetDateTime};
+/// use time_ms_conversions::offset_datetime_to_time_ms;
+///
+/// let dt = OffsetDateTime::UNIX_EPOCH - Duration::microseconds(1500);
+/// assert_eq!(offset_datetime_to_time_ms(&dt), -1);
+/// ```
+pub fn offset_datetime_to_time_ms(dt: &OffsetDateTime) -> i64 {
+    nanos_to_time_ms_rounded(dt.unix_timestamp_nanos(), RoundingMode::HalfUp)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{time_ms_to_time_ns, time_ns_to_time_ms};
+    #[cfg(feature = "std")]
+    use crate::{time_ms_to_utc, utc_to_time_ms};
+    #[cfg(feature = "std")]
+    use chrono::DateTime;
+    use time::{Duration, UtcOffset};
+
+    #[test]
+    fn test_time_crate_epoch() {
+        let dt = time_ms_to_offset_datetime(0).unwrap();
+        assert_eq!(dt, OffsetDateTime::UNIX_EPOCH);
+        assert_eq!(offset_datetime_to_time_ms(&dt), 0);
+    }
+
+    #[test]
+    fn test_time_crate_negative() {
+        let dt = time_ms_to_offset_datetime(-1).unwrap();
+        #[cfg(feature = "std")]
+        dbg!(dt);
+        assert_eq!(dt, OffsetDateTime::UNIX_EPOCH - Duration::milliseconds(1));
+        assert_eq!(dt.nanosecond(), 999_000_000);
+        assert_eq!(offset_datetime_to_time_ms(&dt), -1);
+
+        for tms in [-1_500, -86_400_001, -1_000_000_000_000] {
+            let dt = time_ms_to_offset_datetime(tms).unwrap();
+            assert_eq!(offset_datetime_to_time_ms(&dt), tms);
+        }
+    }
+
+    #[test]
+    fn test_time_crate_agrees_with_time_ns() {
+        for tms in [0, 1, -1, -1_500, 1_657_886_400_123, 253_402_300_799_999] {
+            let dt = time_ms_to_offset_datetime(tms).unwrap();
+            assert_eq!(dt.unix_timestamp_nanos(), time_ms_to_time_ns(tms));
+        }
+
+        // Sub milli-seconds round the same way, without chrono
+        for nanos in [
+            499_999i128,
+            500_000,
+            -500_000,
+            -500_001,
+            -1_500_000,
+            1_657_886_400_123_456_789,
+        ] {
+            let dt = OffsetDateTime::from_unix_timestamp_nanos(nanos).unwrap();
+            assert_eq!(
+                offset_datetime_to_time_ms(&dt),
+                time_ns_to_time_ms(nanos, RoundingMode::HalfUp),
+                "nanos={nanos}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_crate_agrees_with_chrono() {
+        for tms in [
+            0,
+            1,
+            -1,
+            -1_500,
+            1_657_886_400_123,
+            -2_208_988_799_999,
+            253_402_300_799_999,
+        ] {
+            let chrono_dt = time_ms_to_utc(tms);
+            let time_dt = time_ms_to_offset_datetime(tms).unwrap();
+            assert_eq!(time_dt.unix_timestamp(), chrono_dt.timestamp());
+            assert_eq!(time_dt.nanosecond(), chrono_dt.timestamp_subsec_nanos());
+            assert_eq!(
+                offset_datetime_to_time_ms(&time_dt),
+                utc_to_time_ms(&chrono_dt)
+            );
+        }
+
+        // Sub milli-seconds round the same way
+        for nanos in [
+            499_999i64,
+            500_000,
+            -500_000,
+            -500_001,
+            -1_500_000,
+            1_657_886_400_123_456_789,
+        ] {
+            let chrono_dt = DateTime::from_timestamp_nanos(nanos);
+            let time_dt = OffsetDateTime::from_unix_timestamp_nanos(nanos as i128).unwrap();
+            dbg!(nanos);
+            assert_eq!(
+                offset_datetime_to_time_ms(&time_dt),
+                utc_to_time_ms(&chrono_dt),
+                "nanos={nanos}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_offset_datetime_to_time_ms_rounding() {
+        let epoch = OffsetDateTime::UNIX_EPOCH;
+        let us = Duration::microseconds;
+        assert_eq!(offset_datetime_to_time_ms(&(epoch + us(499))), 0);
+        assert_eq!(offset_datetime_to_time_ms(&(epoch + us(500))), 1);
+        assert_eq!(offset_datetime_to_time_ms(&(epoch - us(500))), 0);
+        assert_eq!(offset_datetime_to_time_ms(&(epoch - us(501))), -1);
+    }
+
+    #[test]
+    fn test_offset_datetime_to_time_ms_offset() {
+        // The same instant in another offset is the same time_ms
+        let dt = time_ms_to_offset_datetime(1_657_886_400_123).unwrap();
+        let offset = UtcOffset::from_hms(-7, 0, 0).unwrap();
+        assert_eq!(
+            offset_datetime_to_time_ms(&dt.to_offset(offset)),
+            1_657_886_400_123
+        );
+    }
+
+    #[test]
+    fn test_time_ms_to_offset_datetime_out_of_range() {
+        // 10000-01-01T00:00:00Z
+        assert_eq!(
+            time_ms_to_offset_datetime(253_402_300_800_000),
+            Err(TimeMsError::OutOfRange)
+        );
+        assert_eq!(
+            time_ms_to_offset_datetime(i64::MIN),
+            Err(TimeMsError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,405 @@
+#[cfg(feature = "std")]
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, SecondsFormat, Utc, Weekday};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use crate::{
+    align_time_ms, epoch_seconds_to_time_ms, is_aligned_to_interval, misalignment_ms,
+    secs_nsecs_to_time_ms, time_ms_to_epoch_seconds_floor, time_ms_to_epoch_seconds_trunc,
+    time_ms_to_secs_nsecs, time_ms_to_ticks, truncate_time_ms_to_day, truncate_time_ms_to_hour,
+    truncate_time_ms_to_minute,
+};
+#[cfg(feature = "std")]
+use crate::{
+    dt_str_to_utc_time_ms, explain_time_ms, fixed_offset_to_time_ms, system_time_to_time_ms,
+    time_ms_components_utc, time_ms_days_in_month, time_ms_from_utc_parts, time_ms_is_leap_year,
+    time_ms_part_of_day_utc, time_ms_to_fixed_offset, time_ms_to_fixed_offset_string,
+    time_ms_to_http_date_string, time_ms_to_iso_week, time_ms_to_local, time_ms_to_local_string,
+    time_ms_to_naive_date, time_ms_to_ordinal, time_ms_to_rfc2822_string,
+    time_ms_to_string_with_fmt, time_ms_to_system_time, time_ms_to_utc, time_ms_to_utc_string,
+    time_ms_to_utc_string_opts, time_ms_to_utc_z_string, time_ms_weekday, try_time_ms_to_utc,
+    utc_now_to_time_ms, utc_to_time_ms, DateTimeComponents, PartOfDay, TimeConversionError,
+    TimeMsError, TimeMsExplanation, TzMassaging,
+};
+
+/// A time_ms, milli-seconds since the Unix epoch, as its own type so it
+/// can't be mixed up with seconds, micro-seconds or other i64 values.
+///
+/// The methods are thin wrappers of the free functions of the same
+/// purpose so code can move to `TimeMs` incrementally.
+///
+/// `TimeMs` is `#[repr(transparent)]` so it's guaranteed to have the same
+/// layout as `i64`, a `&[TimeMs]` can be viewed as a `&[i64]` and vice versa.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::TimeMs;
+///
+/// let tms = TimeMs::from_secs_nsecs(1_657_886_400, 123_000_000);
+/// assert_eq!(tms.as_i64(), 1_657_886_400_123);
+/// assert_eq!(tms.truncate_to_day(), TimeMs(1_657_843_200_000));
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeMs(pub i64);
+
+impl TimeMs {
+    /// The epoch, 1970-01-01T00:00:00Z
+    pub const EPOCH: TimeMs = TimeMs(0);
+
+    pub const fn new(time_ms: i64) -> Self {
+        TimeMs(time_ms)
+    }
+
+    pub const fn as_i64(self) -> i64 {
+        self.0
+    }
+
+    /// See [`secs_nsecs_to_time_ms`]
+    pub const fn from_secs_nsecs(secs: i64, nsecs: u32) -> Self {
+        TimeMs(secs_nsecs_to_time_ms(secs, nsecs))
+    }
+
+    /// See [`time_ms_to_secs_nsecs`]
+    pub fn to_secs_nsecs(self) -> (i64, u32) {
+        time_ms_to_secs_nsecs(self.0)
+    }
+
+    /// See [`epoch_seconds_to_time_ms`]
+    pub const fn from_epoch_seconds(secs: i64) -> Self {
+        TimeMs(epoch_seconds_to_time_ms(secs))
+    }
+
+    /// See [`time_ms_to_epoch_seconds_floor`]
+    pub const fn to_epoch_seconds_floor(self) -> i64 {
+        time_ms_to_epoch_seconds_floor(self.0)
+    }
+
+    /// See [`time_ms_to_epoch_seconds_trunc`]
+    pub const fn to_epoch_seconds_trunc(self) -> i64 {
+        time_ms_to_epoch_seconds_trunc(self.0)
+    }
+
+    /// See [`time_ms_to_ticks`]
+    ///
+    /// # Panics
+    ///
+    /// If `tick_ms <= 0`
+    pub fn to_ticks(self, tick_ms: i64) -> i64 {
+        time_ms_to_ticks(self.0, tick_ms)
+    }
+
+    /// See [`align_time_ms`]
+    ///
+    /// # Panics
+    ///
+    /// If `interval_ms <= 0`
+    pub fn align(self, interval_ms: i64) -> Self {
+        TimeMs(align_time_ms(self.0, interval_ms))
+    }
+
+    /// See [`truncate_time_ms_to_day`]
+    pub fn truncate_to_day(self) -> Self {
+        TimeMs(truncate_time_ms_to_day(self.0))
+    }
+
+    /// See [`truncate_time_ms_to_hour`]
+    pub fn truncate_to_hour(self) -> Self {
+        TimeMs(truncate_time_ms_to_hour(self.0))
+    }
+
+    /// See [`truncate_time_ms_to_minute`]
+    pub fn truncate_to_minute(self) -> Self {
+        TimeMs(truncate_time_ms_to_minute(self.0))
+    }
+
+    /// See [`is_aligned_to_interval`]
+    ///
+    /// # Panics
+    ///
+    /// If `interval_ms <= 0`
+    pub fn is_aligned_to_interval(self, interval_ms: i64) -> bool {
+        is_aligned_to_interval(self.0, interval_ms)
+    }
+
+    /// See [`misalignment_ms`]
+    ///
+    /// # Panics
+    ///
+    /// If `interval_ms <= 0`
+    pub fn misalignment_ms(self, interval_ms: i64) -> i64 {
+        misalignment_ms(self.0, interval_ms)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TimeMs {
+    /// See [`utc_now_to_time_ms`]
+    pub fn now() -> Self {
+        TimeMs(utc_now_to_time_ms())
+    }
+
+    /// See [`utc_to_time_ms`]
+    pub fn from_utc(date_time: &DateTime<Utc>) -> Self {
+        TimeMs(utc_to_time_ms(date_time))
+    }
+
+    /// See [`fixed_offset_to_time_ms`]
+    pub fn from_fixed_offset(date_time: &DateTime<FixedOffset>) -> Self {
+        TimeMs(fixed_offset_to_time_ms(date_time))
+    }
+
+    /// See [`time_ms_from_utc_parts`]
+    pub fn from_utc_parts(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+        millis: u32,
+    ) -> Option<Self> {
+        time_ms_from_utc_parts(year, month, day, hour, minute, second, millis).map(TimeMs)
+    }
+
+    /// See [`system_time_to_time_ms`]
+    pub fn from_system_time(st: SystemTime) -> Result<Self, TimeMsError> {
+        system_time_to_time_ms(st).map(TimeMs)
+    }
+
+    /// See [`dt_str_to_utc_time_ms`]
+    ///
+    /// # Example
+    /// ```
+    /// use time_ms_conversions::{TimeMs, TzMassaging};
+    ///
+    /// let tms = TimeMs::parse("2022-07-15T12:00:00.123Z", TzMassaging::HasTz).unwrap();
+    /// assert_eq!(tms, TimeMs(1_657_886_400_123));
+    /// assert_eq!(tms.to_utc_z_string(), "2022-07-15T12:00:00.123Z");
+    /// ```
+    pub fn parse(dt_str: &str, tz_massaging: TzMassaging) -> Result<Self, TimeConversionError> {
+        dt_str_to_utc_time_ms(dt_str, tz_massaging).map(TimeMs)
+    }
+
+    /// See [`time_ms_to_utc`]
+    ///
+    /// # Panics
+    ///
+    /// If out of the range of DateTime<Utc>, see [`TimeMs::try_to_utc`]
+    pub fn to_utc(self) -> DateTime<Utc> {
+        time_ms_to_utc(self.0)
+    }
+
+    /// See [`try_time_ms_to_utc`]
+    pub fn try_to_utc(self) -> Result<DateTime<Utc>, TimeMsError> {
+        try_time_ms_to_utc(self.0)
+    }
+
+    /// See [`time_ms_to_local`]
+    pub fn to_local(self) -> DateTime<Local> {
+        time_ms_to_local(self.0)
+    }
+
+    /// See [`time_ms_to_fixed_offset`]
+    pub fn to_fixed_offset(self, offset: FixedOffset) -> DateTime<FixedOffset> {
+        time_ms_to_fixed_offset(self.0, offset)
+    }
+
+    /// See [`time_ms_to_system_time`]
+    pub fn to_system_time(self) -> Result<SystemTime, TimeMsError> {
+        time_ms_to_system_time(self.0)
+    }
+
+    /// See [`time_ms_to_utc_string`]
+    pub fn to_utc_string(self) -> String {
+        time_ms_to_utc_string(self.0)
+    }
+
+    /// See [`time_ms_to_utc_z_string`]
+    pub fn to_utc_z_string(self) -> String {
+        time_ms_to_utc_z_string(self.0)
+    }
+
+    /// See [`time_ms_to_utc_string_opts`]
+    pub fn to_utc_string_opts(self, seconds_format: SecondsFormat, use_z: bool) -> String {
+        time_ms_to_utc_string_opts(self.0, seconds_format, use_z)
+    }
+
+    /// See [`time_ms_to_local_string`]
+    pub fn to_local_string(self) -> String {
+        time_ms_to_local_string(self.0)
+    }
+
+    /// See [`time_ms_to_fixed_offset_string`]
+    pub fn to_fixed_offset_string(self, offset: FixedOffset) -> String {
+        time_ms_to_fixed_offset_string(self.0, offset)
+    }
+
+    /// See [`time_ms_to_string_with_fmt`]
+    pub fn to_string_with_fmt(self, fmt: &str) -> Result<String, TimeMsError> {
+        time_ms_to_string_with_fmt(self.0, fmt)
+    }
+
+    /// See [`time_ms_to_rfc2822_string`]
+    pub fn to_rfc2822_string(self) -> String {
+        time_ms_to_rfc2822_string(self.0)
+    }
+
+    /// See [`time_ms_to_http_date_string`]
+    pub fn to_http_date_string(self) -> String {
+        time_ms_to_http_date_string(self.0)
+    }
+
+    /// See [`time_ms_components_utc`]
+    pub fn components_utc(self) -> DateTimeComponents {
+        time_ms_components_utc(self.0)
+    }
+
+    /// See [`time_ms_to_iso_week`]
+    pub fn iso_week(self) -> (i32, u32) {
+        time_ms_to_iso_week(self.0)
+    }
+
+    /// See [`time_ms_to_ordinal`]
+    pub fn ordinal(self) -> u32 {
+        time_ms_to_ordinal(self.0)
+    }
+
+    /// See [`time_ms_is_leap_year`]
+    pub fn is_leap_year(self) -> bool {
+        time_ms_is_leap_year(self.0)
+    }
+
+    /// See [`time_ms_to_naive_date`]
+    pub fn to_naive_date(self) -> NaiveDate {
+        time_ms_to_naive_date(self.0)
+    }
+
+    /// See [`time_ms_weekday`]
+    pub fn weekday(self) -> Weekday {
+        time_ms_weekday(self.0)
+    }
+
+    /// See [`time_ms_days_in_month`]
+    pub fn days_in_month(self) -> u32 {
+        time_ms_days_in_month(self.0)
+    }
+
+    /// See [`time_ms_part_of_day_utc`]
+    pub fn part_of_day_utc(self) -> PartOfDay {
+        time_ms_part_of_day_utc(self.0)
+    }
+
+    /// See [`explain_time_ms`]
+    pub fn explain(self) -> TimeMsExplanation {
+        explain_time_ms(self.0)
+    }
+}
+
+impl From<i64> for TimeMs {
+    fn from(time_ms: i64) -> Self {
+        TimeMs(time_ms)
+    }
+}
+
+impl From<TimeMs> for i64 {
+    fn from(time_ms: TimeMs) -> Self {
+        time_ms.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_ms_layout() {
+        assert_eq!(core::mem::size_of::<TimeMs>(), core::mem::size_of::<i64>());
+        assert_eq!(
+            core::mem::align_of::<TimeMs>(),
+            core::mem::align_of::<i64>()
+        );
+
+        let times = [TimeMs(-1), TimeMs(0), TimeMs(1_657_886_400_123)];
+        // SAFETY: TimeMs is repr(transparent) over i64
+        let raw: &[i64] =
+            unsafe { core::slice::from_raw_parts(times.as_ptr().cast(), times.len()) };
+        assert_eq!(raw, [-1, 0, 1_657_886_400_123]);
+    }
+
+    #[test]
+    fn test_time_ms_integer_methods() {
+        let tms = TimeMs::new(-1500);
+        assert_eq!(tms.as_i64(), -1500);
+        assert_eq!(i64::from(tms), -1500);
+        assert_eq!(TimeMs::from(-1500), tms);
+        assert_eq!(tms.to_secs_nsecs(), (-2, 500_000_000));
+        assert_eq!(TimeMs::from_secs_nsecs(-2, 500_000_000), tms);
+        assert_eq!(tms.to_epoch_seconds_floor(), -2);
+        assert_eq!(tms.to_epoch_seconds_trunc(), -1);
+        assert_eq!(TimeMs::from_epoch_seconds(-2), TimeMs(-2000));
+        assert_eq!(tms.to_ticks(1000), -2);
+        assert_eq!(tms.align(1000), TimeMs(-2000));
+        assert_eq!(tms.truncate_to_day(), TimeMs(-86_400_000));
+        assert_eq!(tms.truncate_to_hour(), TimeMs(-3_600_000));
+        assert_eq!(tms.truncate_to_minute(), TimeMs(-60_000));
+        assert!(!tms.is_aligned_to_interval(1000));
+        assert_eq!(tms.misalignment_ms(1000), 500);
+
+        assert!(TimeMs(-1) < TimeMs::EPOCH);
+        assert_eq!(TimeMs::default(), TimeMs::EPOCH);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_ms_chrono_methods() {
+        let tms = TimeMs::from_utc_parts(2022, 7, 15, 12, 0, 0, 123).unwrap();
+        assert_eq!(tms, TimeMs(1_657_886_400_123));
+        assert_eq!(TimeMs::from_utc(&tms.to_utc()), tms);
+        assert_eq!(TimeMs::from_fixed_offset(&tms.to_utc().fixed_offset()), tms);
+        assert_eq!(tms.try_to_utc(), Ok(tms.to_utc()));
+        assert_eq!(tms.to_local(), tms.to_utc());
+        assert_eq!(tms.to_utc_string(), time_ms_to_utc_string(tms.0));
+        assert_eq!(tms.to_utc_z_string(), "2022-07-15T12:00:00.123Z");
+        assert_eq!(
+            tms.to_utc_string_opts(SecondsFormat::Secs, true),
+            "2022-07-15T12:00:00Z"
+        );
+        assert_eq!(tms.to_local_string(), time_ms_to_local_string(tms.0));
+        let jst = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+        assert_eq!(tms.to_fixed_offset(jst), tms.to_utc());
+        assert_eq!(
+            tms.to_fixed_offset_string(jst),
+            "2022-07-15T21:00:00.123+09:00"
+        );
+        assert_eq!(
+            tms.to_string_with_fmt("%Y%m%d_%H%M%S").as_deref(),
+            Ok("20220715_120000")
+        );
+        assert_eq!(tms.to_rfc2822_string(), "Fri, 15 Jul 2022 12:00:00 +0000");
+        assert_eq!(tms.to_http_date_string(), "Fri, 15 Jul 2022 12:00:00 GMT");
+        assert_eq!(tms.components_utc().millisecond, 123);
+        assert_eq!(tms.iso_week(), (2022, 28));
+        assert_eq!(tms.ordinal(), 196);
+        assert!(!tms.is_leap_year());
+        assert_eq!(tms.days_in_month(), 31);
+        assert_eq!(
+            tms.to_naive_date(),
+            NaiveDate::from_ymd_opt(2022, 7, 15).unwrap()
+        );
+        assert_eq!(tms.weekday(), Weekday::Fri);
+        assert_eq!(tms.part_of_day_utc(), PartOfDay::Afternoon);
+        assert_eq!(tms.explain().time_ms, tms.0);
+        assert_eq!(
+            TimeMs::from_system_time(tms.to_system_time().unwrap()),
+            Ok(tms)
+        );
+        assert_eq!(
+            TimeMs::parse("2022-07-15T12:00:00.123Z", TzMassaging::HasTz),
+            Ok(tms)
+        );
+        assert!(TimeMs::parse("bad", TzMassaging::HasTz).is_err());
+        assert!(TimeMs(i64::MAX).try_to_utc().is_err());
+        assert!(TimeMs::now() > tms);
+    }
+}
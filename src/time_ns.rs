@@ -0,0 +1,201 @@
+#[cfg(feature = "std")]
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "std")]
+use crate::conversions::date_time_to_nanos;
+#[cfg(feature = "std")]
+use crate::TimeMsError;
+use crate::{nanos_to_time_ms_rounded, RoundingMode};
+
+const NANOS_PER_MS: i128 = 1_000_000;
+
+/// Convert time_ns, nano-seconds since the Unix epoch as an i128, to
+/// time_ms rounded with `mode`, saturating at i64::MIN and i64::MAX.
+///
+/// An i64 of nano-seconds only covers 1677 to 2262, an i128 covers every
+/// time_ms.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ns_to_time_ms, RoundingMode};
+///
+/// assert_eq!(time_ns_to_time_ms(-1_500_000, RoundingMode::HalfUp), -1);
+/// assert_eq!(time_ns_to_time_ms(-1_500_000, RoundingMode::Floor), -2);
+///
+/// // 3000-01-01T00:00:00Z is beyond an i64 of nano-seconds
+/// assert_eq!(time_ns_to_time_ms(32_503_680_000_000_000_000, RoundingMode::HalfUp), 32_503_680_000_000);
+/// ```
+pub fn time_ns_to_time_ms(time_ns: i128, mode: RoundingMode) -> i64 {
+    // Clamp first so the rounded milli-seconds fit in an i64
+    let min_ns = i64::MIN as i128 * NANOS_PER_MS;
+    let max_ns = i64::MAX as i128 * NANOS_PER_MS;
+    nanos_to_time_ms_rounded(time_ns.clamp(min_ns, max_ns), mode)
+}
+
+/// Convert time_ms to time_ns, this is exact for every time_ms
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_to_time_ns, time_ns_to_time_ms, RoundingMode};
+///
+/// assert_eq!(time_ms_to_time_ns(-1), -1_000_000);
+/// assert_eq!(time_ns_to_time_ms(time_ms_to_time_ns(i64::MIN), RoundingMode::HalfUp), i64::MIN);
+/// ```
+pub const fn time_ms_to_time_ns(time_ms: i64) -> i128 {
+    time_ms as i128 * NANOS_PER_MS
+}
+
+/// Convert time_ns to DateTime<Utc>, returning `TimeMsError::OutOfRange`
+/// if it's out of the range of DateTime<Utc>, about +/-262,000 years
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ns_to_utc, utc_to_time_ns, TimeMsError};
+///
+/// let dt = time_ns_to_utc(-1).unwrap();
+/// assert_eq!(dt.to_string(), "1969-12-31 23:59:59.999999999 UTC");
+/// assert_eq!(utc_to_time_ns(&dt), -1);
+/// assert_eq!(time_ns_to_utc(i128::MAX), Err(TimeMsError::OutOfRange));
+/// ```
+#[cfg(feature = "std")]
+pub fn time_ns_to_utc(time_ns: i128) -> Result<DateTime<Utc>, TimeMsError> {
+    const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+    // div_euclid/rem_euclid so the nano-seconds are always positive
+    let secs =
+        i64::try_from(time_ns.div_euclid(NANOS_PER_SEC)).map_err(|_| TimeMsError::OutOfRange)?;
+    let nsecs = time_ns.rem_euclid(NANOS_PER_SEC) as u32;
+    DateTime::from_timestamp(secs, nsecs).ok_or(TimeMsError::OutOfRange)
+}
+
+/// Convert a DateTime<Utc> to time_ns, this is exact for every
+/// DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use chrono::DateTime;
+/// use time_ms_conversions::utc_to_time_ns;
+///
+/// let dt = DateTime::from_timestamp(-1, 999_999_999).unwrap();
+/// assert_eq!(utc_to_time_ns(&dt), -1);
+/// ```
+#[cfg(feature = "std")]
+pub fn utc_to_time_ns(date_time: &DateTime<Utc>) -> i128 {
+    date_time_to_nanos(date_time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 3000-01-01T00:00:00Z and 1000-01-01T00:00:00Z, both beyond an i64
+    // of nano-seconds
+    const YEAR_3000_MS: i64 = 32_503_680_000_000;
+    const YEAR_1000_MS: i64 = -30_610_224_000_000;
+
+    #[test]
+    fn test_time_ns_to_time_ms() {
+        use RoundingMode::*;
+
+        let cases = [
+            // (time_ns, HalfUp, Truncate, HalfEven, Ceil, Floor)
+            (0, 0, 0, 0, 0, 0),
+            (1_500_000, 2, 1, 2, 2, 1),
+            (-1_500_000, -1, -1, -2, -1, -2),
+            (-2_500_000, -2, -2, -2, -2, -3),
+            (-500_000, 0, 0, 0, 0, -1),
+            (-500_001, -1, 0, -1, 0, -1),
+            (-499_999, 0, 0, 0, 0, -1),
+            (-1, 0, 0, 0, 0, -1),
+            (-1_000_000, -1, -1, -1, -1, -1),
+            (-1_000_001, -1, -1, -1, -1, -2),
+        ];
+        for (time_ns, half_up, truncate, half_even, ceil, floor) in cases {
+            assert_eq!(time_ns_to_time_ms(time_ns, HalfUp), half_up, "{time_ns}");
+            assert_eq!(time_ns_to_time_ms(time_ns, Truncate), truncate, "{time_ns}");
+            assert_eq!(
+                time_ns_to_time_ms(time_ns, HalfEven),
+                half_even,
+                "{time_ns}"
+            );
+            assert_eq!(time_ns_to_time_ms(time_ns, Ceil), ceil, "{time_ns}");
+            assert_eq!(time_ns_to_time_ms(time_ns, Floor), floor, "{time_ns}");
+        }
+    }
+
+    #[test]
+    fn test_time_ns_beyond_i64() {
+        for time_ms in [YEAR_3000_MS, YEAR_1000_MS] {
+            let time_ns = time_ms_to_time_ns(time_ms);
+            assert!(i64::try_from(time_ns).is_err());
+            assert_eq!(time_ns_to_time_ms(time_ns, RoundingMode::HalfUp), time_ms);
+            assert_eq!(
+                time_ns_to_time_ms(time_ns - 1, RoundingMode::Floor),
+                time_ms - 1
+            );
+            assert_eq!(
+                time_ns_to_time_ms(time_ns + 1, RoundingMode::Ceil),
+                time_ms + 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_ns_to_time_ms_limits() {
+        for mode in [
+            RoundingMode::HalfUp,
+            RoundingMode::Ceil,
+            RoundingMode::Floor,
+        ] {
+            assert_eq!(time_ns_to_time_ms(i128::MAX, mode), i64::MAX);
+            assert_eq!(time_ns_to_time_ms(i128::MIN, mode), i64::MIN);
+            for time_ms in [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX - 1, i64::MAX] {
+                assert_eq!(
+                    time_ns_to_time_ms(time_ms_to_time_ns(time_ms), mode),
+                    time_ms
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_ns_to_utc() {
+        let cases = [
+            (0, "1970-01-01T00:00:00.000000000Z"),
+            (1, "1970-01-01T00:00:00.000000001Z"),
+            (-1, "1969-12-31T23:59:59.999999999Z"),
+            (
+                time_ms_to_time_ns(YEAR_3000_MS) + 123_456_789,
+                "3000-01-01T00:00:00.123456789Z",
+            ),
+            (
+                time_ms_to_time_ns(YEAR_1000_MS) - 1,
+                "0999-12-31T23:59:59.999999999Z",
+            ),
+        ];
+        for (time_ns, s) in cases {
+            let dt = time_ns_to_utc(time_ns).unwrap();
+            assert_eq!(dt.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true), s);
+            assert_eq!(utc_to_time_ns(&dt), time_ns);
+        }
+
+        let max_ns = utc_to_time_ns(&DateTime::<Utc>::MAX_UTC);
+        assert!(time_ns_to_utc(max_ns).is_ok());
+        assert_eq!(time_ns_to_utc(max_ns + 1), Err(TimeMsError::OutOfRange));
+        let min_ns = utc_to_time_ns(&DateTime::<Utc>::MIN_UTC);
+        assert!(time_ns_to_utc(min_ns).is_ok());
+        assert_eq!(time_ns_to_utc(min_ns - 1), Err(TimeMsError::OutOfRange));
+        assert_eq!(time_ns_to_utc(i128::MIN), Err(TimeMsError::OutOfRange));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_ns_consistent_with_time_ms() {
+        for time_ms in [YEAR_1000_MS, -1, 0, 1_657_886_400_123, YEAR_3000_MS] {
+            let dt = crate::time_ms_to_utc(time_ms);
+            assert_eq!(utc_to_time_ns(&dt), time_ms_to_time_ns(time_ms));
+            assert_eq!(time_ns_to_utc(time_ms_to_time_ns(time_ms)), Ok(dt));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(feature = "std")]
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{time_ms_to_epoch_seconds_floor, MS_PER_SEC};
+
+/// Convert time_s, whole seconds since the Unix epoch as used by `date +%s`
+/// and JWT `exp`, to time_ms, None if it overflows an i64.
+///
+/// Unlike [`epoch_seconds_to_time_ms`](crate::epoch_seconds_to_time_ms)
+/// this doesn't saturate, so an out of range value can't be mistaken for
+/// a valid time.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_s_to_time_ms;
+///
+/// assert_eq!(time_s_to_time_ms(-1), Some(-1000));
+/// assert_eq!(time_s_to_time_ms(i64::MAX / 1000 + 1), None);
+/// ```
+pub const fn time_s_to_time_ms(time_s: i64) -> Option<i64> {
+    time_s.checked_mul(MS_PER_SEC)
+}
+
+/// Convert time_ms to time_s, the whole seconds floored toward negative
+/// infinity, so -1ms is -1s, the second it's in, matching
+/// [`time_ms_to_secs_nsecs`](crate::time_ms_to_secs_nsecs).
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_to_time_s;
+///
+/// assert_eq!(time_ms_to_time_s(1999), 1);
+/// assert_eq!(time_ms_to_time_s(-1), -1);
+/// assert_eq!(time_ms_to_time_s(-1000), -1);
+/// assert_eq!(time_ms_to_time_s(-1001), -2);
+/// ```
+pub const fn time_ms_to_time_s(time_ms: i64) -> i64 {
+    time_ms_to_epoch_seconds_floor(time_ms)
+}
+
+/// Get Utc::now() and convert to time_s, the current second
+///
+/// # Example
+/// ```
+/// use chrono::Utc;
+/// use time_ms_conversions::utc_now_to_time_s;
+///
+/// let before: i64 = Utc::now().timestamp();
+///
+/// assert!(utc_now_to_time_s() >= before);
+/// ```
+#[cfg(feature = "std")]
+pub fn utc_now_to_time_s() -> i64 {
+    // timestamp() floors so the sub seconds are never rounded up
+    Utc::now().timestamp()
+}
+
+/// Convert time_s to an RFC 3339 string without fractional seconds,
+/// "1970-01-01T00:00:00+00:00"
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_s_to_utc_string;
+///
+/// assert_eq!(time_s_to_utc_string(-1), "1969-12-31T23:59:59+00:00");
+/// ```
+///
+/// # Panics
+///
+/// If time_s is out of the range of DateTime<Utc>, about +/-262,000 years
+#[cfg(feature = "std")]
+pub fn time_s_to_utc_string(time_s: i64) -> String {
+    DateTime::from_timestamp(time_s, 0)
+        .expect("time_s out of range for DateTime<Utc>")
+        .to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_ms_to_time_s() {
+        let cases = [
+            // time_ms, time_s
+            (0, 0),
+            (1, 0),
+            (999, 0),
+            (1_000, 1),
+            (1_999, 1),
+            (-1, -1),
+            (-999, -1),
+            (-1_000, -1),
+            (-1_001, -2),
+            (-2_000, -2),
+            (i64::MAX, i64::MAX / 1_000),
+            (i64::MIN, i64::MIN / 1_000 - 1),
+        ];
+        for (time_ms, time_s) in cases {
+            assert_eq!(time_ms_to_time_s(time_ms), time_s, "{time_ms}");
+            assert_eq!(
+                time_ms_to_time_s(time_ms),
+                crate::time_ms_to_secs_nsecs(time_ms).0
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_s_to_time_ms() {
+        assert_eq!(time_s_to_time_ms(0), Some(0));
+        assert_eq!(time_s_to_time_ms(1), Some(1_000));
+        assert_eq!(time_s_to_time_ms(-1), Some(-1_000));
+        assert_eq!(time_s_to_time_ms(1_657_886_400), Some(1_657_886_400_000));
+
+        // The overflow edges
+        let max_s = i64::MAX / 1_000;
+        assert_eq!(time_s_to_time_ms(max_s), Some(9_223_372_036_854_775_000));
+        assert_eq!(time_s_to_time_ms(max_s + 1), None);
+        let min_s = i64::MIN / 1_000;
+        assert_eq!(time_s_to_time_ms(min_s), Some(-9_223_372_036_854_775_000));
+        assert_eq!(time_s_to_time_ms(min_s - 1), None);
+        assert_eq!(time_s_to_time_ms(i64::MAX), None);
+        assert_eq!(time_s_to_time_ms(i64::MIN), None);
+    }
+
+    #[test]
+    fn test_time_s_round_trip() {
+        for time_s in [
+            i64::MIN / 1_000,
+            -1_657_886_400,
+            -1,
+            0,
+            1,
+            1_657_886_400,
+            i64::MAX / 1_000,
+        ] {
+            let time_ms = time_s_to_time_ms(time_s).unwrap();
+            assert_eq!(time_ms_to_time_s(time_ms), time_s);
+
+            // Every milli-second in the second floors to it, the last
+            // second is cut short by i64::MAX
+            assert_eq!(time_ms_to_time_s(time_ms.saturating_add(999)), time_s);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_s_to_utc_string() {
+        assert_eq!(time_s_to_utc_string(0), "1970-01-01T00:00:00+00:00");
+        assert_eq!(time_s_to_utc_string(-1), "1969-12-31T23:59:59+00:00");
+        assert_eq!(
+            time_s_to_utc_string(1_657_886_400),
+            "2022-07-15T12:00:00+00:00"
+        );
+        assert_eq!(
+            time_s_to_utc_string(time_ms_to_time_s(-1)),
+            "1969-12-31T23:59:59+00:00"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "time_s out of range for DateTime<Utc>")]
+    fn test_time_s_to_utc_string_out_of_range() {
+        time_s_to_utc_string(i64::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_utc_now_to_time_s() {
+        let before = crate::utc_now_to_time_ms();
+        let now_s = utc_now_to_time_s();
+        let after = crate::utc_now_to_time_ms();
+        assert!(now_s >= time_ms_to_time_s(before) && now_s <= time_ms_to_time_s(after));
+    }
+}
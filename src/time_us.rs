@@ -0,0 +1,307 @@
+#[cfg(feature = "std")]
+use chrono::{DateTime, SecondsFormat, Utc};
+
+#[cfg(feature = "std")]
+use crate::conversions::{date_time_to_nanos, parse_dt_str};
+#[cfg(feature = "std")]
+use crate::{ParseTrace, TimeConversionError, TzMassaging};
+
+const US_PER_MS: i64 = 1_000;
+const US_PER_SEC: i64 = 1_000_000;
+
+/// Convert time_us, micro-seconds since the Unix epoch, to seconds and
+/// nano-seconds
+///
+/// As with [`time_ms_to_secs_nsecs`](crate::time_ms_to_secs_nsecs) for
+/// negative times the seconds are negative but the nano-seconds are
+/// positive, so -1us is `(-1, 999_999_000)`.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_us_to_secs_nsecs;
+///
+/// assert_eq!(time_us_to_secs_nsecs(1_000_001), (1, 1_000));
+/// assert_eq!(time_us_to_secs_nsecs(-1), (-1, 999_999_000));
+/// ```
+pub fn time_us_to_secs_nsecs(time_us: i64) -> (i64, u32) {
+    // div_euclid/rem_euclid so the micro-seconds are always positive
+    let secs = time_us.div_euclid(US_PER_SEC);
+    let micros = time_us.rem_euclid(US_PER_SEC) as u32;
+
+    (secs, micros * 1_000)
+}
+
+/// Convert time_us to time_ms, rounded to the nearest milli-second with
+/// halves rounded up as [`utc_to_time_ms`](crate::utc_to_time_ms) does,
+/// so -1.5ms is -1ms.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_us_to_time_ms;
+///
+/// assert_eq!(time_us_to_time_ms(1_499), 1);
+/// assert_eq!(time_us_to_time_ms(1_500), 2);
+/// assert_eq!(time_us_to_time_ms(-1_500), -1);
+/// ```
+pub const fn time_us_to_time_ms(time_us: i64) -> i64 {
+    // Can't overflow as the floor is at most i64::MAX / 1000
+    let floor = time_us.div_euclid(US_PER_MS);
+    let rem = time_us.rem_euclid(US_PER_MS);
+    floor + (rem >= US_PER_MS / 2) as i64
+}
+
+/// Convert time_ms to time_us, saturating at i64::MIN and i64::MAX.
+///
+/// This is lossless for every time_ms in the range of DateTime<Utc>,
+/// about +/-262,000 years, as time_us covers about +/-292,000 years.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{time_ms_to_time_us, time_us_to_time_ms};
+///
+/// assert_eq!(time_ms_to_time_us(-1), -1_000);
+/// assert_eq!(time_us_to_time_ms(time_ms_to_time_us(1_657_886_400_123)), 1_657_886_400_123);
+/// ```
+pub const fn time_ms_to_time_us(time_ms: i64) -> i64 {
+    time_ms.saturating_mul(US_PER_MS)
+}
+
+// Round nano-seconds to the nearest micro-second with halves rounded up,
+// the range of DateTime<Utc> always fits in an i64 of micro-seconds
+#[cfg(feature = "std")]
+fn nanos_to_time_us(nanos: i128) -> i64 {
+    const NANOS_PER_US: i128 = 1_000;
+
+    let floor = nanos.div_euclid(NANOS_PER_US);
+    let rem = nanos.rem_euclid(NANOS_PER_US);
+    (floor + (rem >= NANOS_PER_US / 2) as i128) as i64
+}
+
+/// Get Utc::now() and convert to time_us
+///
+/// # Example
+/// ```
+/// use chrono::Utc;
+/// use time_ms_conversions::utc_now_to_time_us;
+///
+/// let before: i64 = Utc::now().timestamp_micros();
+///
+/// assert!(utc_now_to_time_us() >= before);
+/// ```
+#[cfg(feature = "std")]
+pub fn utc_now_to_time_us() -> i64 {
+    utc_to_time_us(&Utc::now())
+}
+
+/// Convert time_us to DateTime<Utc>
+///
+/// # Example
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use time_ms_conversions::{time_us_to_utc, utc_to_time_us};
+///
+/// let dt: DateTime<Utc> = time_us_to_utc(-1);
+/// assert_eq!(dt.to_string(), "1969-12-31 23:59:59.999999 UTC");
+/// assert_eq!(utc_to_time_us(&dt), -1);
+/// ```
+///
+/// # Panics
+///
+/// If time_us is out of the range of DateTime<Utc>, about +/-262,000 years
+#[cfg(feature = "std")]
+pub fn time_us_to_utc(time_us: i64) -> DateTime<Utc> {
+    let (secs, nsecs) = time_us_to_secs_nsecs(time_us);
+    DateTime::from_timestamp(secs, nsecs).expect("time_us out of range for DateTime<Utc>")
+}
+
+/// Convert a DateTime<Utc> to time_us, sub micro-seconds are rounded to
+/// the nearest micro-second with halves rounded up
+///
+/// # Example
+/// ```
+/// use chrono::DateTime;
+/// use time_ms_conversions::utc_to_time_us;
+///
+/// let dt = DateTime::from_timestamp(1, 2_500).unwrap();
+/// assert_eq!(utc_to_time_us(&dt), 1_000_003);
+/// ```
+#[cfg(feature = "std")]
+pub fn utc_to_time_us(date_time: &DateTime<Utc>) -> i64 {
+    nanos_to_time_us(date_time_to_nanos(date_time))
+}
+
+/// Convert time_us to an RFC 3339 string with micro-seconds,
+/// "1970-01-01T00:00:00.000001+00:00"
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_us_to_utc_string;
+///
+/// assert_eq!(time_us_to_utc_string(-1), "1969-12-31T23:59:59.999999+00:00");
+/// ```
+///
+/// # Panics
+///
+/// If time_us is out of the range of DateTime<Utc>, see [`time_us_to_utc`]
+#[cfg(feature = "std")]
+pub fn time_us_to_utc_string(time_us: i64) -> String {
+    time_us_to_utc(time_us).to_rfc3339_opts(SecondsFormat::Micros, false)
+}
+
+/// DateTime string converted to utc time_us, it's
+/// [`dt_str_to_utc_time_ms`](crate::dt_str_to_utc_time_ms) keeping the
+/// micro-seconds
+///
+/// The same formats and `tz_massaging` are accepted, sub micro-seconds are
+/// rounded to the nearest micro-second with halves rounded up.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_us, TzMassaging};
+///
+/// let tus = dt_str_to_utc_time_us("1970-01-01T00:00:01.000002", TzMassaging::CondAddTzUtc);
+/// assert_eq!(tus, Ok(1_000_002));
+///
+/// let tus = dt_str_to_utc_time_us("1969-12-31 16:00:00.000002-08:00", TzMassaging::HasTz);
+/// assert_eq!(tus, Ok(2));
+/// ```
+#[cfg(feature = "std")]
+pub fn dt_str_to_utc_time_us(
+    dt_str: &str,
+    tz_massaging: TzMassaging,
+) -> Result<i64, TimeConversionError> {
+    parse_dt_str(dt_str, tz_massaging, &mut ParseTrace::default())
+        .map(|dtfo| nanos_to_time_us(date_time_to_nanos(&dtfo)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_us_to_secs_nsecs() {
+        assert_eq!(time_us_to_secs_nsecs(-2_000_001), (-3i64, 999_999_000u32));
+        assert_eq!(time_us_to_secs_nsecs(-2_000_000), (-2i64, 0u32));
+        assert_eq!(time_us_to_secs_nsecs(-1_999_999), (-2i64, 1_000u32));
+        assert_eq!(time_us_to_secs_nsecs(-1_000_001), (-2i64, 999_999_000u32));
+        assert_eq!(time_us_to_secs_nsecs(-1_000_000), (-1i64, 0u32));
+        assert_eq!(time_us_to_secs_nsecs(-999_999), (-1i64, 1_000u32));
+        assert_eq!(time_us_to_secs_nsecs(-1), (-1i64, 999_999_000u32));
+        assert_eq!(time_us_to_secs_nsecs(0), (0i64, 0u32));
+        assert_eq!(time_us_to_secs_nsecs(1), (0i64, 1_000u32));
+        assert_eq!(time_us_to_secs_nsecs(999_999), (0i64, 999_999_000u32));
+        assert_eq!(time_us_to_secs_nsecs(1_000_000), (1i64, 0u32));
+    }
+
+    #[test]
+    fn test_time_us_to_secs_nsecs_limits() {
+        assert_eq!(
+            time_us_to_secs_nsecs(i64::MAX),
+            (i64::MAX / 1_000_000, 775_807_000)
+        );
+        assert_eq!(
+            time_us_to_secs_nsecs(i64::MIN),
+            (i64::MIN / 1_000_000 - 1, 224_192_000)
+        );
+    }
+
+    #[test]
+    fn test_time_us_to_time_ms() {
+        assert_eq!(time_us_to_time_ms(0), 0);
+        assert_eq!(time_us_to_time_ms(499), 0);
+        assert_eq!(time_us_to_time_ms(500), 1);
+        assert_eq!(time_us_to_time_ms(1_000), 1);
+        assert_eq!(time_us_to_time_ms(-1), 0);
+        assert_eq!(time_us_to_time_ms(-500), 0);
+        assert_eq!(time_us_to_time_ms(-501), -1);
+        assert_eq!(time_us_to_time_ms(-1_000), -1);
+        assert_eq!(time_us_to_time_ms(i64::MAX), i64::MAX / 1_000 + 1);
+        assert_eq!(time_us_to_time_ms(i64::MIN), i64::MIN / 1_000 - 1);
+    }
+
+    #[test]
+    fn test_time_ms_to_time_us() {
+        for tms in [-1_000_001, -1_000, -1, 0, 1, 999, 1_657_886_400_123] {
+            assert_eq!(time_ms_to_time_us(tms), tms * 1_000);
+            assert_eq!(time_us_to_time_ms(time_ms_to_time_us(tms)), tms);
+        }
+        assert_eq!(time_ms_to_time_us(i64::MAX), i64::MAX);
+        assert_eq!(time_ms_to_time_us(i64::MIN), i64::MIN);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_us_to_utc() {
+        let cases = [
+            (0, "1970-01-01T00:00:00.000000+00:00"),
+            (1, "1970-01-01T00:00:00.000001+00:00"),
+            (-1, "1969-12-31T23:59:59.999999+00:00"),
+            (-1_000_000, "1969-12-31T23:59:59.000000+00:00"),
+            (1_657_886_400_123_456, "2022-07-15T12:00:00.123456+00:00"),
+        ];
+        for (tus, s) in cases {
+            let dt = time_us_to_utc(tus);
+            assert_eq!(utc_to_time_us(&dt), tus);
+            assert_eq!(dt.timestamp_micros(), tus);
+            assert_eq!(time_us_to_utc_string(tus), s);
+            assert_eq!(dt_str_to_utc_time_us(s, TzMassaging::HasTz), Ok(tus));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_utc_to_time_us_rounding() {
+        let at = |secs, nsecs| utc_to_time_us(&DateTime::from_timestamp(secs, nsecs).unwrap());
+        assert_eq!(at(0, 499), 0);
+        assert_eq!(at(0, 500), 1);
+        assert_eq!(at(-1, 999_999_500), 0);
+        assert_eq!(at(-1, 999_999_499), -1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "time_us out of range for DateTime<Utc>")]
+    fn test_time_us_to_utc_out_of_range() {
+        time_us_to_utc(i64::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_dt_str_to_utc_time_us() {
+        use TzMassaging::*;
+
+        let cases = [
+            ("1970-01-01T00:00:00", CondAddTzUtc, 0),
+            ("1970-01-01T00:00:00.000001Z", CondAddTzUtc, 1),
+            ("1970-01-01 00:00:00.0000015", CondAddTzUtc, 2),
+            ("1969-12-31T23:59:59.999999", CondAddTzUtc, -1),
+            ("1969-12-31T16:00:00.5-0800", CondAddTzUtc, 500_000),
+            ("1970-01-01T00:00:00,25+00:00", HasTz, 250_000),
+            ("1970-01-02", CondAddTzUtc, 86_400_000_000),
+        ];
+        for (s, tz_massaging, expected) in cases {
+            assert_eq!(dt_str_to_utc_time_us(s, tz_massaging), Ok(expected), "{s}");
+
+            // Consistent with the time_ms parser
+            assert_eq!(
+                crate::dt_str_to_utc_time_ms(s, tz_massaging),
+                Ok(time_us_to_time_ms(expected)),
+                "{s}"
+            );
+        }
+
+        assert!(dt_str_to_utc_time_us("1970-01-01T00:00:00", HasTz).is_err());
+        assert!(dt_str_to_utc_time_us("bad", CondAddTzUtc).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_utc_now_to_time_us() {
+        let before = Utc::now().timestamp_micros();
+        let now = utc_now_to_time_us();
+        let after = Utc::now().timestamp_micros();
+
+        // now is rounded and after is truncated
+        assert!((before..=after + 1).contains(&now));
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::DtSeparator;
+
+/// The branch a DateTime string took through [`crate::dt_str_to_utc_time_ms`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ParseBranch {
+    /// The separator between the date and time, None for a date without a
+    /// time or if no separator was found
+    pub separator: Option<DtSeparator>,
+
+    /// A time zone, 'Z' or a numeric offset, was detected with
+    /// `CondAddTzUtc` or is required with `HasTz`
+    pub has_offset: bool,
+
+    /// The chrono format string ultimately used, None if parsing failed
+    /// before one was tried
+    pub format: Option<&'static str>,
+
+    /// `CondAddTzUtc` found no time zone so the time is UTC, as if "+0000"
+    /// had been appended
+    pub appended_utc: bool,
+}
+
+/// What [`crate::dt_str_to_utc_time_ms_traced`] did to parse a DateTime
+/// string, for diagnosing inputs that "mostly parse"
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms_traced, DtSeparator, ParseTrace, TzMassaging};
+///
+/// let mut trace = ParseTrace::default();
+/// let tms = dt_str_to_utc_time_ms_traced("1970-01-01T00:00:00", TzMassaging::CondAddTzUtc, &mut trace);
+/// assert_eq!(tms, Ok(0));
+/// assert_eq!(trace.branch.separator, Some(DtSeparator::T));
+/// assert!(trace.branch.appended_utc);
+/// println!("{trace}");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseTrace {
+    pub branch: ParseBranch,
+
+    /// The string was parsed successfully
+    pub ok: bool,
+
+    /// Nano-seconds taken to parse the string
+    pub elapsed_nanos: u64,
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let branch = &self.branch;
+        let separator = branch.separator.map_or("none", DtSeparator::name);
+        let format = branch.format.unwrap_or("none");
+        write!(
+            f,
+            "{} sep={separator} offset={} format=\"{format}\" +0000={} {}ns",
+            if self.ok { "ok" } else { "err" },
+            yes_no(branch.has_offset),
+            yes_no(branch.appended_utc),
+            self.elapsed_nanos,
+        )
+    }
+}
+
+/// A histogram of the [`ParseBranch`]es taken by many [`ParseTrace`]s
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{dt_str_to_utc_time_ms_traced, ParseTrace, ParseTraceStats, TzMassaging};
+///
+/// let mut stats = ParseTraceStats::default();
+/// for dt_str in ["1970-01-01T00:00:00Z", "1970-01-01 00:00:00", "1970-01-01T00:00:01Z"] {
+///     let mut trace = ParseTrace::default();
+///     let _ = dt_str_to_utc_time_ms_traced(dt_str, TzMassaging::CondAddTzUtc, &mut trace);
+///     stats.absorb(&trace);
+/// }
+/// assert_eq!(stats.traces, 3);
+/// assert_eq!(stats.branches.len(), 2);
+/// for (branch, count) in &stats.branches {
+///     println!("{count:5} {branch:?}");
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseTraceStats {
+    /// The number of traces absorbed
+    pub traces: usize,
+
+    /// The number of traces that failed to parse
+    pub failures: usize,
+
+    /// The total nano-seconds of all the traces
+    pub elapsed_nanos: u128,
+
+    /// The number of traces for each branch
+    pub branches: BTreeMap<ParseBranch, usize>,
+}
+
+impl ParseTraceStats {
+    /// Add `trace` to the statistics
+    pub fn absorb(&mut self, trace: &ParseTrace) {
+        self.traces += 1;
+        if !trace.ok {
+            self.failures += 1;
+        }
+        self.elapsed_nanos += trace.elapsed_nanos as u128;
+        *self.branches.entry(trace.branch).or_insert(0) += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{dt_str_to_utc_time_ms, dt_str_to_utc_time_ms_traced, TzMassaging};
+
+    fn traced(dt_str: &str, tz_massaging: TzMassaging) -> ParseTrace {
+        let mut trace = ParseTrace::default();
+        let result = dt_str_to_utc_time_ms_traced(dt_str, tz_massaging, &mut trace);
+        assert_eq!(result, dt_str_to_utc_time_ms(dt_str, tz_massaging));
+        dbg!(trace)
+    }
+
+    fn branch(
+        separator: Option<DtSeparator>,
+        has_offset: bool,
+        format: &'static str,
+        appended_utc: bool,
+    ) -> ParseBranch {
+        ParseBranch {
+            separator,
+            has_offset,
+            format: Some(format),
+            appended_utc,
+        }
+    }
+
+    #[test]
+    fn test_parse_trace_branches() {
+        use DtSeparator::*;
+        use TzMassaging::*;
+
+        let cases = [
+            (
+                "1970-01-01T00:00:00",
+                CondAddTzUtc,
+                branch(Some(T), false, "%Y-%m-%dT%H:%M:%S%.f", true),
+            ),
+            (
+                "1970-01-01T00:00:00Z",
+                CondAddTzUtc,
+                branch(Some(T), true, "%Y-%m-%dT%H:%M:%S%.f%#z", false),
+            ),
+            (
+                "1969-12-31 16:00:00-0800",
+                CondAddTzUtc,
+                branch(Some(Space), true, "%Y-%m-%d %H:%M:%S%.f%#z", false),
+            ),
+            (
+                "1970-01-01 00:00:00+0000",
+                HasTz,
+                branch(Some(Space), true, "%Y-%m-%d %H:%M:%S%.f%#z", false),
+            ),
+            (
+                "1970-01-01T00:00:00",
+                LocalTz,
+                branch(Some(T), false, "%Y-%m-%dT%H:%M:%S%.f", false),
+            ),
+            (
+                "19700101T000000",
+                CondAddTzUtc,
+                branch(Some(T), false, "%Y%m%dT%H%M%S%.f", true),
+            ),
+            (
+                "1970-01-01",
+                CondAddTzUtc,
+                branch(None, false, "%Y-%m-%d", false),
+            ),
+        ];
+        for (dt_str, tz_massaging, expected) in cases {
+            let trace = traced(dt_str, tz_massaging);
+            assert!(trace.ok, "{dt_str}");
+            assert_eq!(trace.branch, expected, "{dt_str}");
+        }
+    }
+
+    #[test]
+    fn test_parse_trace_failures() {
+        // No separator, so no format was tried
+        let trace = traced("1970-01-01_00:00:00", TzMassaging::CondAddTzUtc);
+        assert!(!trace.ok);
+        assert_eq!(trace.branch, ParseBranch::default());
+
+        let trace = traced("1970-01-01T25:00:00", TzMassaging::CondAddTzUtc);
+        assert!(!trace.ok);
+        assert_eq!(trace.branch.separator, Some(DtSeparator::T));
+        assert_eq!(trace.branch.format, Some("%Y-%m-%dT%H:%M:%S%.f"));
+
+        // A previous trace is overwritten
+        let mut trace = traced("1970-01-01T00:00:00", TzMassaging::CondAddTzUtc);
+        let _ = dt_str_to_utc_time_ms_traced("bad", TzMassaging::HasTz, &mut trace);
+        assert!(!trace.ok);
+        assert_eq!(trace.branch.separator, None);
+    }
+
+    #[test]
+    fn test_parse_trace_display() {
+        let mut trace = traced("1970-01-01T00:00:00", TzMassaging::CondAddTzUtc);
+        trace.elapsed_nanos = 1234;
+        assert_eq!(
+            trace.to_string(),
+            "ok sep='T' offset=no format=\"%Y-%m-%dT%H:%M:%S%.f\" +0000=yes 1234ns"
+        );
+
+        let mut trace = traced("1970-01-01_00:00", TzMassaging::HasTz);
+        trace.elapsed_nanos = 5;
+        assert_eq!(
+            trace.to_string(),
+            "err sep=none offset=no format=\"none\" +0000=no 5ns"
+        );
+    }
+
+    #[test]
+    fn test_parse_trace_stats_absorb() {
+        let mut stats = ParseTraceStats::default();
+        let rows = [
+            "1970-01-01T00:00:00",
+            "1970-01-01T00:00:01",
+            "1970-01-01T00:00:02Z",
+            "1970-01-01 00:00:03",
+            "1970-01-01X00:00:04",
+        ];
+        for dt_str in rows {
+            let trace = traced(dt_str, TzMassaging::CondAddTzUtc);
+            stats.absorb(&trace);
+        }
+        dbg!(&stats);
+        assert_eq!(stats.traces, 5);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.branches.len(), 4);
+        assert_eq!(stats.branches.values().sum::<usize>(), 5);
+
+        let appended_t = branch(Some(DtSeparator::T), false, "%Y-%m-%dT%H:%M:%S%.f", true);
+        assert_eq!(stats.branches[&appended_t], 2);
+        assert_eq!(stats.branches[&ParseBranch::default()], 1);
+    }
+}
@@ -0,0 +1,200 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{MS_PER_DAY, MS_PER_HOUR, MS_PER_MIN, MS_PER_SEC};
+
+// Proleptic Gregorian (year, month, day) of days since 1970-01-01,
+// from Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+
+    // Months start in March so the leap day is last
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    (year, month, day)
+}
+
+// Append `value` as `width` zero padded digits
+fn push_digits(s: &mut String, value: u32, width: u32) {
+    for place in (0..width).rev() {
+        let digit = value / 10u32.pow(place) % 10;
+        s.push(char::from(b'0' + digit as u8));
+    }
+}
+
+// Append the year as chrono's %Y does, 4 digits with a sign outside 0000-9999
+fn push_year(s: &mut String, year: i64) {
+    if year > 9999 {
+        s.push('+');
+    } else if year < 0 {
+        s.push('-');
+    }
+    let abs_year = year.unsigned_abs() as u32;
+    let width = abs_year.checked_ilog10().unwrap_or(0) + 1;
+    push_digits(s, abs_year, width.max(4));
+}
+
+// Append the time_ms_to_utc_string of time_ms to `s`
+fn push_utc_string(s: &mut String, time_ms: i64) {
+    let (year, month, day) = civil_from_days(time_ms.div_euclid(MS_PER_DAY));
+    let ms_of_day = time_ms.rem_euclid(MS_PER_DAY) as u32;
+    let ms_per_min = MS_PER_MIN as u32;
+    let ms_per_sec = MS_PER_SEC as u32;
+
+    push_year(s, year);
+    s.push('-');
+    push_digits(s, month, 2);
+    s.push('-');
+    push_digits(s, day, 2);
+    s.push('T');
+    push_digits(s, ms_of_day / MS_PER_HOUR as u32, 2);
+    s.push(':');
+    push_digits(s, ms_of_day / ms_per_min % 60, 2);
+    s.push(':');
+    push_digits(s, ms_of_day / ms_per_sec % 60, 2);
+    s.push('.');
+    push_digits(s, ms_of_day % ms_per_sec, 3);
+    s.push_str("+00:00");
+}
+
+/// Convert each time_ms to a string just as `time_ms_to_utc_string` does,
+/// "1970-01-01T00:00:00.000+00:00"
+///
+/// The strings are built with integer arithmetic rather than chrono's
+/// formatting, about twice as fast for large slices, so this only needs
+/// the `alloc` feature and handles the full range of time_ms.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_slice_to_utc_strings;
+///
+/// assert_eq!(
+///     time_ms_slice_to_utc_strings(&[0, -1]),
+///     vec!["1970-01-01T00:00:00.000+00:00", "1969-12-31T23:59:59.999+00:00"]
+/// );
+/// ```
+pub fn time_ms_slice_to_utc_strings(times: &[i64]) -> Vec<String> {
+    let mut strings = Vec::with_capacity(times.len());
+    time_ms_slice_to_utc_strings_into(times, &mut strings);
+
+    strings
+}
+
+/// Convert each time_ms to a string into `strings`, replacing its contents
+///
+/// The Strings already in `strings` are overwritten in place, so reusing
+/// the same Vec for each batch avoids allocating once they're big enough.
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::time_ms_slice_to_utc_strings_into;
+///
+/// let mut strings = Vec::new();
+/// for batch in [[0, 1], [2, 3]] {
+///     time_ms_slice_to_utc_strings_into(&batch, &mut strings);
+/// }
+/// assert_eq!(strings, vec!["1970-01-01T00:00:00.002+00:00", "1970-01-01T00:00:00.003+00:00"]);
+/// ```
+pub fn time_ms_slice_to_utc_strings_into(times: &[i64], strings: &mut Vec<String>) {
+    // "YYYY-MM-DDTHH:MM:SS.mmm+00:00"
+    const UTC_STRING_LEN: usize = 29;
+
+    strings.truncate(times.len());
+    let reused = strings.len();
+    for (s, &time_ms) in strings.iter_mut().zip(times) {
+        s.clear();
+        push_utc_string(s, time_ms);
+    }
+    for &time_ms in &times[reused..] {
+        let mut s = String::with_capacity(UTC_STRING_LEN);
+        push_utc_string(&mut s, time_ms);
+        strings.push(s);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    #[test]
+    fn test_time_ms_slice_to_utc_strings() {
+        let times = [
+            0,
+            -1,
+            1_657_886_400_123,
+            951_782_400_000,
+            253_402_300_799_999,
+            253_402_300_800_000,
+            -62_167_219_200_001,
+            i64::MAX,
+            i64::MIN,
+        ];
+        assert_eq!(
+            time_ms_slice_to_utc_strings(&times),
+            vec![
+                "1970-01-01T00:00:00.000+00:00",
+                "1969-12-31T23:59:59.999+00:00",
+                "2022-07-15T12:00:00.123+00:00",
+                "2000-02-29T00:00:00.000+00:00",
+                "9999-12-31T23:59:59.999+00:00",
+                "+10000-01-01T00:00:00.000+00:00",
+                "-0001-12-31T23:59:59.999+00:00",
+                "+292278994-08-17T07:12:55.807+00:00",
+                "-292275055-05-16T16:47:04.192+00:00",
+            ]
+        );
+
+        assert!(time_ms_slice_to_utc_strings(&[]).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_ms_slice_to_utc_strings_vs_chrono() {
+        use crate::time_ms_to_utc_string;
+
+        // Spread over chrono's range of about +/-262,000 years
+        let mut seed: u64 = 0x1234_5678_9abc_def0;
+        let times: Vec<i64> = (0..10_000)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                (seed % 16_000_000_000_000_000) as i64 - 8_000_000_000_000_000
+            })
+            .collect();
+        for (s, &tms) in time_ms_slice_to_utc_strings(&times).iter().zip(&times) {
+            assert_eq!(*s, time_ms_to_utc_string(tms));
+        }
+    }
+
+    #[test]
+    fn test_time_ms_slice_to_utc_strings_into() {
+        let mut strings = vec!["stale".to_string(); 3];
+        time_ms_slice_to_utc_strings_into(&[0, 1], &mut strings);
+        assert_eq!(
+            strings,
+            vec![
+                "1970-01-01T00:00:00.000+00:00",
+                "1970-01-01T00:00:00.001+00:00"
+            ]
+        );
+
+        // Growing reuses the existing Strings and adds new ones
+        let reused = strings[0].as_ptr();
+        time_ms_slice_to_utc_strings_into(&[2, 3, 4], &mut strings);
+        assert_eq!(strings.len(), 3);
+        assert_eq!(strings[0].as_ptr(), reused);
+        assert_eq!(strings[2], "1970-01-01T00:00:00.004+00:00");
+
+        time_ms_slice_to_utc_strings_into(&[], &mut strings);
+        assert!(strings.is_empty());
+    }
+}
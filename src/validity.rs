@@ -0,0 +1,213 @@
+use crate::{time_ms_add_saturating, time_ms_sub_saturating};
+
+/// The result of checking time_ms against a validity window, see
+/// [`is_valid_at`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidityCheck {
+    /// Within the window
+    Valid,
+
+    /// Before the window, `early_by_ms` until it's valid
+    NotYetValid { early_by_ms: i64 },
+
+    /// After the window, expired `late_by_ms` ago
+    Expired { late_by_ms: i64 },
+}
+
+/// The bounds of the validity window the clock skew is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SkewSide {
+    /// Only not_before, tolerating an issuer whose clock is ahead while
+    /// enforcing expiry strictly
+    NotBefore,
+
+    /// Both not_before and not_after
+    Both,
+}
+
+/// Check `now_ms` against the validity window of a certificate or token,
+/// `not_before_ms..=not_after_ms`, tolerating `skew_ms` of clock skew on
+/// the bounds selected by `skew_side`.
+///
+/// Both bounds are inclusive, so `now_ms` equal to either is Valid, and
+/// a None bound is unbounded. Skew widens the window, not_before is
+/// moved earlier and, for [`SkewSide::Both`], not_after later, saturating
+/// at i64::MIN and i64::MAX. `early_by_ms` and `late_by_ms` are measured
+/// from the widened bounds so they're always positive.
+///
+/// # Panics
+///
+/// If `skew_ms < 0`
+///
+/// # Example
+/// ```
+/// use time_ms_conversions::{is_valid_at, SkewSide, ValidityCheck};
+///
+/// let (not_before, not_after) = (Some(10_000), Some(20_000));
+///
+/// // 3s early but within the 5s of skew
+/// assert_eq!(is_valid_at(not_before, not_after, 7_000, 5_000, SkewSide::NotBefore), ValidityCheck::Valid);
+///
+/// // Expiry is enforced strictly
+/// assert_eq!(
+///     is_valid_at(not_before, not_after, 20_001, 5_000, SkewSide::NotBefore),
+///     ValidityCheck::Expired { late_by_ms: 1 }
+/// );
+/// assert_eq!(is_valid_at(not_before, not_after, 20_001, 5_000, SkewSide::Both), ValidityCheck::Valid);
+/// ```
+pub fn is_valid_at(
+    not_before_ms: Option<i64>,
+    not_after_ms: Option<i64>,
+    now_ms: i64,
+    skew_ms: i64,
+    skew_side: SkewSide,
+) -> ValidityCheck {
+    assert!(skew_ms >= 0, "is_valid_at: skew_ms must be >= 0");
+
+    if let Some(not_before_ms) = not_before_ms {
+        let earliest = time_ms_sub_saturating(not_before_ms, skew_ms);
+        if now_ms < earliest {
+            // i128 as the difference can exceed i64::MAX, saturating there
+            return ValidityCheck::NotYetValid {
+                early_by_ms: (earliest as i128 - now_ms as i128).min(i64::MAX as i128) as i64,
+            };
+        }
+    }
+
+    if let Some(not_after_ms) = not_after_ms {
+        let latest = match skew_side {
+            SkewSide::NotBefore => not_after_ms,
+            SkewSide::Both => time_ms_add_saturating(not_after_ms, skew_ms),
+        };
+        if now_ms > latest {
+            return ValidityCheck::Expired {
+                late_by_ms: (now_ms as i128 - latest as i128).min(i64::MAX as i128) as i64,
+            };
+        }
+    }
+
+    ValidityCheck::Valid
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use SkewSide::*;
+    use ValidityCheck::*;
+
+    const NOT_BEFORE: i64 = 10_000;
+    const NOT_AFTER: i64 = 20_000;
+    const SKEW: i64 = 5_000;
+
+    #[test]
+    fn test_is_valid_at_both_bounds() {
+        let (nb, na) = (Some(NOT_BEFORE), Some(NOT_AFTER));
+        let cases = [
+            // now, skew, skew_side, expected
+            (NOT_BEFORE - 1, 0, NotBefore, NotYetValid { early_by_ms: 1 }),
+            (NOT_BEFORE, 0, NotBefore, Valid),
+            (15_000, 0, NotBefore, Valid),
+            (NOT_AFTER, 0, NotBefore, Valid),
+            (NOT_AFTER + 1, 0, NotBefore, Expired { late_by_ms: 1 }),
+            (NOT_BEFORE - 1, 0, Both, NotYetValid { early_by_ms: 1 }),
+            (NOT_AFTER + 1, 0, Both, Expired { late_by_ms: 1 }),
+            // Skew on not_before only
+            (
+                NOT_BEFORE - SKEW - 1,
+                SKEW,
+                NotBefore,
+                NotYetValid { early_by_ms: 1 },
+            ),
+            (NOT_BEFORE - SKEW, SKEW, NotBefore, Valid),
+            (NOT_BEFORE - 1, SKEW, NotBefore, Valid),
+            (NOT_AFTER, SKEW, NotBefore, Valid),
+            (NOT_AFTER + 1, SKEW, NotBefore, Expired { late_by_ms: 1 }),
+            // Skew on both
+            (
+                NOT_BEFORE - SKEW - 1,
+                SKEW,
+                Both,
+                NotYetValid { early_by_ms: 1 },
+            ),
+            (NOT_BEFORE - SKEW, SKEW, Both, Valid),
+            (NOT_AFTER + 1, SKEW, Both, Valid),
+            (NOT_AFTER + SKEW, SKEW, Both, Valid),
+            (NOT_AFTER + SKEW + 1, SKEW, Both, Expired { late_by_ms: 1 }),
+            (0, SKEW, Both, NotYetValid { early_by_ms: 5_000 }),
+            (30_000, SKEW, Both, Expired { late_by_ms: 5_000 }),
+        ];
+        for (now, skew, skew_side, expected) in cases {
+            assert_eq!(
+                is_valid_at(nb, na, now, skew, skew_side),
+                expected,
+                "now={now} skew={skew} {skew_side:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_valid_at_unbounded() {
+        for skew_side in [NotBefore, Both] {
+            for skew in [0, SKEW] {
+                // No bounds is always valid
+                for now in [i64::MIN, -1, 0, NOT_BEFORE, NOT_AFTER, i64::MAX] {
+                    assert_eq!(is_valid_at(None, None, now, skew, skew_side), Valid);
+                }
+
+                // Only not_before
+                let nb = Some(NOT_BEFORE);
+                assert_eq!(
+                    is_valid_at(nb, None, NOT_BEFORE - skew - 1, skew, skew_side),
+                    NotYetValid { early_by_ms: 1 }
+                );
+                assert_eq!(
+                    is_valid_at(nb, None, NOT_BEFORE - skew, skew, skew_side),
+                    Valid
+                );
+                assert_eq!(is_valid_at(nb, None, i64::MAX, skew, skew_side), Valid);
+
+                // Only not_after
+                let na = Some(NOT_AFTER);
+                let latest = if skew_side == Both {
+                    NOT_AFTER + skew
+                } else {
+                    NOT_AFTER
+                };
+                assert_eq!(is_valid_at(None, na, i64::MIN, skew, skew_side), Valid);
+                assert_eq!(is_valid_at(None, na, latest, skew, skew_side), Valid);
+                assert_eq!(
+                    is_valid_at(None, na, latest + 1, skew, skew_side),
+                    Expired { late_by_ms: 1 }
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_valid_at_limits() {
+        // The skew saturates rather than overflowing
+        assert_eq!(
+            is_valid_at(Some(i64::MIN), Some(i64::MAX), 0, i64::MAX, Both),
+            Valid
+        );
+        assert_eq!(
+            is_valid_at(Some(i64::MAX), None, i64::MIN, 0, NotBefore),
+            NotYetValid {
+                early_by_ms: i64::MAX
+            }
+        );
+        assert_eq!(
+            is_valid_at(None, Some(i64::MIN), i64::MAX, 0, NotBefore),
+            Expired {
+                late_by_ms: i64::MAX
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is_valid_at: skew_ms must be >= 0")]
+    fn test_is_valid_at_negative_skew() {
+        is_valid_at(Some(NOT_BEFORE), Some(NOT_AFTER), 0, -1, NotBefore);
+    }
+}